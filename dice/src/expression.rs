@@ -0,0 +1,310 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use crate::DiceError;
+
+/// The most sides a die can have.
+pub const MAX_SIDES: u64 = 1000;
+/// The most dice an expression can roll.
+pub const MAX_DICE: u64 = 100;
+/// The most the constants of an expression can add up to, ignoring their signs.
+pub const MAX_CONSTANT: u64 = 1_000_000;
+
+/// A single term of a dice expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Term {
+    /// A number of dice with the same number of sides, like `3d6`,
+    /// which is subtracted when the count is negative.
+    Dice { count: i64, sides: u64 },
+    /// A fixed number that is added, or subtracted when negative.
+    Constant(i64),
+}
+
+/// A sum of dice and constants, like `3d6+2` or `2d6 - 1d4 + 1`.
+///
+/// Expressions are read case-insensitively, ignoring whitespace, and a die
+/// without a count (`d6`) is rolled once.
+///
+/// # Example
+/// ```
+/// # use dice::expression::DiceExpression;
+/// let expression: DiceExpression = "d6 + 2d6+3 - 1".parse().unwrap();
+/// assert_eq!(expression.to_string(), "3d6+2");
+/// assert_eq!(expression.pretty(), "3d6 + 2");
+/// assert_eq!(expression.minimum(), 5);
+/// assert_eq!(expression.maximum(), 20);
+/// assert_eq!(expression.average(), 12.5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceExpression {
+    terms: Vec<Term>,
+}
+
+impl DiceExpression {
+    /// The terms of the expression, in the order they were written.
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// Combines dice with the same sides and all constants, with dice
+    /// ordered from the most to the fewest sides, and the constant last.
+    pub fn normalized(&self) -> DiceExpression {
+        let mut dice: BTreeMap<u64, i64> = BTreeMap::new();
+        let mut constant = 0;
+        for term in &self.terms {
+            match term {
+                Term::Dice { count, sides } => *dice.entry(*sides).or_default() += count,
+                Term::Constant(value) => constant += value,
+            }
+        }
+        let mut terms: Vec<Term> = dice
+            .into_iter()
+            .rev()
+            .filter(|(_, count)| *count != 0)
+            .map(|(sides, count)| Term::Dice { count, sides })
+            .collect();
+        if constant != 0 || terms.is_empty() {
+            terms.push(Term::Constant(constant));
+        }
+        DiceExpression { terms }
+    }
+
+    /// Writes the normalized expression with spaces around the operators, like `3d6 + 2`.
+    pub fn pretty(&self) -> String {
+        self.write_normalized(" + ", " - ")
+    }
+
+    /// The lowest result of the expression.
+    pub fn minimum(&self) -> i64 {
+        self.terms
+            .iter()
+            .map(|term| match *term {
+                Term::Dice { count, sides } if count < 0 => count * sides as i64,
+                Term::Dice { count, .. } => count,
+                Term::Constant(value) => value,
+            })
+            .sum()
+    }
+
+    /// The highest result of the expression.
+    pub fn maximum(&self) -> i64 {
+        self.terms
+            .iter()
+            .map(|term| match *term {
+                Term::Dice { count, .. } if count < 0 => count,
+                Term::Dice { count, sides } => count * sides as i64,
+                Term::Constant(value) => value,
+            })
+            .sum()
+    }
+
+    /// The average result of the expression.
+    pub fn average(&self) -> f64 {
+        self.terms
+            .iter()
+            .map(|term| match *term {
+                Term::Dice { count, sides } => count as f64 * (sides as f64 + 1.0) / 2.0,
+                Term::Constant(value) => value as f64,
+            })
+            .sum()
+    }
+
+    fn write_normalized(&self, plus: &str, minus: &str) -> String {
+        let mut result = String::new();
+        for (index, term) in self.normalized().terms.iter().enumerate() {
+            let (negative, text) = match *term {
+                Term::Dice { count, sides } => (count < 0, format!("{}d{}", count.abs(), sides)),
+                Term::Constant(value) => (value < 0, value.abs().to_string()),
+            };
+            match (index, negative) {
+                (0, true) => result.push('-'),
+                (0, false) => {}
+                (_, true) => result.push_str(minus),
+                (_, false) => result.push_str(plus),
+            }
+            result.push_str(&text);
+        }
+        result
+    }
+}
+
+impl Display for DiceExpression {
+    /// Writes the normalized expression, like `3d6+2`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.write_normalized("+", "-"))
+    }
+}
+
+impl FromStr for DiceExpression {
+    type Err = DiceError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let compact: String = value
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        if compact.is_empty() {
+            return Err(DiceError::Empty);
+        }
+        let mut terms = Vec::new();
+        let mut dice = 0;
+        let mut constants: u64 = 0;
+        let mut rest = compact.as_str();
+        while !rest.is_empty() {
+            let (sign, unsigned) = match rest.strip_prefix('-') {
+                Some(unsigned) => (-1, unsigned),
+                None => (1, rest.strip_prefix('+').unwrap_or(rest)),
+            };
+            let end = unsigned.find(['+', '-']).unwrap_or(unsigned.len());
+            let text = &unsigned[..end];
+            rest = &unsigned[end..];
+            let term = parse_term(text)?;
+            terms.push(match term {
+                Term::Dice { count, sides } => {
+                    dice += count as u64;
+                    Term::Dice {
+                        count: sign * count,
+                        sides,
+                    }
+                }
+                Term::Constant(constant) => {
+                    constants += constant.unsigned_abs();
+                    if constants > MAX_CONSTANT {
+                        return Err(DiceError::ConstantTooLarge(value.to_string(), constants));
+                    }
+                    Term::Constant(sign * constant)
+                }
+            });
+        }
+        if dice > MAX_DICE {
+            return Err(DiceError::TooManyDice(value.to_string(), dice));
+        }
+        Ok(DiceExpression { terms })
+    }
+}
+
+/// Reads a term without it's sign, like `3d6`, `d6` or `2`.
+fn parse_term(text: &str) -> Result<Term, DiceError> {
+    let invalid = || DiceError::InvalidTerm(text.to_string());
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    match text.split_once('d') {
+        Some((count, sides)) => {
+            if !(count.is_empty() || is_number(count)) || !is_number(sides) {
+                return Err(invalid());
+            }
+            let count: u64 = if count.is_empty() {
+                1
+            } else {
+                count.parse().map_err(|_| invalid())?
+            };
+            let sides: u64 = sides.parse().map_err(|_| invalid())?;
+            if sides == 0 || sides > MAX_SIDES {
+                return Err(DiceError::InvalidSides(text.to_string(), sides));
+            }
+            if count > MAX_DICE {
+                return Err(DiceError::TooManyDice(text.to_string(), count));
+            }
+            Ok(Term::Dice {
+                count: count as i64,
+                sides,
+            })
+        }
+        None if is_number(text) => {
+            // Only numbers too large for a u64 fail to parse
+            let value: u64 = text.parse().unwrap_or(u64::MAX);
+            if value > MAX_CONSTANT {
+                return Err(DiceError::ConstantTooLarge(text.to_string(), value));
+            }
+            Ok(Term::Constant(value as i64))
+        }
+        None => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_terms_with_signs() {
+        assert_eq!(
+            "2D6 - d4 + 3".parse::<DiceExpression>().unwrap().terms(),
+            &[
+                Term::Dice { count: 2, sides: 6 },
+                Term::Dice {
+                    count: -1,
+                    sides: 4
+                },
+                Term::Constant(3)
+            ]
+        );
+        assert_eq!(
+            "-1+d6".parse::<DiceExpression>().unwrap().terms(),
+            &[Term::Constant(-1), Term::Dice { count: 1, sides: 6 }]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!("".parse::<DiceExpression>(), Err(DiceError::Empty));
+        assert_eq!(
+            "3d".parse::<DiceExpression>(),
+            Err(DiceError::InvalidTerm("3d".to_string()))
+        );
+        assert_eq!(
+            "3d6++2".parse::<DiceExpression>(),
+            Err(DiceError::InvalidTerm("".to_string()))
+        );
+        assert_eq!(
+            "2x6".parse::<DiceExpression>(),
+            Err(DiceError::InvalidTerm("2x6".to_string()))
+        );
+        assert_eq!(
+            "1d0".parse::<DiceExpression>(),
+            Err(DiceError::InvalidSides("1d0".to_string(), 0))
+        );
+        assert_eq!(
+            "60d6+60d6".parse::<DiceExpression>(),
+            Err(DiceError::TooManyDice("60d6+60d6".to_string(), 120))
+        );
+        assert_eq!(
+            "9223372036854775807+1".parse::<DiceExpression>(),
+            Err(DiceError::ConstantTooLarge(
+                "9223372036854775807".to_string(),
+                9223372036854775807
+            ))
+        );
+        assert_eq!(
+            "1d6+600000-600000".parse::<DiceExpression>(),
+            Err(DiceError::ConstantTooLarge(
+                "1d6+600000-600000".to_string(),
+                1_200_000
+            ))
+        );
+    }
+
+    #[test]
+    fn normalizes_expressions() {
+        let normalize = |e: &str| e.parse::<DiceExpression>().unwrap().to_string();
+
+        assert_eq!(normalize("2 + 1d6 + 2d6"), "3d6+2");
+        assert_eq!(normalize("1d4 + 2d8 - 3"), "2d8+1d4-3");
+        assert_eq!(normalize("-d6+1"), "-1d6+1");
+        assert_eq!(normalize("1d6-1d6"), "0");
+        assert_eq!(
+            "2d6-1d4-1".parse::<DiceExpression>().unwrap().pretty(),
+            "2d6 - 1d4 - 1"
+        );
+    }
+
+    #[test]
+    fn computes_minimum_maximum_and_average() {
+        let expression: DiceExpression = "2d6 - 1d4 + 1".parse().unwrap();
+
+        assert_eq!(expression.minimum(), -1);
+        assert_eq!(expression.maximum(), 12);
+        assert_eq!(expression.average(), 5.5);
+    }
+}
@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+/// The errors that can happen when reading a dice expression.
+#[derive(Error, Debug, PartialEq)]
+pub enum DiceError {
+    #[error("the dice expression is empty")]
+    Empty,
+    #[error("`{0}` is not a valid term of a dice expression")]
+    InvalidTerm(String),
+    #[error("dice must have between 1 and {max} sides, but `{0}` has {1}", max = expression::MAX_SIDES)]
+    InvalidSides(String, u64),
+    #[error("expressions can roll at most {max} dice, but `{0}` rolls {1}", max = expression::MAX_DICE)]
+    TooManyDice(String, u64),
+    #[error("constants can add up to at most {max}, but `{0}` adds up to {1}", max = expression::MAX_CONSTANT)]
+    ConstantTooLarge(String, u64),
+}
+
+/// Reads and writes dice expressions, like `3d6+2`.
+pub mod expression;
+/// Computes the probabilities of the results of dice expressions.
+pub mod probability;
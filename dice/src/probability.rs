@@ -0,0 +1,158 @@
+use crate::expression::{DiceExpression, Term};
+
+/// The chance of a single result of an expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outcome {
+    /// The result of the roll.
+    pub total: i64,
+    /// The chance of rolling exactly the total, between 0 and 1.
+    pub chance: f64,
+    /// The chance of rolling the total or more, between 0 and 1.
+    pub at_least: f64,
+    /// The chance of rolling the total or less, between 0 and 1.
+    pub at_most: f64,
+}
+
+impl DiceExpression {
+    /// The chance of rolling every possible result, ordered by the result.
+    ///
+    /// # Example
+    /// ```
+    /// # use dice::expression::DiceExpression;
+    /// let table = "2d6".parse::<DiceExpression>().unwrap().probabilities();
+    /// assert_eq!(table.len(), 11);
+    /// assert_eq!(table[5].total, 7);
+    /// assert!((table[5].chance - 6.0 / 36.0).abs() < 1e-9);
+    /// ```
+    pub fn probabilities(&self) -> Vec<Outcome> {
+        // The chance of every total, starting from the lowest one.
+        let mut lowest = 0;
+        let mut chances = vec![1.0];
+        for term in self.normalized().terms() {
+            match *term {
+                Term::Dice { count, sides } => {
+                    for _ in 0..count.abs() {
+                        chances = roll_die(&chances, sides as usize);
+                        lowest += if count > 0 { 1 } else { -(sides as i64) };
+                    }
+                }
+                Term::Constant(value) => lowest += value,
+            }
+        }
+        let mut at_most = 0.0;
+        let mut outcomes: Vec<Outcome> = chances
+            .into_iter()
+            .enumerate()
+            .map(|(index, chance)| {
+                at_most += chance;
+                Outcome {
+                    total: lowest + index as i64,
+                    chance,
+                    at_least: 0.0,
+                    at_most: at_most.min(1.0),
+                }
+            })
+            .collect();
+        let mut at_least = 0.0;
+        for outcome in outcomes.iter_mut().rev() {
+            at_least += outcome.chance;
+            outcome.at_least = at_least.min(1.0);
+        }
+        outcomes
+    }
+
+    /// The chance of rolling a target number or more, between 0 and 1.
+    pub fn chance_at_least(&self, target: i64) -> f64 {
+        if target <= self.minimum() {
+            return 1.0;
+        }
+        self.probabilities()
+            .iter()
+            .find(|outcome| outcome.total >= target)
+            .map_or(0.0, |outcome| outcome.at_least)
+    }
+
+    /// The chance of rolling a target number or less, between 0 and 1.
+    pub fn chance_at_most(&self, target: i64) -> f64 {
+        1.0 - self.chance_at_least(target + 1)
+    }
+}
+
+/// Adds a die to the chances of every total, where the first total
+/// becomes the first total plus one.
+fn roll_die(chances: &[f64], sides: usize) -> Vec<f64> {
+    let mut result = Vec::with_capacity(chances.len() + sides - 1);
+    let mut window = 0.0;
+    for index in 0..chances.len() + sides - 1 {
+        window += chances.get(index).copied().unwrap_or(0.0);
+        if index >= sides {
+            window -= chances[index - sides];
+        }
+        result.push((window / sides as f64).max(0.0));
+    }
+    result
+}
+
+/// Writes a chance as a percentage, like `58.3%`.
+pub fn format_chance(chance: f64, decimals: usize) -> String {
+    format!("{:.*}%", decimals, chance * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn expression(value: &str) -> DiceExpression {
+        value.parse().unwrap()
+    }
+
+    fn round(value: f64) -> f64 {
+        (value * 1e6).round() / 1e6
+    }
+
+    #[test]
+    fn single_die_is_uniform() {
+        let outcomes = expression("1d6+1").probabilities();
+
+        assert_eq!(
+            outcomes.iter().map(|o| o.total).collect::<Vec<i64>>(),
+            vec![2, 3, 4, 5, 6, 7]
+        );
+        assert!(outcomes.iter().all(|o| round(o.chance) == round(1.0 / 6.0)));
+        assert_eq!(round(outcomes[0].at_least), 1.0);
+        assert_eq!(round(outcomes[5].at_most), 1.0);
+    }
+
+    #[test]
+    fn subtracted_dice_lower_the_results() {
+        let outcomes = expression("1d6-1d6").probabilities();
+
+        assert_eq!(outcomes.first().unwrap().total, 0);
+        assert_eq!(outcomes.len(), 1);
+
+        let outcomes = expression("1d4-1d2").probabilities();
+        assert_eq!(
+            outcomes.iter().map(|o| o.total).collect::<Vec<i64>>(),
+            vec![-1, 0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn computes_chances_of_targets() {
+        let three_dice = expression("3d6");
+
+        assert_eq!(round(three_dice.chance_at_least(3)), 1.0);
+        assert_eq!(round(three_dice.chance_at_least(11)), 0.5);
+        assert_eq!(round(three_dice.chance_at_least(18)), round(1.0 / 216.0));
+        assert_eq!(three_dice.chance_at_least(19), 0.0);
+        assert_eq!(round(three_dice.chance_at_most(10)), 0.5);
+    }
+
+    #[test]
+    fn formats_chances_as_percentages() {
+        assert_eq!(format_chance(0.58333, 1), "58.3%");
+        assert_eq!(format_chance(1.0, 0), "100%");
+    }
+}
@@ -1,7 +1,7 @@
 use self::{json::Json, text::Text, yaml::Yaml};
 use crate::{FileDataError, FileTypeDataReader};
 use serde_json::Value;
-use std::{ffi::OsStr, ops::Deref, path::Path};
+use std::{ffi::OsStr, path::Path};
 use strum_macros::{EnumString, EnumVariantNames};
 use tracing::instrument;
 
@@ -40,7 +40,7 @@ pub trait FileDataType {
 
 impl<T: AsRef<Path>> FileDataType for T {
     fn try_get_file_type(&self) -> Result<FileType, FileDataError> {
-        let path = self.deref().as_ref();
+        let path = self.as_ref();
         match path.extension().and_then(OsStr::to_str) {
             Some(extension) => match extension {
                 "json" => Ok(FileType::JSON),
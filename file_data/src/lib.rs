@@ -1,7 +1,7 @@
 use file_type::{FileDataType, FileType};
 use serde_json::Value;
 use std::error::Error;
-use std::{ops::Deref, path::Path};
+use std::path::Path;
 use strum::VariantNames;
 use strum_macros::EnumIs;
 use thiserror::Error;
@@ -48,10 +48,10 @@ pub trait FileData {
 impl<T: AsRef<Path>> FileData for T {
     #[instrument(
         skip(self),
-        fields(path=self.deref().as_ref().to_str().expect("Path should be a valid UTF-8 String."))
+        fields(path=self.as_ref().to_str().expect("Path should be a valid UTF-8 String."))
     )]
     fn try_read_file(&self) -> Result<Value, FileDataError> {
-        let path: &Path = self.deref().as_ref();
+        let path: &Path = self.as_ref();
         let file_type = self.try_get_file_type()?;
         file_type.try_read_file(path)
     }
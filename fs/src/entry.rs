@@ -1,12 +1,9 @@
-use std::{
-    ops::Deref,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use path_utils::{children::ChildrenPaths, name::NamePaths};
 use tracing::{debug, instrument};
 
-use crate::{RENDERING_DIRECTORY, UNDERSCORE_FILE_NAME};
+use crate::{PARTIALS_DIRECTORY, RENDERING_DIRECTORY, UNDERSCORE_FILE_NAME};
 
 /// A collection of one or more file system resources that corresponds to a single data value.
 #[derive(Debug, PartialEq, Clone)]
@@ -21,11 +18,13 @@ pub enum Entry {
     },
     /// The data object is represented by a sparse directory,
     /// containing a root file and zero or more additional files for data,
-    /// as well as zero or more additional files for rendering templates.
+    /// as well as zero or more additional files for rendering templates
+    /// and for the partial templates they share.
     RenderingDirectory {
         root_file: PathBuf,
         extra_files: Vec<PathBuf>,
         rendering_files: Vec<PathBuf>,
+        partial_files: Vec<PathBuf>,
     },
 }
 
@@ -40,13 +39,13 @@ pub trait EntryFromNamedPath {
 impl<T: AsRef<Path>> EntryFromNamedPath for T {
     #[instrument(skip(self))]
     fn has_entry_named(&self, name: String) -> Option<Entry> {
-        let path: &Path = self.deref().as_ref();
+        let path: &Path = self.as_ref();
         path.get_first_child_named(&name).and_then(|c| c.to_entry())
     }
 
     #[instrument(skip(self))]
     fn to_entry(&self) -> Option<Entry> {
-        let path: &Path = self.deref().as_ref();
+        let path: &Path = self.as_ref();
         if path.is_file() {
             debug!("Path is a file. Mapping to Entry::File.");
             Some(Entry::File(path.to_path_buf()))
@@ -69,6 +68,12 @@ impl<T: AsRef<Path>> EntryFromNamedPath for T {
                                 .into_iter()
                                 .filter(|e| e.is_file())
                                 .collect(),
+                            partial_files: rendering_directory
+                                .join(PARTIALS_DIRECTORY)
+                                .get_children()
+                                .into_iter()
+                                .filter(|e| e.is_file())
+                                .collect(),
                         })
                     } else {
                         debug!("Path is a directory with an UNDERSCORE file. Mapping to Entry::Directory.");
@@ -168,13 +173,16 @@ mod tests {
 
         let rendering_dir = create_test_directory(&some_dir.join(RENDERING_DIRECTORY));
         let rendering_template = create_test_file(&rendering_dir.join("md.hjs"), "");
+        let partials_dir = create_test_directory(&rendering_dir.join(PARTIALS_DIRECTORY));
+        let partial_template = create_test_file(&partials_dir.join("statBlock.hjs"), "");
 
         assert_eq!(
             dir.has_entry_named(dir_name.to_string()).unwrap(),
             Entry::RenderingDirectory {
                 root_file: underscore_file,
                 extra_files: vec![an_extra_file],
-                rendering_files: vec![rendering_template]
+                rendering_files: vec![rendering_template],
+                partial_files: vec![partial_template]
             }
         );
         logs_contain("Path is a directory with an UNDERSCORE file and RENDERING directory. Mapping to Entry::RenderingDirectory.");
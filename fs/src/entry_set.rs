@@ -5,10 +5,7 @@ use crate::{
     entry::{Entry, EntryFromNamedPath},
     RENDERING_DIRECTORY, UNDERSCORE_FILE_NAME,
 };
-use std::{
-    ops::Deref,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 /// A collection of [Entries](Entry) contained within a directory.
 ///
@@ -39,6 +36,7 @@ impl EntrySet {
                 root_file,
                 extra_files: _,
                 rendering_files: _,
+                partial_files: _,
             } => root_file,
         };
         entry_path.get_id_from_path(&self.base_path)
@@ -56,9 +54,9 @@ pub trait EntrySetFromPath {
 }
 
 impl<T: AsRef<Path>> EntrySetFromPath for T {
-    #[instrument(skip(self), fields(path=self.deref().as_ref().to_str().expect("Path should be a valid UTF-8 String.")))]
+    #[instrument(skip(self), fields(path=self.as_ref().to_str().expect("Path should be a valid UTF-8 String.")))]
     fn to_entry_set(&self) -> Option<EntrySet> {
-        let path: &Path = self.deref().as_ref();
+        let path: &Path = self.as_ref();
         if !path.exists() {
             error!("Tried to map an inexistent Path to an EntrySet.");
             return None;
@@ -171,7 +169,8 @@ mod tests {
                 entries: vec![Entry::RenderingDirectory {
                     root_file: underscore_file,
                     extra_files: vec![sibling_file],
-                    rendering_files: vec![rendering_file]
+                    rendering_files: vec![rendering_file],
+                    partial_files: vec![]
                 }]
             }
         )
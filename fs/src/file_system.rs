@@ -3,9 +3,14 @@ use tracing::{debug, instrument};
 use crate::{
     entry::{Entry, EntryFromNamedPath},
     entry_set::{EntrySet, EntrySetFromPath},
-    FileSystemError, CONTENTS_DIRECTORY, MODULE, TYPES_DIRECTORY,
+    FileSystemError, CONTENTS_DIRECTORY, GROUP_HEADERS_DIRECTORY, INDEXES_DIRECTORY,
+    LOCALES_DIRECTORY, MODULE, PARTIALS_DIRECTORY, RENDERING_DIRECTORY, TYPES_DIRECTORY,
+};
+use path_utils::{children::ChildrenPaths, name::NamePaths};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
 };
-use std::path::PathBuf;
 
 /// A representation of a file system, meant to build Modules from.
 #[derive(Debug, PartialEq)]
@@ -18,6 +23,38 @@ pub struct FileSystem {
     pub types: Option<EntrySet>,
     // An optional set of entries that define contents.
     pub contents: Option<EntrySet>,
+    // The sets of entries that translate contents, keyed by their locale.
+    // Every directory inside the locales directory declares a locale, even when empty.
+    pub locales: BTreeMap<String, EntrySet>,
+    // The files of the rendering directory, that define the layouts of the module.
+    pub rendering: Vec<PathBuf>,
+    // The files of the partials directory, inside the rendering directory,
+    // that define the partial templates shared by all types.
+    pub partials: Vec<PathBuf>,
+    // The files of the group headers directory, inside the rendering directory,
+    // that define the headers of groups of contents.
+    pub group_headers: Vec<PathBuf>,
+    // The files of the indexes directory, inside the rendering directory,
+    // that define the indexes of split outputs.
+    pub indexes: Vec<PathBuf>,
+}
+
+impl FileSystem {
+    /// The locales declared by the directories inside the locales directory.
+    ///
+    /// Only these locales are recognized in the names of translated files.
+    pub fn known_locales(&self) -> Vec<String> {
+        self.locales.keys().cloned().collect()
+    }
+}
+
+/// The files directly inside a directory.
+fn files_in(directory: &Path) -> Vec<PathBuf> {
+    directory
+        .get_children()
+        .into_iter()
+        .filter(|e| e.is_file())
+        .collect()
 }
 
 impl TryFrom<PathBuf> for FileSystem {
@@ -33,10 +70,34 @@ impl TryFrom<PathBuf> for FileSystem {
             Some(module_entry) => {
                 let types_entry_set = value.join(TYPES_DIRECTORY).to_entry_set();
                 let contents_entry_set = value.join(CONTENTS_DIRECTORY).to_entry_set();
+                let locales_entry_sets: BTreeMap<String, EntrySet> = value
+                    .join(LOCALES_DIRECTORY)
+                    .get_children()
+                    .into_iter()
+                    .filter(|e| e.is_dir())
+                    .map(|d| {
+                        let entry_set = d.to_entry_set().unwrap_or_else(|| EntrySet {
+                            base_path: d.clone(),
+                            entries: vec![],
+                        });
+                        (d.get_name_without_extension(), entry_set)
+                    })
+                    .collect();
+                let rendering_directory = value.join(RENDERING_DIRECTORY);
+                let rendering_files = files_in(&rendering_directory);
+                let partial_files = files_in(&rendering_directory.join(PARTIALS_DIRECTORY));
+                let group_header_files =
+                    files_in(&rendering_directory.join(GROUP_HEADERS_DIRECTORY));
+                let index_files = files_in(&rendering_directory.join(INDEXES_DIRECTORY));
 
                 debug!(
                     type_size = types_entry_set.as_ref().map_or(0, |t| t.entries.len()),
                     content_size = contents_entry_set.as_ref().map_or(0, |t| t.entries.len()),
+                    locales_size = locales_entry_sets.len(),
+                    rendering_size = rendering_files.len(),
+                    partials_size = partial_files.len(),
+                    group_headers_size = group_header_files.len(),
+                    indexes_size = index_files.len(),
                     "Creating FileSystem."
                 );
 
@@ -45,6 +106,11 @@ impl TryFrom<PathBuf> for FileSystem {
                     module: module_entry,
                     types: types_entry_set,
                     contents: contents_entry_set,
+                    locales: locales_entry_sets,
+                    rendering: rendering_files,
+                    partials: partial_files,
+                    group_headers: group_header_files,
+                    indexes: index_files,
                 })
             }
         }
@@ -95,7 +161,12 @@ mod tests {
                 root_directory: dir,
                 module: module_file.to_entry().unwrap(),
                 types: None,
-                contents: None
+                contents: None,
+                locales: BTreeMap::new(),
+                rendering: vec![],
+                partials: vec![],
+                group_headers: vec![],
+                indexes: vec![],
             }
         )
     }
@@ -125,7 +196,72 @@ mod tests {
                     base_path: contents_dir,
                     entries: vec![first_content.to_entry().unwrap()]
                 }),
+                locales: BTreeMap::new(),
+                rendering: vec![],
+                partials: vec![],
+                group_headers: vec![],
+                indexes: vec![],
             }
         )
     }
+
+    #[test]
+    fn creates_with_one_entry_set_per_locale() {
+        let dir = testdir!();
+
+        create_test_file(&dir.join(format!("{}.json", MODULE)), "");
+
+        let locales_dir = create_test_directory(&dir.join(LOCALES_DIRECTORY));
+        let portuguese_dir = create_test_directory(&locales_dir.join("pt"));
+        let translated_content = create_test_file(&portuguese_dir.join("a.json"), "");
+
+        assert_eq!(
+            FileSystem::try_from(dir).unwrap().locales,
+            BTreeMap::from([(
+                "pt".to_string(),
+                EntrySet {
+                    base_path: portuguese_dir,
+                    entries: vec![translated_content.to_entry().unwrap()]
+                }
+            )])
+        )
+    }
+
+    #[test]
+    fn empty_locale_directories_declare_locales() {
+        let dir = testdir!();
+
+        create_test_file(&dir.join(format!("{}.json", MODULE)), "");
+
+        let locales_dir = create_test_directory(&dir.join(LOCALES_DIRECTORY));
+        create_test_directory(&locales_dir.join("pt"));
+
+        assert_eq!(
+            FileSystem::try_from(dir).unwrap().known_locales(),
+            vec!["pt".to_string()]
+        )
+    }
+
+    #[test]
+    fn creates_with_rendering_files() {
+        let dir = testdir!();
+
+        create_test_file(&dir.join(format!("{}.json", MODULE)), "");
+
+        let rendering_dir = create_test_directory(&dir.join(RENDERING_DIRECTORY));
+        let html_layout = create_test_file(&rendering_dir.join("html.hjs"), "");
+        let md_layout = create_test_file(&rendering_dir.join("md.hjs"), "");
+        let partials_dir = create_test_directory(&rendering_dir.join(PARTIALS_DIRECTORY));
+        let stat_block = create_test_file(&partials_dir.join("statBlock.hjs"), "");
+        let groups_dir = create_test_directory(&rendering_dir.join(GROUP_HEADERS_DIRECTORY));
+        let group_header = create_test_file(&groups_dir.join("md.hjs"), "");
+        let indexes_dir = create_test_directory(&rendering_dir.join(INDEXES_DIRECTORY));
+        let index = create_test_file(&indexes_dir.join("html.hjs"), "");
+
+        let file_system = FileSystem::try_from(dir).unwrap();
+        assert_eq!(file_system.rendering, vec![html_layout, md_layout]);
+        assert_eq!(file_system.partials, vec![stat_block]);
+        assert_eq!(file_system.group_headers, vec![group_header]);
+        assert_eq!(file_system.indexes, vec![index]);
+    }
 }
@@ -18,8 +18,20 @@ pub const MODULE: &str = "module";
 pub const TYPES_DIRECTORY: &str = "types";
 /// The name of the directory or file that corresponds to the rendering templates of a type.
 pub const RENDERING_DIRECTORY: &str = "rendering";
+/// The name of the directory, inside a rendering directory, that contains
+/// the partial templates shared by other templates.
+pub const PARTIALS_DIRECTORY: &str = "partials";
+/// The name of the directory, inside the rendering directory of the module,
+/// that contains the headers of groups of contents, one per format.
+pub const GROUP_HEADERS_DIRECTORY: &str = "@group";
+/// The name of the directory, inside the rendering directory of the module,
+/// that contains the indexes of split outputs, one per format.
+pub const INDEXES_DIRECTORY: &str = "@index";
 /// The name of the directory that contains the contents.
 pub const CONTENTS_DIRECTORY: &str = "contents";
+/// The name of the directory that contains one directory per locale,
+/// each mirroring the contents directory with translated data.
+pub const LOCALES_DIRECTORY: &str = "locales";
 
 /// Represents a single file system [Entry].
 pub mod entry;
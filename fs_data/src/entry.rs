@@ -1,13 +1,17 @@
 use file_data::FileData;
 use fs::entry::Entry;
 use serde_json::Value;
+use std::time::SystemTime;
 use tracing::instrument;
 
-use crate::{EntryData, FileSystemDataError};
+use crate::{modified_time, EntryData, FileSystemDataError, Localization};
 
 impl EntryData for Entry {
     #[instrument]
-    fn try_get_data(&self) -> Result<Value, FileSystemDataError> {
+    fn try_get_localized_data(
+        &self,
+        localization: Localization,
+    ) -> Result<Value, FileSystemDataError> {
         match self {
             Entry::File(file) => file.try_read_file().map_err(|e| {
                 FileSystemDataError::UnableToReadFile(file.clone().into_boxed_path(), e.into())
@@ -19,7 +23,7 @@ impl EntryData for Entry {
                 Ok(root_data) => {
                     if let Some(root_data_object) = root_data.as_object() {
                         let mut result = root_data_object.clone();
-                        let extra_files_data = extra_files.try_get_data()?;
+                        let extra_files_data = extra_files.try_get_localized_data(localization)?;
                         let extra_data = extra_files_data.as_object().expect("When a Vec of Path Buffers is transformed into data, the result is always a valid Object");
                         extra_data.iter().for_each(|(k, v)| {
                             result.insert(k.clone(), v.clone());
@@ -39,17 +43,24 @@ impl EntryData for Entry {
                 root_file,
                 extra_files,
                 rendering_files,
+                partial_files,
             } => match root_file.try_read_file() {
                 Ok(root_data) => {
                     if let Some(root_data_object) = root_data.as_object() {
                         let mut result = root_data_object.clone();
-                        let extra_files_data = extra_files.try_get_data()?;
+                        let extra_files_data = extra_files.try_get_localized_data(localization)?;
                         let extra_data = extra_files_data.as_object().expect("When a Vec of Path Buffers is transformed into data, the result is always a valid Object");
                         extra_data.iter().for_each(|(k, v)| {
                             result.insert(k.clone(), v.clone());
                         });
-                        let rendering_files_data = rendering_files.try_get_data()?;
+                        let rendering_files_data =
+                            rendering_files.try_get_localized_data(localization)?;
                         result.insert("rendering".to_string(), rendering_files_data);
+                        if !partial_files.is_empty() {
+                            let partial_files_data =
+                                partial_files.try_get_localized_data(localization)?;
+                            result.insert("partials".to_string(), partial_files_data);
+                        }
                         serde_json::to_value(result)
                             .map_err(|e| FileSystemDataError::UnableToSerializeResult(e.into()))
                     } else {
@@ -63,6 +74,33 @@ impl EntryData for Entry {
             },
         }
     }
+
+    fn get_translated_keys(&self, known_locales: &[String]) -> Vec<(String, String)> {
+        match self {
+            Entry::File(_) => Vec::new(),
+            Entry::Directory { extra_files, .. }
+            | Entry::RenderingDirectory { extra_files, .. } => {
+                extra_files.get_translated_keys(known_locales)
+            }
+        }
+    }
+
+    fn get_modified_time(&self, key: &str, localization: Localization) -> Option<SystemTime> {
+        match self {
+            Entry::File(file) => modified_time(file),
+            Entry::Directory {
+                root_file,
+                extra_files,
+            }
+            | Entry::RenderingDirectory {
+                root_file,
+                extra_files,
+                ..
+            } => extra_files
+                .get_modified_time(key, localization)
+                .or_else(|| modified_time(root_file)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -129,12 +167,15 @@ mod tests {
         let extra_file = create_test_file(&dir.join("b.txt"), "test");
         let rendering_dir = create_test_directory(&dir.join("rendering"));
         let rendering_file = create_test_file(&rendering_dir.join("md.hjs"), "");
+        let partials_dir = create_test_directory(&rendering_dir.join("partials"));
+        let partial_file = create_test_file(&partials_dir.join("statBlock.hjs"), "stats");
 
         assert_eq!(
             Entry::RenderingDirectory {
                 root_file,
                 extra_files: vec![extra_file],
-                rendering_files: vec![rendering_file]
+                rendering_files: vec![rendering_file],
+                partial_files: vec![partial_file]
             }
             .try_get_data()
             .unwrap(),
@@ -143,6 +184,9 @@ mod tests {
                 "b": "test",
                 "rendering": {
                     "md": ""
+                },
+                "partials": {
+                    "statBlock": "stats"
                 }
             })
         )
@@ -1,5 +1,5 @@
 use serde_json::Value;
-use std::{error::Error, path::Path};
+use std::{error::Error, path::Path, time::SystemTime};
 use thiserror::Error;
 
 /// The errors that can happen when reading data from an Entry.
@@ -15,10 +15,41 @@ pub enum FileSystemDataError {
     UnableToSerializeResult(#[source] Box<dyn Error>),
 }
 
+/// The locale Entries are read in, and the locales that can be used in the names of files.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Localization<'a> {
+    /// The locale to translate into, or `None` for the default locale.
+    pub locale: Option<&'a str>,
+    /// The locales that are recognized in the names of translated files.
+    pub known_locales: &'a [String],
+}
+
 /// Simplifies reading Entries into valid [JSON Value](serde_json::Value).
 pub trait EntryData {
-    /// Attempts to read the data into a valid format.
-    fn try_get_data(&self) -> Result<Value, FileSystemDataError>;
+    /// Attempts to read the data into a valid format, in the default locale.
+    ///
+    /// No locales are known, so every file is read as it's own key.
+    fn try_get_data(&self) -> Result<Value, FileSystemDataError> {
+        self.try_get_localized_data(Localization::default())
+    }
+    /// Attempts to read the data into a valid format, translated into a locale.
+    ///
+    /// Files named with a known locale before their extension (like `title.pt.md`)
+    /// translate the file without it (`title.md`): they replace it's value
+    /// when their locale is used, and are ignored otherwise.
+    fn try_get_localized_data(
+        &self,
+        localization: Localization,
+    ) -> Result<Value, FileSystemDataError>;
+    /// Finds the keys that have translated files, next to their locales.
+    fn get_translated_keys(&self, known_locales: &[String]) -> Vec<(String, String)>;
+    /// Gets when the file a key is read from was last modified, if it can be known.
+    fn get_modified_time(&self, key: &str, localization: Localization) -> Option<SystemTime>;
+}
+
+/// Gets when a file was last modified, if it can be known.
+fn modified_time(file: &Path) -> Option<SystemTime> {
+    file.metadata().and_then(|m| m.modified()).ok()
 }
 
 /// Handles the reading of data from Entries.
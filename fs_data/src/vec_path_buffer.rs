@@ -1,30 +1,64 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::BTreeMap, path::PathBuf, time::SystemTime};
 
 use file_data::FileData;
 use path_utils::name::NamePaths;
 use serde_json::Value;
 
-use crate::{EntryData, FileSystemDataError};
+use crate::{modified_time, EntryData, FileSystemDataError, Localization};
 
 impl EntryData for Vec<PathBuf> {
-    fn try_get_data(&self) -> Result<Value, FileSystemDataError> {
-        let mut result: HashMap<String, Value> = HashMap::new();
+    fn try_get_localized_data(
+        &self,
+        localization: Localization,
+    ) -> Result<Value, FileSystemDataError> {
+        let mut result: BTreeMap<String, Value> = BTreeMap::new();
+        let mut translations = Vec::new();
         for file in self {
-            match file.try_read_file() {
-                Ok(value) => {
-                    result.insert(file.get_name_without_extension(), value);
+            match file.get_localized_name(localization.known_locales) {
+                Some((key, file_locale)) => {
+                    if Some(file_locale.as_str()) == localization.locale {
+                        translations.push((key, file));
+                    }
                 }
-                Err(e) => {
-                    return Err(FileSystemDataError::UnableToReadFile(
-                        file.clone().into_boxed_path(),
-                        e.into(),
-                    ))
+                None => {
+                    result.insert(file.get_name_without_extension(), read_file(file)?);
                 }
             }
         }
+        // Translations are applied last, so they always replace the default locale
+        for (key, file) in translations {
+            result.insert(key, read_file(file)?);
+        }
         serde_json::to_value(result)
             .map_err(|e| FileSystemDataError::UnableToSerializeResult(e.into()))
     }
+
+    fn get_translated_keys(&self, known_locales: &[String]) -> Vec<(String, String)> {
+        self.iter()
+            .filter_map(|file| file.get_localized_name(known_locales))
+            .collect()
+    }
+
+    fn get_modified_time(&self, key: &str, localization: Localization) -> Option<SystemTime> {
+        let translation = self.iter().find(|file| {
+            file.get_localized_name(localization.known_locales)
+                .is_some_and(|(k, l)| k == key && Some(l.as_str()) == localization.locale)
+        });
+        let file = translation.or_else(|| {
+            self.iter().find(|file| {
+                file.get_localized_name(localization.known_locales)
+                    .is_none()
+                    && file.get_name_without_extension() == key
+            })
+        })?;
+        modified_time(file)
+    }
+}
+
+fn read_file(file: &PathBuf) -> Result<Value, FileSystemDataError> {
+    file.try_read_file().map_err(|e| {
+        FileSystemDataError::UnableToReadFile(file.clone().into_boxed_path(), e.into())
+    })
 }
 
 #[cfg(test)]
@@ -57,4 +91,104 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn translated_files_are_used_only_for_their_locale() {
+        let dir = testdir!();
+        let known_locales = vec!["pt".to_string()];
+
+        let translated = create_test_file(&dir.join("title.pt.txt"), "Meu titulo");
+        let default = create_test_file(&dir.join("title.txt"), "My title");
+        let other = create_test_file(&dir.join("stats.txt"), "5");
+
+        let vector = vec![translated, default, other];
+
+        assert_eq!(
+            vector
+                .try_get_localized_data(Localization {
+                    locale: None,
+                    known_locales: &known_locales
+                })
+                .unwrap(),
+            json!({"title": "My title", "stats": "5"})
+        );
+        assert_eq!(
+            vector
+                .try_get_localized_data(Localization {
+                    locale: Some("pt"),
+                    known_locales: &known_locales
+                })
+                .unwrap(),
+            json!({"title": "Meu titulo", "stats": "5"})
+        );
+        assert_eq!(
+            vector.get_translated_keys(&known_locales),
+            vec![("title".to_string(), "pt".to_string())]
+        );
+    }
+
+    #[test]
+    fn dotted_file_names_are_not_translations_of_unknown_locales() {
+        let dir = testdir!();
+        let known_locales = vec!["pt".to_string()];
+
+        let max = create_test_file(&dir.join("stats.max.txt"), "5");
+        let old = create_test_file(&dir.join("notes.old.txt"), "Old notes");
+
+        let vector = vec![max, old];
+
+        assert_eq!(
+            vector
+                .try_get_localized_data(Localization {
+                    locale: Some("pt"),
+                    known_locales: &known_locales
+                })
+                .unwrap(),
+            json!({"stats.max": "5", "notes.old": "Old notes"})
+        );
+        assert_eq!(vector.get_translated_keys(&known_locales), vec![]);
+    }
+
+    #[test]
+    fn modified_time_is_of_the_file_the_key_is_read_from() {
+        let dir = testdir!();
+        let known_locales = vec!["pt".to_string()];
+
+        let translated = create_test_file(&dir.join("title.pt.txt"), "Meu titulo");
+        let default = create_test_file(&dir.join("title.txt"), "My title");
+        let earlier = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&translated)
+            .unwrap()
+            .set_modified(earlier)
+            .unwrap();
+
+        let vector = vec![translated, default.clone()];
+
+        assert_eq!(
+            vector.get_modified_time(
+                "title",
+                Localization {
+                    locale: Some("pt"),
+                    known_locales: &known_locales
+                }
+            ),
+            Some(earlier)
+        );
+        assert_eq!(
+            vector.get_modified_time(
+                "title",
+                Localization {
+                    locale: None,
+                    known_locales: &known_locales
+                }
+            ),
+            modified_time(&default)
+        );
+        assert_eq!(
+            vector.get_modified_time("subtitle", Localization::default()),
+            None
+        );
+    }
 }
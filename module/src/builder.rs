@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use url::Url;
+
+use crate::{module::Module, module_type::ModuleType, JsonMap};
+
+/// Creates [Modules](Module) programmatically.
+///
+/// # Example
+/// ```
+/// # use module::module::Module;
+/// # use module::module_type::ModuleType;
+/// # use url::Url;
+/// let module = Module::builder(
+///     "My title",
+///     "My description",
+///     Url::parse("https://powerd6.org").unwrap(),
+/// )
+/// .with_type(
+///     "spell",
+///     ModuleType {
+///         description: "A spell".to_string(),
+///         schema: None,
+///         rendering: None,
+///         partials: None,
+///         escaping: None,
+///         extends: None,
+///         ancestors: None,
+///     },
+/// )
+/// .build();
+/// assert_eq!(module.title(), "My title");
+/// assert!(module.types.unwrap().contains_key("spell"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModuleBuilder {
+    module: Module,
+}
+
+impl ModuleBuilder {
+    /// Starts a module with it's required fields.
+    pub fn new(title: impl Into<String>, description: impl Into<String>, source: Url) -> Self {
+        ModuleBuilder {
+            module: Module {
+                title: title.into(),
+                description: description.into(),
+                source,
+                rendering: None,
+                partials: None,
+                types: None,
+                contents: None,
+            },
+        }
+    }
+
+    /// Adds a type, replacing any type with the same identifier.
+    pub fn with_type(mut self, type_key: impl Into<String>, module_type: ModuleType) -> Self {
+        self.module
+            .types
+            .get_or_insert_with(BTreeMap::new)
+            .insert(type_key.into(), module_type);
+        self
+    }
+
+    /// Adds a content, replacing any content with the same identifier.
+    pub fn with_content(mut self, content_id: impl Into<String>, content: JsonMap) -> Self {
+        self.module
+            .contents
+            .get_or_insert_with(BTreeMap::new)
+            .insert(content_id.into(), content);
+        self
+    }
+
+    /// Finishes the module.
+    pub fn build(self) -> Module {
+        self.module
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn builds_module_without_types_or_contents() {
+        let source = Url::parse("https://powerd6.org").unwrap();
+
+        let module = ModuleBuilder::new("My title", "My description", source.clone()).build();
+
+        assert_eq!(module.title(), "My title");
+        assert_eq!(module.description(), "My description");
+        assert_eq!(module.source(), &source);
+        assert_eq!(module.types, None);
+        assert_eq!(module.contents, None);
+    }
+
+    #[test]
+    fn later_contents_replace_earlier_ones() {
+        let module = ModuleBuilder::new(
+            "My title",
+            "My description",
+            Url::parse("https://powerd6.org").unwrap(),
+        )
+        .with_content("a", BTreeMap::from([("key".to_string(), json!(1))]))
+        .with_content("a", BTreeMap::from([("key".to_string(), json!(2))]))
+        .build();
+
+        assert_eq!(module.contents.unwrap()["a"]["key"], json!(2));
+    }
+}
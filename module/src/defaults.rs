@@ -0,0 +1,288 @@
+use serde_json::Value;
+use tracing::{debug, instrument};
+
+use crate::{module::Module, TYPE_KEY};
+
+/// The key of the default value of a JSON schema.
+const DEFAULT: &str = "default";
+
+/// Fills the missing properties of a value with the `default` values declared
+/// in it's JSON schema.
+///
+/// Nested objects (and the items of arrays) are filled recursively. Local
+/// references (like `#/$defs/price`) and `allOf` compositions are followed,
+/// but the branches of `anyOf` and `oneOf` are ignored, since it is not
+/// possible to know which of them applies.
+#[instrument(skip_all)]
+pub fn apply_defaults(value: &mut Value, schema: &Value) {
+    apply_defaults_with_root(value, schema, schema, 0, &mut Vec::new())
+}
+
+/// The maximum depth of schemas that will be followed,
+/// to protect against recursive references.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// Applies the defaults of a schema to a value.
+///
+/// The `references` are the ones that were followed for this value, which are
+/// not followed again, so schemas that (indirectly) reference themselves stop.
+fn apply_defaults_with_root<'a>(
+    value: &mut Value,
+    schema: &'a Value,
+    root: &'a Value,
+    depth: usize,
+    references: &mut Vec<&'a str>,
+) {
+    if depth > MAX_DEPTH {
+        debug!("Stopped applying defaults, the schema is too deep");
+        return;
+    }
+    if let Some((reference, target)) = follow_reference(schema, root, references) {
+        references.push(reference);
+        apply_defaults_with_root(value, target, root, depth + 1, references);
+        references.pop();
+    }
+    if let Some(branches) = schema.get("allOf").and_then(Value::as_array) {
+        for branch in branches {
+            apply_defaults_with_root(value, branch, root, depth + 1, references);
+        }
+    }
+    match value {
+        Value::Object(object) => {
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, property_schema) in properties {
+                    if !object.contains_key(name) {
+                        if let Some(default) =
+                            find_default(property_schema, root, depth + 1, &mut Vec::new())
+                        {
+                            debug!("Using default value for property `{}`", name);
+                            object.insert(name.clone(), default.clone());
+                        }
+                    }
+                    if let Some(property) = object.get_mut(name) {
+                        apply_defaults_with_root(
+                            property,
+                            property_schema,
+                            root,
+                            depth + 1,
+                            &mut Vec::new(),
+                        );
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(items_schema) = schema.get("items").filter(|s| s.is_object()) {
+                for item in items {
+                    apply_defaults_with_root(item, items_schema, root, depth + 1, &mut Vec::new());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Finds the default value of a schema, following references and `allOf` compositions.
+fn find_default<'a>(
+    schema: &'a Value,
+    root: &'a Value,
+    depth: usize,
+    references: &mut Vec<&'a str>,
+) -> Option<&'a Value> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    if let Some(default) = schema.get(DEFAULT) {
+        return Some(default);
+    }
+    if let Some((reference, target)) = follow_reference(schema, root, references) {
+        references.push(reference);
+        let default = find_default(target, root, depth + 1, references);
+        references.pop();
+        if default.is_some() {
+            return default;
+        }
+    }
+    schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .and_then(|branches| {
+            branches
+                .iter()
+                .find_map(|branch| find_default(branch, root, depth + 1, references))
+        })
+}
+
+/// Follows a local reference (`$ref` starting with `#`) of a schema.
+pub(crate) fn resolve_reference<'a>(schema: &Value, root: &'a Value) -> Option<&'a Value> {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+}
+
+/// Follows a local reference of a schema, unless it is one of the references
+/// that were already followed, returning the reference and it's target.
+pub(crate) fn follow_reference<'a>(
+    schema: &'a Value,
+    root: &'a Value,
+    followed: &[&str],
+) -> Option<(&'a str, &'a Value)> {
+    let reference = schema.get("$ref").and_then(Value::as_str)?;
+    if followed.contains(&reference) {
+        debug!("Stopped following `{}`, it references itself", reference);
+        return None;
+    }
+    resolve_reference(schema, root).map(|target| (reference, target))
+}
+
+impl Module {
+    /// Fills the missing properties of every content with the `default`
+    /// values declared in the schema of it's type.
+    ///
+    /// Contents without a type, or whose type has no schema, are unchanged.
+    #[instrument(skip(self))]
+    pub fn apply_schema_defaults(&mut self) {
+        let (Some(types), Some(contents)) = (&self.types, &mut self.contents) else {
+            return;
+        };
+        for (content_id, content) in contents.iter_mut() {
+            let schema = content
+                .get(TYPE_KEY)
+                .and_then(Value::as_str)
+                .and_then(|type_key| types.get(type_key))
+                .and_then(|module_type| module_type.schema.as_ref());
+            if let Some(schema) = schema {
+                debug!("Applying defaults to content `{}`", content_id);
+                let mut value = Value::Object(content.clone().into_iter().collect());
+                apply_defaults(&mut value, schema);
+                if let Value::Object(object) = value {
+                    *content = object.into_iter().collect();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn fills_missing_properties_only() {
+        let mut value = json!({"name": "Fireball"});
+
+        apply_defaults(
+            &mut value,
+            &json!({
+                "properties": {
+                    "name": {"type": "string", "default": "Unnamed"},
+                    "level": {"type": "integer", "default": 1}
+                }
+            }),
+        );
+
+        assert_eq!(value, json!({"name": "Fireball", "level": 1}));
+    }
+
+    #[test]
+    fn fills_nested_objects_and_array_items() {
+        let mut value = json!({"cost": {}, "effects": [{"kind": "fire"}]});
+
+        apply_defaults(
+            &mut value,
+            &json!({
+                "properties": {
+                    "cost": {
+                        "properties": {"currency": {"default": "gold"}}
+                    },
+                    "range": {
+                        "default": {},
+                        "properties": {"unit": {"default": "meters"}}
+                    },
+                    "effects": {
+                        "items": {"properties": {"duration": {"default": 0}}}
+                    }
+                }
+            }),
+        );
+
+        assert_eq!(
+            value,
+            json!({
+                "cost": {"currency": "gold"},
+                "range": {"unit": "meters"},
+                "effects": [{"kind": "fire", "duration": 0}]
+            })
+        );
+    }
+
+    #[test]
+    fn follows_references_and_compositions() {
+        let mut value = json!({});
+
+        apply_defaults(
+            &mut value,
+            &json!({
+                "allOf": [
+                    {"properties": {"level": {"$ref": "#/$defs/level"}}},
+                    {"properties": {"school": {"default": "fire"}}}
+                ],
+                "$defs": {"level": {"type": "integer", "default": 1}}
+            }),
+        );
+
+        assert_eq!(value, json!({"level": 1, "school": "fire"}));
+    }
+
+    #[test]
+    fn module_contents_use_the_schema_of_their_type() {
+        let mut module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "spell": {
+                    "description": "a spell",
+                    "schema": {"properties": {"level": {"default": 1}}}
+                }
+            },
+            "contents": {
+                "fireball": {"type": "spell"},
+                "sword": {"type": "item"}
+            }
+        }))
+        .unwrap();
+
+        module.apply_schema_defaults();
+
+        let contents = module.contents.unwrap();
+        assert_eq!(contents["fireball"]["level"], json!(1));
+        assert_eq!(contents["sword"].get("level"), None);
+    }
+
+    #[test]
+    fn stops_at_references_to_themselves() {
+        let mut value = json!({"children": [{}]});
+
+        apply_defaults(
+            &mut value,
+            &json!({
+                "$ref": "#/$defs/node",
+                "$defs": {"node": {
+                    "allOf": [{"$ref": "#/$defs/node"}, {"$ref": "#/$defs/node"}],
+                    "properties": {
+                        "name": {"$ref": "#/$defs/name"},
+                        "children": {"items": {"$ref": "#/$defs/node"}}
+                    }
+                }, "name": {"allOf": [{"$ref": "#/$defs/name"}, {"$ref": "#/$defs/name"}]}}
+            }),
+        );
+
+        assert_eq!(value, json!({"children": [{}]}));
+    }
+}
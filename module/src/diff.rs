@@ -0,0 +1,716 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::instrument;
+
+use crate::{
+    module::Module, module_type::ModuleType, JsonMap, DESCRIPTION, PARTIALS, RENDERING, SCHEMA,
+    SOURCE, TITLE,
+};
+
+/// The differences between two versions of a Module.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ModuleDiff {
+    /// Changes to the title, description, source, layouts and partials of the module.
+    pub metadata: Vec<FieldChange>,
+    /// Changes to the types of the module.
+    pub types: EntriesDiff,
+    /// Changes to the contents of the module.
+    pub contents: EntriesDiff,
+}
+
+/// The differences between two collections of entries, compared by identifier.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct EntriesDiff {
+    /// The identifiers that only exist in the new version.
+    pub added: Vec<String>,
+    /// The identifiers that only exist in the old version.
+    pub removed: Vec<String>,
+    /// The entries that exist in both versions, but are different.
+    pub modified: BTreeMap<String, EntryChanges>,
+}
+
+/// The changes made to a single entry.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct EntryChanges {
+    /// Every field that changed.
+    pub fields: Vec<FieldChange>,
+    /// The classified changes to the schema, for types.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schema: Vec<SchemaChange>,
+}
+
+/// A change to a single value, identified by it's
+/// [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub pointer: String,
+    /// The old value, if the field existed before.
+    pub old: Option<Value>,
+    /// The new value, if the field still exists.
+    pub new: Option<Value>,
+}
+
+/// A change to a type schema, and whether existing contents may stop being valid.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+    pub pointer: String,
+    pub breaking: bool,
+    pub reason: String,
+}
+
+impl ModuleDiff {
+    /// Checks if nothing changed between the versions.
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty() && self.types.is_empty() && self.contents.is_empty()
+    }
+
+    /// Checks if any of the changes can make existing contents invalid.
+    ///
+    /// Removing a type, or making it's schema more restrictive, is a breaking change.
+    pub fn is_breaking(&self) -> bool {
+        !self.types.removed.is_empty()
+            || self
+                .types
+                .modified
+                .values()
+                .flat_map(|c| c.schema.iter())
+                .any(|c| c.breaking)
+    }
+}
+
+impl EntriesDiff {
+    /// Checks if nothing changed between the versions.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Compares two versions of a module.
+#[instrument(skip_all)]
+pub fn diff(old: &Module, new: &Module) -> ModuleDiff {
+    let mut metadata = Vec::new();
+    diff_values(
+        "",
+        &metadata_value(old),
+        &metadata_value(new),
+        &mut metadata,
+    );
+    ModuleDiff {
+        metadata,
+        types: diff_entries(old.types.as_ref(), new.types.as_ref(), diff_types),
+        contents: diff_entries(old.contents.as_ref(), new.contents.as_ref(), diff_contents),
+    }
+}
+
+/// The fields of the module that are not types or contents.
+fn metadata_value(module: &Module) -> Value {
+    serde_json::json!({
+        TITLE: module.title,
+        DESCRIPTION: module.description,
+        SOURCE: module.source,
+        RENDERING: module.rendering,
+        PARTIALS: module.partials,
+    })
+}
+
+fn diff_entries<T: PartialEq>(
+    old: Option<&BTreeMap<String, T>>,
+    new: Option<&BTreeMap<String, T>>,
+    diff_entry: fn(&T, &T) -> EntryChanges,
+) -> EntriesDiff {
+    let empty = BTreeMap::new();
+    let old = old.unwrap_or(&empty);
+    let new = new.unwrap_or(&empty);
+    let mut result = EntriesDiff::default();
+    for (id, old_entry) in old {
+        match new.get(id) {
+            Some(new_entry) if new_entry != old_entry => {
+                result
+                    .modified
+                    .insert(id.clone(), diff_entry(old_entry, new_entry));
+            }
+            Some(_) => {}
+            None => result.removed.push(id.clone()),
+        }
+    }
+    result.added = new
+        .keys()
+        .filter(|id| !old.contains_key(*id))
+        .cloned()
+        .collect();
+    result
+}
+
+fn diff_types(old: &ModuleType, new: &ModuleType) -> EntryChanges {
+    let mut fields = Vec::new();
+    diff_values(
+        "",
+        &serde_json::to_value(old).expect("Types should always be a valid JSON Value"),
+        &serde_json::to_value(new).expect("Types should always be a valid JSON Value"),
+        &mut fields,
+    );
+    let mut schema = Vec::new();
+    let pointer = format!("/{}", SCHEMA);
+    match (&old.schema, &new.schema) {
+        (Some(old_schema), Some(new_schema)) => {
+            diff_schemas(&pointer, old_schema, new_schema, &mut schema)
+        }
+        (None, Some(_)) => schema.push(SchemaChange {
+            pointer,
+            breaking: true,
+            reason: "a schema was added".to_string(),
+        }),
+        (Some(_), None) => schema.push(SchemaChange {
+            pointer,
+            breaking: false,
+            reason: "the schema was removed".to_string(),
+        }),
+        (None, None) => {}
+    }
+    EntryChanges { fields, schema }
+}
+
+fn diff_contents(old: &JsonMap, new: &JsonMap) -> EntryChanges {
+    let mut fields = Vec::new();
+    diff_values(
+        "",
+        &serde_json::to_value(old).expect("Contents should always be a valid JSON Value"),
+        &serde_json::to_value(new).expect("Contents should always be a valid JSON Value"),
+        &mut fields,
+    );
+    EntryChanges {
+        fields,
+        schema: Vec::new(),
+    }
+}
+
+/// Escapes a key so it can be used as a JSON Pointer token.
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Collects the changes between two values, descending into objects.
+///
+/// Any other value (including arrays) is compared as a whole.
+fn diff_values(pointer: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(old_object), Value::Object(new_object)) => {
+            let keys: BTreeSet<&String> = old_object.keys().chain(new_object.keys()).collect();
+            for key in keys {
+                let key_pointer = format!("{}/{}", pointer, pointer_token(key));
+                match (old_object.get(key), new_object.get(key)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_values(&key_pointer, old_value, new_value, changes)
+                    }
+                    (old_value, new_value) => changes.push(FieldChange {
+                        pointer: key_pointer,
+                        old: old_value.cloned(),
+                        new: new_value.cloned(),
+                    }),
+                }
+            }
+        }
+        (old_value, new_value) if old_value != new_value => changes.push(FieldChange {
+            pointer: pointer.to_string(),
+            old: Some(old_value.clone()),
+            new: Some(new_value.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// Schema keywords that only document the schema, and never affect validation.
+const ANNOTATION_KEYWORDS: [&str; 6] = [
+    "title",
+    "description",
+    "default",
+    "examples",
+    "$comment",
+    "deprecated",
+];
+/// Schema keywords whose values are a lower bound.
+const LOWER_BOUNDS: [&str; 6] = [
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+    "minContains",
+];
+/// Schema keywords whose values are an upper bound.
+const UPPER_BOUNDS: [&str; 6] = [
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+    "maxContains",
+];
+
+/// Classifies the changes between two schemas.
+///
+/// Changes are considered breaking when a content that was valid against
+/// the old schema could be invalid against the new one. Changes that can't
+/// be classified are considered breaking.
+fn diff_schemas(pointer: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    let (old_object, new_object) = match (old, new) {
+        (Value::Object(o), Value::Object(n)) => (o, n),
+        (o, n) if o == n => return,
+        _ => {
+            changes.push(SchemaChange {
+                pointer: pointer.to_string(),
+                breaking: new != &Value::Bool(true),
+                reason: "the schema was replaced".to_string(),
+            });
+            return;
+        }
+    };
+    let keywords: BTreeSet<&String> = old_object.keys().chain(new_object.keys()).collect();
+    for keyword in keywords {
+        let old_value = old_object.get(keyword);
+        let new_value = new_object.get(keyword);
+        if old_value == new_value {
+            continue;
+        }
+        let keyword_pointer = format!("{}/{}", pointer, pointer_token(keyword));
+        let mut push = |breaking: bool, reason: String| {
+            changes.push(SchemaChange {
+                pointer: keyword_pointer.clone(),
+                breaking,
+                reason,
+            })
+        };
+        match keyword.as_str() {
+            k if ANNOTATION_KEYWORDS.contains(&k) => {
+                push(false, format!("the annotation `{}` changed", k))
+            }
+            "properties" | "$defs" | "definitions" | "patternProperties" => {
+                diff_schema_maps(&keyword_pointer, old_value, new_value, changes)
+            }
+            "required" => {
+                let old_required = string_set(old_value);
+                let new_required = string_set(new_value);
+                for added in new_required.difference(&old_required) {
+                    push(true, format!("the property `{}` became required", added));
+                }
+                for removed in old_required.difference(&new_required) {
+                    push(false, format!("the property `{}` became optional", removed));
+                }
+            }
+            "type" => {
+                let old_types = string_set(old_value);
+                let new_types = string_set(new_value);
+                let breaking = new_value.is_some()
+                    && (old_value.is_none() || !old_types.is_subset(&new_types));
+                push(breaking, "the allowed types changed".to_string());
+            }
+            "enum" => {
+                let old_values = value_list(old_value);
+                let new_values = value_list(new_value);
+                let breaking = new_value.is_some()
+                    && (old_value.is_none() || old_values.iter().any(|v| !new_values.contains(v)));
+                push(breaking, "the allowed values changed".to_string());
+            }
+            k if LOWER_BOUNDS.contains(&k) => {
+                let breaking = match (
+                    old_value.and_then(Value::as_f64),
+                    new_value.and_then(Value::as_f64),
+                ) {
+                    (Some(o), Some(n)) => n > o,
+                    (_, new) => new.is_some(),
+                };
+                push(breaking, format!("the lower bound `{}` changed", k));
+            }
+            k if UPPER_BOUNDS.contains(&k) => {
+                let breaking = match (
+                    old_value.and_then(Value::as_f64),
+                    new_value.and_then(Value::as_f64),
+                ) {
+                    (Some(o), Some(n)) => n < o,
+                    (_, new) => new.is_some(),
+                };
+                push(breaking, format!("the upper bound `{}` changed", k));
+            }
+            "allOf" | "anyOf" | "oneOf" => {
+                diff_schema_lists(keyword, &keyword_pointer, old_value, new_value, changes)
+            }
+            "items" | "additionalProperties" | "unevaluatedProperties" | "not" => {
+                match (old_value, new_value) {
+                    (Some(o), Some(n)) => diff_schemas(&keyword_pointer, o, n, changes),
+                    (None, Some(n)) => push(
+                        n != &Value::Bool(true),
+                        format!("the keyword `{}` was added", keyword),
+                    ),
+                    (_, None) => push(false, format!("the keyword `{}` was removed", keyword)),
+                }
+            }
+            _ => push(
+                new_value.is_some(),
+                format!("the keyword `{}` changed", keyword),
+            ),
+        }
+    }
+}
+
+/// Classifies the changes between two maps of named schemas, like `properties`.
+fn diff_schema_maps(
+    pointer: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let empty = Map::new();
+    let old = old.and_then(Value::as_object).unwrap_or(&empty);
+    let new = new.and_then(Value::as_object).unwrap_or(&empty);
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for name in names {
+        let name_pointer = format!("{}/{}", pointer, pointer_token(name));
+        match (old.get(name), new.get(name)) {
+            (Some(o), Some(n)) => diff_schemas(&name_pointer, o, n, changes),
+            (Some(_), None) => changes.push(SchemaChange {
+                pointer: name_pointer,
+                breaking: true,
+                reason: format!("`{}` was removed", name),
+            }),
+            (None, Some(_)) => changes.push(SchemaChange {
+                pointer: name_pointer,
+                breaking: false,
+                reason: format!("`{}` was added", name),
+            }),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Classifies the changes between two lists of schemas, like `anyOf`.
+///
+/// The schemas that are not in both lists are compared in order, since
+/// they were most likely changed, and the rest were added or removed.
+fn diff_schema_lists(
+    keyword: &str,
+    pointer: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let (old, new) = match (old, new) {
+        (_, None) => {
+            changes.push(SchemaChange {
+                pointer: pointer.to_string(),
+                breaking: false,
+                reason: format!("the keyword `{}` was removed", keyword),
+            });
+            return;
+        }
+        (None, Some(new)) => {
+            // Only schemas that accept anything leave the contents as they were
+            let trivial = value_list(Some(new))
+                .iter()
+                .all(|s| s == &Value::Bool(true) || s == &Value::Object(Map::new()));
+            changes.push(SchemaChange {
+                pointer: pointer.to_string(),
+                breaking: !trivial || (keyword == "oneOf" && value_list(Some(new)).len() != 1),
+                reason: format!("the keyword `{}` was added", keyword),
+            });
+            return;
+        }
+        (Some(old), Some(new)) => (value_list(Some(old)), value_list(Some(new))),
+    };
+    let removed: Vec<(usize, &Value)> = old
+        .iter()
+        .enumerate()
+        .filter(|(_, schema)| !new.contains(schema))
+        .collect();
+    let added: Vec<(usize, &Value)> = new
+        .iter()
+        .enumerate()
+        .filter(|(_, schema)| !old.contains(schema))
+        .collect();
+    let changed = removed.len().min(added.len());
+    for ((_, old_schema), (index, new_schema)) in removed.iter().zip(&added) {
+        diff_schemas(
+            &format!("{}/{}", pointer, index),
+            old_schema,
+            new_schema,
+            changes,
+        );
+    }
+    // Every schema of `allOf` restricts the contents, while the others are alternatives,
+    // of which `oneOf` only accepts one
+    for (index, _) in removed.into_iter().skip(changed) {
+        changes.push(SchemaChange {
+            pointer: format!("{}/{}", pointer, index),
+            breaking: keyword != "allOf",
+            reason: format!("a schema was removed from `{}`", keyword),
+        });
+    }
+    for (index, _) in added.into_iter().skip(changed) {
+        changes.push(SchemaChange {
+            pointer: format!("{}/{}", pointer, index),
+            breaking: keyword != "anyOf",
+            reason: format!("a schema was added to `{}`", keyword),
+        });
+    }
+}
+
+/// Reads a string or an array of strings as a set.
+fn string_set(value: Option<&Value>) -> BTreeSet<String> {
+    match value {
+        Some(Value::String(s)) => BTreeSet::from([s.clone()]),
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+/// Reads an array as a list of values.
+fn value_list(value: Option<&Value>) -> Vec<Value> {
+    value.and_then(Value::as_array).cloned().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn module(types: Value, contents: Value) -> Module {
+        serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": types,
+            "contents": contents
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn identical_modules_have_no_changes() {
+        let old = module(json!({}), json!({"a": {"type": "spell"}}));
+
+        assert!(diff(&old, &old.clone()).is_empty());
+    }
+
+    #[test]
+    fn reports_added_removed_and_modified_contents() {
+        let old = module(
+            json!({}),
+            json!({
+                "a": {"type": "spell", "level": 1},
+                "b": {"type": "spell"}
+            }),
+        );
+        let new = module(
+            json!({}),
+            json!({
+                "a": {"type": "spell", "level": 2, "school": "fire"},
+                "c": {"type": "spell"}
+            }),
+        );
+
+        let result = diff(&old, &new);
+
+        assert_eq!(result.contents.added, vec!["c"]);
+        assert_eq!(result.contents.removed, vec!["b"]);
+        assert_eq!(
+            result.contents.modified["a"].fields,
+            vec![
+                FieldChange {
+                    pointer: "/level".to_string(),
+                    old: Some(json!(1)),
+                    new: Some(json!(2))
+                },
+                FieldChange {
+                    pointer: "/school".to_string(),
+                    old: None,
+                    new: Some(json!("fire"))
+                }
+            ]
+        );
+        assert!(!result.is_breaking());
+    }
+
+    #[test]
+    fn metadata_changes_are_reported() {
+        let old = module(json!({}), json!({}));
+        let mut new = old.clone();
+        new.title = "New title".to_string();
+
+        assert_eq!(
+            diff(&old, &new).metadata,
+            vec![FieldChange {
+                pointer: "/title".to_string(),
+                old: Some(json!("My title")),
+                new: Some(json!("New title"))
+            }]
+        );
+    }
+
+    #[test]
+    fn removing_a_type_is_breaking() {
+        let old = module(json!({"spell": {"description": "a spell"}}), json!({}));
+        let new = module(json!({}), json!({}));
+
+        assert!(diff(&old, &new).is_breaking());
+    }
+
+    #[test]
+    fn relaxing_a_schema_is_not_breaking() {
+        let old = module(
+            json!({"spell": {"description": "a spell", "schema": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": {"type": "string", "maxLength": 10},
+                    "school": {"enum": ["fire", "ice"]}
+                }
+            }}}),
+            json!({}),
+        );
+        let new = module(
+            json!({"spell": {"description": "a nicer spell", "schema": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": ["string", "null"], "maxLength": 20},
+                    "school": {"enum": ["fire", "ice", "earth"]},
+                    "level": {"type": "integer"}
+                }
+            }}}),
+            json!({}),
+        );
+
+        let result = diff(&old, &new);
+
+        assert!(!result.is_breaking());
+        assert_eq!(result.types.modified["spell"].schema.len(), 5);
+    }
+
+    #[test]
+    fn restricting_a_schema_is_breaking() {
+        let old = module(
+            json!({"spell": {"description": "a spell", "schema": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "level": {"type": "integer", "minimum": 0}
+                }
+            }}}),
+            json!({}),
+        );
+        let new = module(
+            json!({"spell": {"description": "a spell", "schema": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": {"type": "string"},
+                    "level": {"type": "integer", "minimum": 1}
+                }
+            }}}),
+            json!({}),
+        );
+
+        let changes = &diff(&old, &new).types.modified["spell"].schema;
+
+        assert_eq!(
+            changes,
+            &vec![
+                SchemaChange {
+                    pointer: "/schema/properties/level/minimum".to_string(),
+                    breaking: true,
+                    reason: "the lower bound `minimum` changed".to_string()
+                },
+                SchemaChange {
+                    pointer: "/schema/required".to_string(),
+                    breaking: true,
+                    reason: "the property `name` became required".to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn layouts_and_partials_are_metadata() {
+        let old = module(json!({}), json!({}));
+        let mut new = old.clone();
+        new.rendering = Some(BTreeMap::from([(
+            "md".to_string(),
+            "{{contents}}".to_string(),
+        )]));
+        new.partials = Some(BTreeMap::from([(
+            "name".to_string(),
+            "{{name}}".to_string(),
+        )]));
+
+        assert_eq!(
+            diff(&old, &new).metadata,
+            vec![
+                FieldChange {
+                    pointer: "/partials".to_string(),
+                    old: Some(Value::Null),
+                    new: Some(json!({"name": "{{name}}"}))
+                },
+                FieldChange {
+                    pointer: "/rendering".to_string(),
+                    old: Some(Value::Null),
+                    new: Some(json!({"md": "{{contents}}"}))
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn composition_keywords_are_classified() {
+        let old = module(
+            json!({"spell": {"description": "a spell", "schema": {
+                "allOf": [{"required": ["name"]}, {"required": ["level"]}],
+                "anyOf": [{"type": "string"}, {"type": "integer", "maximum": 5}],
+                "oneOf": [{"type": "string"}, {"type": "integer"}]
+            }}}),
+            json!({}),
+        );
+        let new = module(
+            json!({"spell": {"description": "a spell", "schema": {
+                "allOf": [{"required": ["name"]}],
+                "anyOf": [{"type": "string"}, {"type": "integer", "maximum": 10}, {"type": "null"}],
+                "oneOf": [{"type": "string"}]
+            }}}),
+            json!({}),
+        );
+
+        let result = diff(&old, &new);
+
+        assert_eq!(
+            result.types.modified["spell"].schema,
+            vec![
+                SchemaChange {
+                    pointer: "/schema/allOf/1".to_string(),
+                    breaking: false,
+                    reason: "a schema was removed from `allOf`".to_string()
+                },
+                SchemaChange {
+                    pointer: "/schema/anyOf/1/maximum".to_string(),
+                    breaking: false,
+                    reason: "the upper bound `maximum` changed".to_string()
+                },
+                SchemaChange {
+                    pointer: "/schema/anyOf/2".to_string(),
+                    breaking: false,
+                    reason: "a schema was added to `anyOf`".to_string()
+                },
+                SchemaChange {
+                    pointer: "/schema/oneOf/1".to_string(),
+                    breaking: true,
+                    reason: "a schema was removed from `oneOf`".to_string()
+                }
+            ]
+        );
+        assert!(result.is_breaking());
+    }
+}
@@ -0,0 +1,235 @@
+use std::io::Write;
+
+use tracing::instrument;
+
+use crate::{module::Module, FormatError};
+
+/// The formats a Module can be stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleFormat {
+    /// [JSON](https://www.json.org/), the default format.
+    Json,
+    /// [CBOR](https://cbor.io/), a binary format.
+    Cbor,
+    /// [MessagePack](https://msgpack.org/), a binary format.
+    MessagePack,
+    /// [YAML](https://yaml.org/), a format meant for humans.
+    Yaml,
+    /// [TOML](https://toml.io/), a format meant for humans.
+    ///
+    /// It can not represent `null` values, so modules that have them can not be written in it.
+    Toml,
+}
+
+impl ModuleFormat {
+    /// All the supported formats.
+    pub const ALL: [ModuleFormat; 5] = [
+        ModuleFormat::Json,
+        ModuleFormat::Cbor,
+        ModuleFormat::MessagePack,
+        ModuleFormat::Yaml,
+        ModuleFormat::Toml,
+    ];
+
+    /// The extension of files in this format, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ModuleFormat::Json => "json",
+            ModuleFormat::Cbor => "cbor",
+            ModuleFormat::MessagePack => "msgpack",
+            ModuleFormat::Yaml => "yaml",
+            ModuleFormat::Toml => "toml",
+        }
+    }
+
+    /// Finds the format of a file from it's extension, ignoring the case.
+    pub fn from_extension(extension: &str) -> Option<ModuleFormat> {
+        match extension.to_lowercase().as_str() {
+            "json" => Some(ModuleFormat::Json),
+            "cbor" => Some(ModuleFormat::Cbor),
+            "msgpack" | "mpk" => Some(ModuleFormat::MessagePack),
+            "yaml" | "yml" => Some(ModuleFormat::Yaml),
+            "toml" => Some(ModuleFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl Module {
+    /// Serializes the module in a format.
+    ///
+    /// JSON is written without any spacing.
+    #[instrument(skip(self))]
+    pub fn to_bytes(&self, format: ModuleFormat) -> Result<Vec<u8>, FormatError> {
+        let mut result = Vec::new();
+        self.to_writer(&mut result, format)?;
+        Ok(result)
+    }
+
+    /// Serializes the module in a format, directly into a writer.
+    ///
+    /// All formats but TOML are written as they are serialized,
+    /// without holding the entire output in memory.
+    #[instrument(skip(self, writer))]
+    pub fn to_writer<W: Write>(
+        &self,
+        mut writer: W,
+        format: ModuleFormat,
+    ) -> Result<(), FormatError> {
+        let to_error = |e: Box<dyn std::error::Error>| FormatError::Serialization(format, e);
+        match format {
+            ModuleFormat::Json => {
+                serde_json::to_writer(writer, self).map_err(|e| to_error(e.into()))
+            }
+            ModuleFormat::Cbor => {
+                ciborium::ser::into_writer(self, writer).map_err(|e| to_error(e.into()))
+            }
+            // Structs are written as maps, so optional fields can be skipped.
+            ModuleFormat::MessagePack => {
+                rmp_serde::encode::write_named(&mut writer, self).map_err(|e| to_error(e.into()))
+            }
+            ModuleFormat::Yaml => {
+                serde_yaml::to_writer(writer, self).map_err(|e| to_error(e.into()))
+            }
+            ModuleFormat::Toml => toml::to_string_pretty(self)
+                .map_err(|e| to_error(e.into()))
+                .and_then(|text| {
+                    writer
+                        .write_all(text.as_bytes())
+                        .map_err(|e| to_error(e.into()))
+                }),
+        }
+    }
+
+    /// Deserializes a module from a format.
+    #[instrument(skip(bytes))]
+    pub fn from_bytes(bytes: &[u8], format: ModuleFormat) -> Result<Module, FormatError> {
+        let to_error = |e: Box<dyn std::error::Error>| FormatError::Deserialization(format, e);
+        match format {
+            ModuleFormat::Json => serde_json::from_slice(bytes).map_err(|e| to_error(e.into())),
+            ModuleFormat::Cbor => ciborium::de::from_reader(bytes).map_err(|e| to_error(e.into())),
+            ModuleFormat::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| to_error(e.into()))
+            }
+            ModuleFormat::Yaml => serde_yaml::from_slice(bytes).map_err(|e| to_error(e.into())),
+            ModuleFormat::Toml => std::str::from_utf8(bytes)
+                .map_err(|e| to_error(e.into()))
+                .and_then(|text| toml::from_str(text).map_err(|e| to_error(e.into()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn module() -> Module {
+        serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "spell": {
+                    "description": "a spell",
+                    "schema": {"properties": {"cost": {"type": "number", "minimum": -1.5}}},
+                    "rendering": {"md": "# {{name}}"}
+                },
+                "note": {"description": "a note", "extends": "spell"}
+            },
+            "contents": {
+                "fireball": {
+                    "type": "spell",
+                    "name": "Fireball",
+                    "cost": 3,
+                    "ratio": 0.25,
+                    "offset": -2,
+                    "tags": ["fire", "ice"],
+                    "visible": true,
+                    "nested": {"deep": {"deeper": []}}
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trips_in_every_format() {
+        let module = module();
+
+        for format in ModuleFormat::ALL {
+            let bytes = module.to_bytes(format).unwrap();
+
+            assert_eq!(
+                Module::from_bytes(&bytes, format).unwrap(),
+                module,
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_without_optional_fields() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org"
+        }))
+        .unwrap();
+
+        for format in ModuleFormat::ALL {
+            let bytes = module.to_bytes(format).unwrap();
+
+            assert_eq!(
+                Module::from_bytes(&bytes, format).unwrap(),
+                module,
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn fails_to_write_null_values_as_toml() {
+        let mut module = module();
+        module
+            .contents
+            .as_mut()
+            .unwrap()
+            .get_mut("fireball")
+            .unwrap()
+            .insert("cost".to_string(), json!(null));
+
+        assert!(matches!(
+            module.to_bytes(ModuleFormat::Toml),
+            Err(FormatError::Serialization(ModuleFormat::Toml, _))
+        ));
+        assert!(module.to_bytes(ModuleFormat::Yaml).is_ok());
+    }
+
+    #[test]
+    fn fails_to_read_other_formats() {
+        let bytes = module().to_bytes(ModuleFormat::Cbor).unwrap();
+
+        assert!(matches!(
+            Module::from_bytes(&bytes, ModuleFormat::Json),
+            Err(FormatError::Deserialization(ModuleFormat::Json, _))
+        ));
+    }
+
+    #[test]
+    fn finds_format_from_extension() {
+        assert_eq!(
+            ModuleFormat::from_extension("CBOR"),
+            Some(ModuleFormat::Cbor)
+        );
+        assert_eq!(
+            ModuleFormat::from_extension("mpk"),
+            Some(ModuleFormat::MessagePack)
+        );
+        assert_eq!(ModuleFormat::from_extension("txt"), None);
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::module::Module;
+
+/// The algorithm used for all hashes.
+pub const HASH_ALGORITHM: &str = "sha256";
+
+/// The hashes of a Module, and each of it's types and contents.
+///
+/// The hashes are calculated over the minimized JSON representation,
+/// whose keys are always sorted, so they do not depend on the format
+/// the module is stored in, nor on the order the sources were read.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Integrity {
+    /// The algorithm used to calculate the hashes.
+    pub algorithm: String,
+    /// The hash of the entire module.
+    pub module: String,
+    /// The hashes of each type, keyed by their identifier.
+    pub types: BTreeMap<String, String>,
+    /// The hashes of each content, keyed by their identifier.
+    pub contents: BTreeMap<String, String>,
+}
+
+/// A difference between two [Integrity] records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityMismatch {
+    /// The hashes were calculated with different algorithms.
+    Algorithm(String, String),
+    /// The whole module is different.
+    Module,
+    /// A type is different, or only exists in one of the records.
+    Type(String),
+    /// A content is different, or only exists in one of the records.
+    Content(String),
+}
+
+impl std::fmt::Display for IntegrityMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityMismatch::Algorithm(expected, found) => {
+                write!(f, "expected hashes with `{}`, found `{}`", expected, found)
+            }
+            IntegrityMismatch::Module => write!(f, "the module does not match"),
+            IntegrityMismatch::Type(key) => write!(f, "the type `{}` does not match", key),
+            IntegrityMismatch::Content(id) => write!(f, "the content `{}` does not match", id),
+        }
+    }
+}
+
+/// Hashes the JSON representation of a value.
+fn hash<T: Serialize>(value: &T) -> String {
+    let bytes = serde_json::to_vec(value).expect("Modules should always be valid JSON Values");
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+impl Integrity {
+    /// Calculates the hashes of a Module.
+    #[instrument(skip(module))]
+    pub fn of(module: &Module) -> Integrity {
+        Integrity {
+            algorithm: HASH_ALGORITHM.to_string(),
+            module: hash(module),
+            types: module
+                .types
+                .iter()
+                .flatten()
+                .map(|(key, module_type)| (key.clone(), hash(module_type)))
+                .collect(),
+            contents: module
+                .contents
+                .iter()
+                .flatten()
+                .map(|(id, content)| (id.clone(), hash(content)))
+                .collect(),
+        }
+    }
+
+    /// Lists everything that differs from an expected record.
+    ///
+    /// An empty result means the hashes are the same.
+    pub fn compare(&self, expected: &Integrity) -> Vec<IntegrityMismatch> {
+        if self.algorithm != expected.algorithm {
+            return vec![IntegrityMismatch::Algorithm(
+                expected.algorithm.clone(),
+                self.algorithm.clone(),
+            )];
+        }
+        let mut result = Vec::new();
+        if self.module != expected.module {
+            result.push(IntegrityMismatch::Module);
+        }
+        result.extend(
+            different_keys(&self.types, &expected.types)
+                .into_iter()
+                .map(IntegrityMismatch::Type),
+        );
+        result.extend(
+            different_keys(&self.contents, &expected.contents)
+                .into_iter()
+                .map(IntegrityMismatch::Content),
+        );
+        result
+    }
+}
+
+/// The keys that are missing from either map, or have different values.
+fn different_keys(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>) -> Vec<String> {
+    let mut keys: Vec<String> = a
+        .keys()
+        .chain(b.keys())
+        .filter(|k| a.get(*k) != b.get(*k))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use fs::file_system::FileSystem;
+    use path_utils::{create_test_directory, create_test_file};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::convert::TryFrom;
+    use testdir::testdir;
+
+    fn module(cost: i64) -> Module {
+        serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {"description": "a spell"}},
+            "contents": {
+                "fireball": {"type": "spell", "cost": cost},
+                "frostbolt": {"type": "spell", "cost": 1}
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn same_modules_have_same_hashes() {
+        assert_eq!(Integrity::of(&module(3)), Integrity::of(&module(3)));
+        assert_eq!(
+            Integrity::of(&module(3)).compare(&Integrity::of(&module(3))),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn reports_changed_entries() {
+        let expected = Integrity::of(&module(3));
+        let mut changed = module(4);
+        changed.types = None;
+
+        assert_eq!(
+            Integrity::of(&changed).compare(&expected),
+            vec![
+                IntegrityMismatch::Module,
+                IntegrityMismatch::Type("spell".to_string()),
+                IntegrityMismatch::Content("fireball".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_do_not_depend_on_the_order_files_are_created() {
+        let dir = testdir!();
+        let names = ["b", "a", "c"];
+        let mut outputs = Vec::new();
+        for (index, order) in [names, [names[2], names[0], names[1]]].iter().enumerate() {
+            let source = create_test_directory(&dir.join(format!("source_{}", index)));
+            let module_directory = create_test_directory(&source.join("module"));
+            create_test_file(&module_directory.join("title.md"), "My title");
+            create_test_file(&module_directory.join("description.md"), "My description");
+            create_test_file(
+                &module_directory.join("_.json"),
+                r#"{"source": "https://powerd6.org"}"#,
+            );
+            let contents = create_test_directory(&source.join("contents"));
+            for name in order {
+                let content = create_test_directory(&contents.join(name));
+                create_test_file(
+                    &content.join("_.json"),
+                    r#"{"type": "spell", "cost": {"b": 1, "a": 2}}"#,
+                );
+                create_test_file(&content.join("name.md"), name);
+            }
+            let module = Module::try_from(FileSystem::try_from(source).unwrap()).unwrap();
+            outputs.push(serde_json::to_vec(&module).unwrap());
+        }
+
+        assert_eq!(outputs[0], outputs[1]);
+    }
+}
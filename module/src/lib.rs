@@ -16,6 +16,55 @@ pub enum ModuleError {
     IncompatibleFieldType(Box<Value>),
     #[error("Unable to create a valid identifier from entry: `{0:#?}`.")]
     InvalidIdentifier(Box<Entry>),
+    #[error("the type `{0}` extends the type `{1}`, which does not exist")]
+    MissingParentType(String, String),
+    #[error("the types inherit from each other in a cycle: {0}")]
+    CyclicTypeInheritance(String),
+    #[error("the content `{0}` does not exist")]
+    MissingContent(String),
+    #[error("the content `{0}` does not have the expected shape")]
+    InvalidContent(String, #[source] Box<dyn Error>),
+    #[error("the locale `{0}` is not declared by a directory in `locales`")]
+    UnknownLocale(String),
+    #[error("a type and the type it extends define `{0}` differently in their schemas")]
+    ConflictingDefinition(String),
+}
+
+/// The errors that can happen when merging Modules.
+#[derive(Error, Debug, PartialEq)]
+pub enum MergeError {
+    #[error("no modules were provided to be merged")]
+    NoModules,
+    #[error("the type `{0}` is defined differently by `{1}` and `{2}`")]
+    ConflictingType(String, String, String),
+    #[error("the content `{0}` is defined differently by `{1}` and `{2}`")]
+    ConflictingContent(String, String, String),
+    #[error("the template `{0}` is defined differently by `{1}` and `{2}`")]
+    ConflictingTemplate(String, String, String),
+}
+
+/// The errors that can happen when converting Modules from and into a format.
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("unable to write the module as {0:?}")]
+    Serialization(format::ModuleFormat, #[source] Box<dyn Error>),
+    #[error("unable to read the module as {0:?}")]
+    Deserialization(format::ModuleFormat, #[source] Box<dyn Error>),
+}
+
+/// The errors that can happen when signing Modules or verifying their signatures.
+#[derive(Error, Debug, PartialEq)]
+pub enum SignatureError {
+    #[error("the key could not be read: {0}")]
+    InvalidKey(String),
+    #[error("signatures with `{0}` are not supported")]
+    UnsupportedAlgorithm(String),
+    #[error("the signature is not correctly encoded: {0}")]
+    InvalidEncoding(String),
+    #[error("the module was signed by a key that is not trusted: `{0}`")]
+    UntrustedKey(String),
+    #[error("the signature does not match the module")]
+    InvalidSignature,
 }
 
 /// The key to the contents property.
@@ -28,17 +77,49 @@ pub const DESCRIPTION: &str = "description";
 pub const SCHEMA: &str = "schema";
 /// The key to the rendering property.
 pub const RENDERING: &str = "rendering";
+/// The key to the partials property.
+pub const PARTIALS: &str = "partials";
+/// The prefix of the keys of the rendering property that hold the headers
+/// of groups of contents, like `@group/md`.
+pub const GROUP_HEADERS: &str = "@group";
+/// The prefix of the keys of the rendering property that hold the indexes
+/// of split outputs, like `@index/html`.
+pub const INDEXES: &str = "@index";
 /// The key to the title property.
 pub const TITLE: &str = "title";
 /// The key to the source property.
 pub const SOURCE: &str = "source";
+/// The key to the extends property.
+pub const EXTENDS: &str = "extends";
+/// The schema keyword that marks a property as a reference to another content.
+pub const REFERENCE: &str = "x-reference";
 /// The key to the type property.
 pub const TYPE_KEY: &str = "type";
 
 /// A generic JSON Map, similar to [serde's Value::Object](serde_json::Value::Object).
 type JsonMap = BTreeMap<String, Value>;
 
+/// Handles creating Modules programmatically.
+pub mod builder;
+/// Handles filling contents with the default values of their schemas.
+pub mod defaults;
+/// Handles comparing two versions of a Module.
+pub mod diff;
+/// Handles the formats Modules can be stored in.
+pub mod format;
+/// Handles hashing Modules to verify their integrity.
+pub mod integrity;
+/// Handles translations of Modules into multiple locales.
+pub mod locale;
+/// Handles combining several Modules into one.
+pub mod merge;
 /// Handles a Module.
 pub mod module;
 /// Handles a Type inside a Module.
 pub mod module_type;
+/// Handles references between the contents of a Module.
+pub mod references;
+/// Handles the JSON Schemas of Modules and their components.
+pub mod schema;
+/// Handles signing Modules and verifying their signatures.
+pub mod signature;
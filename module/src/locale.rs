@@ -0,0 +1,418 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::SystemTime,
+};
+
+use fs::{entry::Entry, file_system::FileSystem};
+use fs_data::{EntryData, Localization};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::{instrument, warn};
+
+use crate::{JsonMap, ModuleError, CONTENTS, DESCRIPTION, TITLE, TYPES, TYPE_KEY};
+
+/// A JSON object, as read from the file system.
+type JsonObject = Map<String, Value>;
+
+/// Reads the translated contents of a locale from the `locales` directory,
+/// keyed by their identifiers, next to the entries they were read from.
+fn try_get_locale_entries<'a>(
+    filesystem: &'a FileSystem,
+    localization: Localization,
+) -> Result<BTreeMap<String, (JsonObject, &'a Entry)>, ModuleError> {
+    let mut result = BTreeMap::new();
+    if let Some(entry_set) = localization.locale.and_then(|l| filesystem.locales.get(l)) {
+        for entry in entry_set.entries.iter() {
+            let identifier = entry_set
+                .get_identifier_for_entry(entry)
+                .ok_or_else(|| ModuleError::InvalidIdentifier(Box::new(entry.clone())))?;
+            let data = entry
+                .try_get_localized_data(localization)
+                .map_err(|e| ModuleError::UnableToGetRequiredData(e.into()))?;
+            match data {
+                Value::Object(object) => {
+                    result.insert(identifier, (object, entry));
+                }
+                other => return Err(ModuleError::NotAnObject(other.into())),
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Applies the translated contents of the `locales` directory over the contents.
+///
+/// Translated contents only replace the fields they define.
+pub(crate) fn apply_locale_contents(
+    contents: &mut BTreeMap<String, JsonMap>,
+    filesystem: &FileSystem,
+    localization: Localization,
+) -> Result<(), ModuleError> {
+    for (content_id, (translation, _)) in try_get_locale_entries(filesystem, localization)? {
+        match contents.get_mut(&content_id) {
+            Some(content) => content.extend(translation),
+            None => warn!(
+                "Translation to `{}` exists for content `{}`, which does not exist",
+                localization.locale.unwrap_or_default(),
+                content_id
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// The state of the translation of a source directory into a locale.
+///
+/// Keys are identified by their location, such as `module/title`,
+/// `types/spell/description` or `contents/fireball/name`.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct TranslationReport {
+    /// The locale this report is about.
+    pub locale: String,
+    /// Keys that exist in the default locale, but have no translation.
+    pub untranslated: Vec<String>,
+    /// Translations for keys (or contents) that no longer exist in the default locale,
+    /// or whose files are older than the files of the default locale they translate.
+    pub stale: Vec<String>,
+}
+
+/// Finds every locale declared in a source directory.
+#[instrument(skip(filesystem))]
+pub fn find_locales(filesystem: &FileSystem) -> BTreeSet<String> {
+    filesystem.known_locales().into_iter().collect()
+}
+
+impl TranslationReport {
+    /// Compares the translations of a locale with the default locale of a source directory.
+    ///
+    /// Only text fields are considered translatable: the title and description
+    /// of the module, the description of types, and every string property of
+    /// contents (except their type).
+    ///
+    /// Translations are outdated when their file was modified before the file
+    /// of the default locale they translate, so they should be updated (or
+    /// saved again) after the default locale changes.
+    #[instrument(skip(filesystem))]
+    pub fn try_from_file_system(
+        filesystem: &FileSystem,
+        locale: &str,
+    ) -> Result<TranslationReport, ModuleError> {
+        let known_locales = filesystem.known_locales();
+        if !known_locales.iter().any(|k| k == locale) {
+            return Err(ModuleError::UnknownLocale(locale.to_string()));
+        }
+        let default_locale = Localization {
+            locale: None,
+            known_locales: &known_locales,
+        };
+        let localization = Localization {
+            locale: Some(locale),
+            ..default_locale
+        };
+        let mut report = TranslationReport {
+            locale: locale.to_string(),
+            ..Default::default()
+        };
+        let locale_entries = try_get_locale_entries(filesystem, localization)?;
+        let raw_objects = try_get_raw_objects(filesystem, default_locale)?;
+        let mut content_ids = BTreeSet::new();
+        for RawObject {
+            location,
+            object,
+            entry,
+        } in &raw_objects
+        {
+            let content_id = location.strip_prefix(&format!("{}/", CONTENTS));
+            if let Some(content_id) = content_id {
+                content_ids.insert(content_id.to_string());
+            }
+            let translated_fields = content_id.and_then(|id| locale_entries.get(id));
+            let translated_files: BTreeSet<String> = entry
+                .get_translated_keys(&known_locales)
+                .into_iter()
+                .filter(|(_, key_locale)| key_locale == locale)
+                .map(|(key, _)| key)
+                .collect();
+            for (key, value) in object {
+                let translated = translated_files.contains(key)
+                    || translated_fields.is_some_and(|(t, _)| t.contains_key(key));
+                if is_translatable(location, key, value) && !translated {
+                    report.untranslated.push(format!("{}/{}", location, key));
+                }
+            }
+            report.stale.extend(
+                translated_files
+                    .iter()
+                    .filter(|key| {
+                        !object.contains_key(*key)
+                            || is_outdated(
+                                entry.get_modified_time(key, localization),
+                                entry.get_modified_time(key, default_locale),
+                            )
+                    })
+                    .map(|key| format!("{}/{}.{}", location, key, locale)),
+            );
+            if let Some((translated_fields, translation_entry)) = translated_fields {
+                report.stale.extend(
+                    translated_fields
+                        .keys()
+                        .filter(|k| {
+                            !object.contains_key(*k)
+                                || is_outdated(
+                                    translation_entry.get_modified_time(k, localization),
+                                    entry.get_modified_time(k, default_locale),
+                                )
+                        })
+                        .map(|k| format!("{}/{}", location, k)),
+                );
+            }
+        }
+        report.stale.extend(
+            locale_entries
+                .keys()
+                .filter(|id| !content_ids.contains(*id))
+                .map(|id| format!("{}/{}", CONTENTS, id)),
+        );
+        Ok(report)
+    }
+}
+
+/// Checks if a translation was modified before the value it translates.
+fn is_outdated(translation: Option<SystemTime>, default: Option<SystemTime>) -> bool {
+    matches!((translation, default), (Some(t), Some(d)) if t < d)
+}
+
+/// Checks if a field of the default locale is expected to be translated.
+fn is_translatable(location: &str, key: &str, value: &Value) -> bool {
+    if !value.is_string() {
+        return false;
+    }
+    if location.starts_with(&format!("{}/", CONTENTS)) {
+        key != TYPE_KEY
+    } else if location.starts_with(&format!("{}/", TYPES)) {
+        key == DESCRIPTION
+    } else {
+        key == TITLE || key == DESCRIPTION
+    }
+}
+
+/// The data of the module, a type or a content in the default locale,
+/// identified by it's location, next to the entry it was read from.
+struct RawObject {
+    location: String,
+    object: JsonObject,
+    entry: Entry,
+}
+
+/// Reads the data of the module, types and contents in the default locale,
+/// identified by their location.
+///
+/// Types and contents defined inside the module are read from the file of the module.
+fn try_get_raw_objects(
+    filesystem: &FileSystem,
+    default_locale: Localization,
+) -> Result<Vec<RawObject>, ModuleError> {
+    let mut result = Vec::new();
+    let module_data = filesystem
+        .module
+        .try_get_localized_data(default_locale)
+        .map_err(|e| ModuleError::UnableToGetRequiredData(e.into()))?;
+    let Value::Object(mut module_object) = module_data else {
+        return Err(ModuleError::NotAnObject(module_data.into()));
+    };
+    let module_file = match &filesystem.module {
+        Entry::File(file) => file,
+        Entry::Directory { root_file, .. } | Entry::RenderingDirectory { root_file, .. } => {
+            root_file
+        }
+    };
+    for key in [TYPES, CONTENTS] {
+        if let Some(Value::Object(entries)) = module_object.remove(key) {
+            for (id, entry) in entries {
+                if let Value::Object(object) = entry {
+                    result.push(RawObject {
+                        location: format!("{}/{}", key, id),
+                        object,
+                        entry: Entry::File(module_file.clone()),
+                    });
+                }
+            }
+        }
+    }
+    result.insert(
+        0,
+        RawObject {
+            location: "module".to_string(),
+            object: module_object,
+            entry: filesystem.module.clone(),
+        },
+    );
+    for (key, entry_set) in [(TYPES, &filesystem.types), (CONTENTS, &filesystem.contents)] {
+        if let Some(entry_set) = entry_set {
+            for entry in entry_set.entries.iter() {
+                let identifier = entry_set
+                    .get_identifier_for_entry(entry)
+                    .ok_or_else(|| ModuleError::InvalidIdentifier(Box::new(entry.clone())))?;
+                let location = format!("{}/{}", key, identifier);
+                if let Value::Object(object) = entry
+                    .try_get_localized_data(default_locale)
+                    .map_err(|e| ModuleError::UnableToGetRequiredData(e.into()))?
+                {
+                    // Entries from the file system replace the ones from the module.
+                    result.retain(|raw| raw.location != location);
+                    result.push(RawObject {
+                        location,
+                        object,
+                        entry: entry.clone(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use fs::{CONTENTS_DIRECTORY, LOCALES_DIRECTORY};
+    use path_utils::{create_test_directory, create_test_file};
+    use pretty_assertions::assert_eq;
+    use testdir::testdir;
+
+    #[test]
+    fn reports_untranslated_and_stale_keys() {
+        let dir = testdir!();
+
+        let module_directory = create_test_directory(&dir.join("module"));
+        create_test_file(
+            &module_directory.join("_.json"),
+            r#"{
+                "description": "My description",
+                "source": "https://powerd6.org"
+            }"#,
+        );
+        create_test_file(&module_directory.join("title.md"), "My title");
+        create_test_file(&module_directory.join("title.pt.md"), "Meu titulo");
+        create_test_file(&module_directory.join("subtitle.pt.md"), "Meu subtitulo");
+        let contents_directory = create_test_directory(&dir.join(CONTENTS_DIRECTORY));
+        create_test_file(
+            &contents_directory.join("a.json"),
+            r#"{
+                "type": "spell",
+                "name": "Fireball",
+                "effect": "Burns",
+                "stats.max": "5",
+                "level": 1
+            }"#,
+        );
+        let locales_directory = create_test_directory(&dir.join(LOCALES_DIRECTORY));
+        let portuguese_directory = create_test_directory(&locales_directory.join("pt"));
+        create_test_file(
+            &portuguese_directory.join("a.json"),
+            r#"{
+                "name": "Bola de fogo",
+                "damage": "2d6"
+            }"#,
+        );
+        create_test_file(&portuguese_directory.join("b.json"), r#"{"name": "Gelo"}"#);
+
+        let filesystem = FileSystem::try_from(dir).unwrap();
+
+        assert_eq!(
+            find_locales(&filesystem),
+            BTreeSet::from(["pt".to_string()])
+        );
+        assert_eq!(
+            TranslationReport::try_from_file_system(&filesystem, "pt").unwrap(),
+            TranslationReport {
+                locale: "pt".to_string(),
+                untranslated: vec![
+                    "module/description".to_string(),
+                    "contents/a/effect".to_string(),
+                    "contents/a/stats.max".to_string()
+                ],
+                stale: vec![
+                    "module/subtitle.pt".to_string(),
+                    "contents/a/damage".to_string(),
+                    "contents/b".to_string()
+                ]
+            }
+        );
+    }
+
+    /// Moves the modification time of a file to the past.
+    fn make_older(file: &std::path::Path) {
+        std::fs::File::options()
+            .write(true)
+            .open(file)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH)
+            .unwrap();
+    }
+
+    #[test]
+    fn reports_translations_older_than_the_default_locale() {
+        let dir = testdir!();
+
+        let module_directory = create_test_directory(&dir.join("module"));
+        create_test_file(
+            &module_directory.join("_.json"),
+            r#"{"source": "https://powerd6.org"}"#,
+        );
+        create_test_file(&module_directory.join("title.md"), "My new title");
+        make_older(&create_test_file(
+            &module_directory.join("title.pt.md"),
+            "Meu titulo",
+        ));
+        create_test_file(&module_directory.join("description.md"), "My description");
+        create_test_file(
+            &module_directory.join("description.pt.md"),
+            "Minha descricao",
+        );
+        let contents_directory = create_test_directory(&dir.join(CONTENTS_DIRECTORY));
+        create_test_file(
+            &contents_directory.join("a.json"),
+            r#"{"type": "spell", "name": "Fireball"}"#,
+        );
+        let locales_directory = create_test_directory(&dir.join(LOCALES_DIRECTORY));
+        let portuguese_directory = create_test_directory(&locales_directory.join("pt"));
+        make_older(&create_test_file(
+            &portuguese_directory.join("a.json"),
+            r#"{"name": "Bola de fogo"}"#,
+        ));
+
+        let filesystem = FileSystem::try_from(dir).unwrap();
+
+        assert_eq!(
+            TranslationReport::try_from_file_system(&filesystem, "pt").unwrap(),
+            TranslationReport {
+                locale: "pt".to_string(),
+                untranslated: vec![],
+                stale: vec!["module/title.pt".to_string(), "contents/a/name".to_string()]
+            }
+        );
+    }
+
+    #[test]
+    fn reports_only_declared_locales() {
+        let dir = testdir!();
+
+        create_test_file(
+            &dir.join("module.json"),
+            r#"{
+                "title": "My title",
+                "description": "My description",
+                "source": "https://powerd6.org"
+            }"#,
+        );
+
+        let filesystem = FileSystem::try_from(dir).unwrap();
+
+        assert!(matches!(
+            TranslationReport::try_from_file_system(&filesystem, "pt"),
+            Err(ModuleError::UnknownLocale(locale)) if locale == "pt"
+        ));
+    }
+}
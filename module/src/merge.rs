@@ -0,0 +1,984 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, instrument, warn};
+use url::Url;
+
+use crate::{module::Module, module_type::ModuleType, JsonMap, MergeError, REFERENCE, TYPE_KEY};
+
+/// How identifiers that are defined differently by more than one module are handled.
+///
+/// Entries that are exactly the same in every module that defines them are
+/// never considered conflicts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Stop the merge with an error.
+    Fail,
+    /// Keep the entry of the first module that defines it.
+    FirstWins,
+    /// Keep the entry of the last module that defines it.
+    LastWins,
+    /// Keep every entry, prefixing their identifiers with the namespace of
+    /// the module that defines them.
+    ///
+    /// The types, contents and templates of each module are updated to use
+    /// the new identifiers of it's entries. Layouts are identified by their
+    /// format, which can't be prefixed, so conflicting layouts fail instead.
+    Namespaced,
+}
+
+/// How a metadata field (like the title) of the merged modules is combined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataPolicy {
+    /// Use the value of the first module.
+    First,
+    /// Use the value of the last module.
+    Last,
+    /// Join the values of all modules.
+    Join,
+    /// Ignore the values of the modules and use the provided one instead.
+    Custom(String),
+}
+
+/// The configuration of a merge operation.
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// How conflicting types are handled.
+    pub types: ConflictPolicy,
+    /// How conflicting contents are handled.
+    pub contents: ConflictPolicy,
+    /// How conflicting layouts and partials of the modules are handled.
+    pub templates: ConflictPolicy,
+    /// How the titles are combined.
+    pub title: MetadataPolicy,
+    /// How the descriptions are combined.
+    pub description: MetadataPolicy,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        MergeOptions {
+            types: ConflictPolicy::Fail,
+            contents: ConflictPolicy::Fail,
+            templates: ConflictPolicy::Fail,
+            title: MetadataPolicy::First,
+            description: MetadataPolicy::First,
+        }
+    }
+}
+
+/// A module that takes part in a merge.
+#[derive(Debug, Clone)]
+pub struct MergeSource {
+    /// A short name that identifies the module,
+    /// used to report conflicts and to prefix namespaced identifiers.
+    pub namespace: String,
+    /// The module to be merged.
+    pub module: Module,
+}
+
+/// Where an entry of a merged module came from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    /// The namespace of the source module.
+    pub namespace: String,
+    /// The source of the module, as declared by it.
+    pub source: Url,
+    /// The identifier of the entry inside the source module.
+    pub id: String,
+}
+
+/// The origin of every type and content of a merged module,
+/// keyed by their identifiers in the merged module.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Provenance {
+    pub types: BTreeMap<String, Origin>,
+    pub contents: BTreeMap<String, Origin>,
+}
+
+/// The result of merging modules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedModule {
+    pub module: Module,
+    pub provenance: Provenance,
+}
+
+/// The identifiers that were namespaced, mapped to their new identifiers.
+type Renames = BTreeMap<String, String>;
+
+/// The entries of all sources, after being merged.
+struct MergedEntries<T> {
+    entries: BTreeMap<String, T>,
+    /// The index of the source each merged entry came from.
+    origins: BTreeMap<String, usize>,
+    /// The identifiers that were namespaced, for each of the sources.
+    renames: Vec<Renames>,
+}
+
+impl<T> MergedEntries<T> {
+    /// Finds the namespaced identifier of an entry used by a source.
+    ///
+    /// Sources prefer the entry they define themselves, and otherwise use the
+    /// entry defined by the first source that was namespaced.
+    fn renamed(
+        &self,
+        source_index: usize,
+        id: &str,
+        entries_per_source: &[BTreeMap<String, T>],
+    ) -> Option<&String> {
+        if entries_per_source[source_index].contains_key(id) {
+            self.renames[source_index].get(id)
+        } else {
+            self.renames.iter().find_map(|r| r.get(id))
+        }
+    }
+}
+
+/// The helpers that receive the identifier of a content as their first parameter.
+const CONTENT_HELPERS: [&str; 2] = ["renderContent", "lookupContent"];
+
+/// The separator used when joining titles.
+const TITLE_SEPARATOR: &str = " + ";
+/// The separator used when joining descriptions.
+const DESCRIPTION_SEPARATOR: &str = "\n\n";
+
+/// Combines the layouts or partials of all modules, following a conflict policy.
+fn merge_templates(
+    sources: &[MergeSource],
+    templates_per_source: &[BTreeMap<String, String>],
+    policy: ConflictPolicy,
+) -> Result<MergedEntries<String>, MergeError> {
+    merge_entries(
+        sources,
+        templates_per_source,
+        policy,
+        &mut BTreeMap::new(),
+        MergeError::ConflictingTemplate,
+    )
+}
+
+/// Combines several modules into a single one.
+///
+/// The order of the sources matters: the first module provides the `source`
+/// of the result, and defines what "first" and "last" mean for the policies.
+#[instrument(skip(sources))]
+pub fn merge(sources: &[MergeSource], options: &MergeOptions) -> Result<MergedModule, MergeError> {
+    let first = sources.first().ok_or(MergeError::NoModules)?;
+    info!("Merging {} modules", sources.len());
+
+    let layouts_per_source: Vec<BTreeMap<String, String>> = sources
+        .iter()
+        .map(|s| s.module.rendering.clone().unwrap_or_default())
+        .collect();
+    let layout_policy = match options.templates {
+        ConflictPolicy::Namespaced => ConflictPolicy::Fail,
+        policy => policy,
+    };
+    let mut layouts = merge_templates(sources, &layouts_per_source, layout_policy)?;
+    let partials_per_source: Vec<BTreeMap<String, String>> = sources
+        .iter()
+        .map(|s| s.module.partials.clone().unwrap_or_default())
+        .collect();
+    let mut partials = merge_templates(sources, &partials_per_source, options.templates)?;
+
+    let mut module = Module {
+        title: merge_metadata(sources, &options.title, TITLE_SEPARATOR, |m| &m.title),
+        description: merge_metadata(sources, &options.description, DESCRIPTION_SEPARATOR, |m| {
+            &m.description
+        }),
+        source: first.module.source.clone(),
+        rendering: None,
+        partials: None,
+        types: None,
+        contents: None,
+    };
+    let mut provenance = Provenance::default();
+
+    let types_per_source: Vec<BTreeMap<String, ModuleType>> = sources
+        .iter()
+        .map(|s| s.module.types.clone().unwrap_or_default())
+        .collect();
+    let mut types = merge_entries(
+        sources,
+        &types_per_source,
+        options.types,
+        &mut provenance.types,
+        MergeError::ConflictingType,
+    )?;
+
+    let contents_per_source: Vec<BTreeMap<String, JsonMap>> = sources
+        .iter()
+        .enumerate()
+        .map(|(index, s)| {
+            let mut contents = s.module.contents.clone().unwrap_or_default();
+            for content in contents.values_mut() {
+                rename_content_type(content, index, &types_per_source, &types);
+            }
+            contents
+        })
+        .collect();
+    let mut contents = merge_entries(
+        sources,
+        &contents_per_source,
+        options.contents,
+        &mut provenance.contents,
+        MergeError::ConflictingContent,
+    )?;
+
+    // Namespaced entries are only known after merging, so each source is
+    // then updated to use the new identifiers of it's own entries.
+    let mut type_entries = std::mem::take(&mut types.entries);
+    let content_entries = std::mem::take(&mut contents.entries);
+    let mut layout_entries = std::mem::take(&mut layouts.entries);
+    let mut partial_entries = std::mem::take(&mut partials.entries);
+    let rename_content = |index: usize, id: &str| contents.renamed(index, id, &contents_per_source);
+    let rename_partial = |index: usize, id: &str| partials.renamed(index, id, &partials_per_source);
+    for (id, module_type) in type_entries.iter_mut() {
+        let index = types.origins[id];
+        let rename_type = |id: &str| types.renamed(index, id, &types_per_source).cloned();
+        rename_type_references(module_type, &rename_type);
+        let own_partials = module_type.partials.clone().unwrap_or_default();
+        for template in module_type
+            .rendering
+            .iter_mut()
+            .chain(module_type.partials.iter_mut())
+            .flat_map(|templates| templates.values_mut())
+        {
+            *template = rename_template_references(
+                template,
+                |id| rename_content(index, id),
+                |name| {
+                    (!own_partials.contains_key(name))
+                        .then(|| rename_partial(index, name))
+                        .flatten()
+                },
+            );
+        }
+    }
+    for (templates, origins) in [
+        (&mut layout_entries, &layouts.origins),
+        (&mut partial_entries, &partials.origins),
+    ] {
+        for (name, template) in templates.iter_mut() {
+            let index = origins[name];
+            *template = rename_template_references(
+                template,
+                |id| rename_content(index, id),
+                |name| rename_partial(index, name),
+            );
+        }
+    }
+
+    if sources.iter().any(|s| s.module.rendering.is_some()) {
+        module.rendering = Some(layout_entries);
+    }
+    if sources.iter().any(|s| s.module.partials.is_some()) {
+        module.partials = Some(partial_entries);
+    }
+    if sources.iter().any(|s| s.module.types.is_some()) {
+        module.types = Some(type_entries);
+    }
+    if sources.iter().any(|s| s.module.contents.is_some()) {
+        module.contents = Some(content_entries);
+    }
+    rename_content_references(&mut module, |content_id, target| {
+        rename_content(contents.origins[content_id], target)
+    });
+    Ok(MergedModule { module, provenance })
+}
+
+/// Combines a metadata field from all sources according to the policy.
+fn merge_metadata(
+    sources: &[MergeSource],
+    policy: &MetadataPolicy,
+    separator: &str,
+    field: impl Fn(&Module) -> &String,
+) -> String {
+    match policy {
+        MetadataPolicy::First => field(&sources[0].module).clone(),
+        MetadataPolicy::Last => field(&sources[sources.len() - 1].module).clone(),
+        MetadataPolicy::Join => {
+            let mut seen = BTreeSet::new();
+            sources
+                .iter()
+                .map(|s| field(&s.module).as_str())
+                .filter(|value| seen.insert(*value))
+                .collect::<Vec<&str>>()
+                .join(separator)
+        }
+        MetadataPolicy::Custom(value) => value.clone(),
+    }
+}
+
+/// The identifier an entry receives when it is namespaced.
+fn namespaced_id(namespace: &str, id: &str) -> String {
+    format!("{}_{}", namespace, id)
+}
+
+/// Merges the entries of all sources, following a conflict policy.
+fn merge_entries<T: Clone + PartialEq>(
+    sources: &[MergeSource],
+    entries_per_source: &[BTreeMap<String, T>],
+    policy: ConflictPolicy,
+    provenance: &mut BTreeMap<String, Origin>,
+    conflict_error: fn(String, String, String) -> MergeError,
+) -> Result<MergedEntries<T>, MergeError> {
+    let conflicting_ids = find_conflicting_ids(entries_per_source);
+    let mut result: BTreeMap<String, T> = BTreeMap::new();
+    let mut origins: BTreeMap<String, usize> = BTreeMap::new();
+    let mut renames: Vec<Renames> = vec![Renames::new(); sources.len()];
+
+    for (index, (source, entries)) in sources.iter().zip(entries_per_source).enumerate() {
+        for (id, entry) in entries {
+            let origin = Origin {
+                namespace: source.namespace.clone(),
+                source: source.module.source.clone(),
+                id: id.clone(),
+            };
+            let mut target_id = id.clone();
+            if conflicting_ids.contains(id) {
+                match policy {
+                    ConflictPolicy::Fail => {
+                        if let Some(existing) = provenance.get(id) {
+                            return Err(conflict_error(
+                                id.clone(),
+                                existing.namespace.clone(),
+                                source.namespace.clone(),
+                            ));
+                        }
+                    }
+                    ConflictPolicy::FirstWins => {
+                        if result.contains_key(id) {
+                            debug!("Keeping the first definition of `{}`", id);
+                            continue;
+                        }
+                    }
+                    ConflictPolicy::LastWins => {
+                        debug!(
+                            "Using the definition of `{}` from `{}`",
+                            id, source.namespace
+                        );
+                    }
+                    ConflictPolicy::Namespaced => {
+                        target_id = namespaced_id(&source.namespace, id);
+                        renames[index].insert(id.clone(), target_id.clone());
+                        if let Some(existing) = provenance.get(&target_id) {
+                            return Err(conflict_error(
+                                target_id,
+                                existing.namespace.clone(),
+                                source.namespace.clone(),
+                            ));
+                        }
+                    }
+                }
+            } else if let Some(existing) = provenance.get(id) {
+                if existing.namespace != source.namespace && result.get(id) != Some(entry) {
+                    // A namespaced identifier clashes with a regular one.
+                    return Err(conflict_error(
+                        id.clone(),
+                        existing.namespace.clone(),
+                        source.namespace.clone(),
+                    ));
+                }
+                continue;
+            }
+            result.insert(target_id.clone(), entry.clone());
+            origins.insert(target_id.clone(), index);
+            provenance.insert(target_id, origin);
+        }
+    }
+    Ok(MergedEntries {
+        entries: result,
+        origins,
+        renames,
+    })
+}
+
+/// Finds the identifiers that have different definitions across sources.
+fn find_conflicting_ids<T: PartialEq>(
+    entries_per_source: &[BTreeMap<String, T>],
+) -> BTreeSet<String> {
+    let mut first_definitions: BTreeMap<&String, &T> = BTreeMap::new();
+    let mut result = BTreeSet::new();
+    for entries in entries_per_source {
+        for (id, entry) in entries {
+            match first_definitions.get(id) {
+                Some(existing) if *existing != entry => {
+                    result.insert(id.clone());
+                }
+                Some(_) => {}
+                None => {
+                    first_definitions.insert(id, entry);
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Points a content to the namespaced identifier of its type, if the type was namespaced.
+///
+/// Contents prefer the type defined by their own module, and otherwise use the
+/// type defined by the first module that was namespaced.
+fn rename_content_type(
+    content: &mut JsonMap,
+    source_index: usize,
+    types_per_source: &[BTreeMap<String, ModuleType>],
+    types: &MergedEntries<ModuleType>,
+) {
+    let Some(type_key) = content.get(TYPE_KEY).and_then(Value::as_str) else {
+        return;
+    };
+    if !types_per_source[source_index].contains_key(type_key)
+        && types.renames.iter().any(|r| r.contains_key(type_key))
+    {
+        warn!(
+            "Content uses the conflicting type `{}` without defining it, using the first definition",
+            type_key
+        );
+    }
+    if let Some(renamed) = types.renamed(source_index, type_key, types_per_source) {
+        content.insert(TYPE_KEY.to_string(), Value::String(renamed.clone()));
+    }
+}
+
+/// Points the parents of a type, and the types allowed by the references
+/// of it's schema, to their namespaced identifiers.
+fn rename_type_references(module_type: &mut ModuleType, rename: &dyn Fn(&str) -> Option<String>) {
+    if let Some(renamed) = module_type.extends.as_deref().and_then(rename) {
+        module_type.extends = Some(renamed);
+    }
+    for ancestor in module_type.ancestors.iter_mut().flatten() {
+        if let Some(renamed) = rename(ancestor) {
+            *ancestor = renamed;
+        }
+    }
+    if let Some(schema) = module_type.schema.as_mut() {
+        rename_allowed_types(schema, rename);
+    }
+}
+
+/// Points the `types` of every `x-reference` inside a schema to their namespaced identifiers.
+fn rename_allowed_types(schema: &mut Value, rename: &dyn Fn(&str) -> Option<String>) {
+    match schema {
+        Value::Object(object) => {
+            if let Some(Value::Array(types)) = object
+                .get_mut(REFERENCE)
+                .and_then(|reference| reference.get_mut("types"))
+            {
+                for allowed_type in types.iter_mut() {
+                    if let Some(renamed) = allowed_type.as_str().and_then(rename) {
+                        *allowed_type = Value::String(renamed);
+                    }
+                }
+            }
+            for value in object.values_mut() {
+                rename_allowed_types(value, rename);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                rename_allowed_types(value, rename);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Points every reference between contents to the namespaced identifier of
+/// the referenced content.
+fn rename_content_references<'a>(
+    module: &mut Module,
+    rename: impl Fn(&str, &str) -> Option<&'a String>,
+) {
+    let references = module.find_references();
+    let Some(contents) = module.contents.as_mut() else {
+        return;
+    };
+    for reference in references {
+        let Some(renamed) = rename(&reference.content_id, &reference.target) else {
+            continue;
+        };
+        let content = contents
+            .get_mut(&reference.content_id)
+            .expect("References are always found in existing contents");
+        let mut value = Value::Object(std::mem::take(content).into_iter().collect());
+        if let Some(property) = value.pointer_mut(&reference.pointer) {
+            debug!(
+                "Pointing `{}` of `{}` to `{}`",
+                reference.pointer, reference.content_id, renamed
+            );
+            *property = Value::String(renamed.clone());
+        }
+        if let Value::Object(object) = value {
+            *content = object.into_iter().collect();
+        }
+    }
+}
+
+/// Points the contents and partials used by a template to their namespaced identifiers.
+///
+/// Contents are found in the literal identifiers given to the content helpers,
+/// like `{{renderContent "fireball"}}`, and partials in `{{> name}}` and in
+/// partial blocks (`{{#> name}}...{{/name}}`).
+fn rename_template_references<'a>(
+    template: &str,
+    rename_content: impl Fn(&str) -> Option<&'a String>,
+    rename_partial: impl Fn(&str) -> Option<&'a String>,
+) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut partial_blocks = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start..].find("}}") else {
+            break;
+        };
+        let expression = &rest[start + 2..start + length];
+        let mut replacements: Vec<(usize, usize, &String)> = Vec::new();
+        let markers = expression.trim_start_matches(['{', '~']);
+        let partial = markers
+            .strip_prefix('>')
+            .or_else(|| markers.strip_prefix("#>"));
+        let closing = markers.strip_prefix('/');
+        if let Some(name) = partial.or(closing) {
+            let (name_start, name_end) = name_range(expression, name);
+            let name = &expression[name_start..name_end];
+            let renamed = if markers.starts_with("#>") {
+                partial_blocks.push(name);
+                rename_partial(name)
+            } else if closing.is_some() {
+                partial_blocks
+                    .contains(&name)
+                    .then(|| rename_partial(name))
+                    .flatten()
+            } else {
+                rename_partial(name)
+            };
+            if let Some(renamed) = renamed {
+                replacements.push((name_start, name_end, renamed));
+            }
+        }
+        for helper in CONTENT_HELPERS {
+            for (position, _) in expression.match_indices(helper) {
+                let parameters = &expression[position + helper.len()..];
+                let literal = parameters.trim_start();
+                if literal.len() == parameters.len() {
+                    // Other helpers that start with the same name
+                    continue;
+                }
+                let Some(quote) = literal.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+                    continue;
+                };
+                let Some(length) = literal[1..].find(quote) else {
+                    continue;
+                };
+                let id_start = expression.len() - literal.len() + 1;
+                if let Some(renamed) = rename_content(&expression[id_start..id_start + length]) {
+                    replacements.push((id_start, id_start + length, renamed));
+                }
+            }
+        }
+        replacements.sort();
+        result.push_str(&rest[..start + 2]);
+        let mut copied = 0;
+        for (replaced_start, replaced_end, renamed) in replacements {
+            result.push_str(&expression[copied..replaced_start]);
+            result.push_str(renamed);
+            copied = replaced_end;
+        }
+        result.push_str(&expression[copied..]);
+        rest = &rest[start + length..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Finds where the name at the start of the remainder of an expression is.
+fn name_range(expression: &str, remainder: &str) -> (usize, usize) {
+    let name = remainder.trim_start();
+    let start = expression.len() - name.len();
+    let end = name
+        .find(|c: char| c.is_whitespace() || c == '~')
+        .map_or(expression.len(), |length| start + length);
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn module_type(description: &str) -> ModuleType {
+        ModuleType {
+            description: description.to_string(),
+            schema: None,
+            rendering: None,
+            partials: None,
+            escaping: None,
+            extends: None,
+            ancestors: None,
+        }
+    }
+
+    fn content(type_key: &str, name: &str) -> JsonMap {
+        BTreeMap::from([
+            (TYPE_KEY.to_string(), json!(type_key)),
+            ("name".to_string(), json!(name)),
+        ])
+    }
+
+    fn source(
+        namespace: &str,
+        types: Vec<(&str, ModuleType)>,
+        contents: Vec<(&str, JsonMap)>,
+    ) -> MergeSource {
+        MergeSource {
+            namespace: namespace.to_string(),
+            module: Module {
+                title: format!("{} title", namespace),
+                description: format!("{} description", namespace),
+                source: Url::parse(&format!("https://powerd6.org/{}", namespace)).unwrap(),
+                rendering: None,
+                partials: None,
+                types: Some(
+                    types
+                        .into_iter()
+                        .map(|(id, t)| (id.to_string(), t))
+                        .collect(),
+                ),
+                contents: Some(
+                    contents
+                        .into_iter()
+                        .map(|(id, c)| (id.to_string(), c))
+                        .collect(),
+                ),
+            },
+        }
+    }
+
+    fn conflicting_sources() -> Vec<MergeSource> {
+        vec![
+            source(
+                "core",
+                vec![("spell", module_type("core spell"))],
+                vec![("fireball", content("spell", "Fireball"))],
+            ),
+            source(
+                "expansion",
+                vec![("spell", module_type("expansion spell"))],
+                vec![("fireball", content("spell", "Big Fireball"))],
+            ),
+        ]
+    }
+
+    #[test]
+    fn fails_without_sources() {
+        assert_eq!(
+            merge(&[], &MergeOptions::default()).unwrap_err(),
+            MergeError::NoModules
+        );
+    }
+
+    #[test]
+    fn combines_disjoint_modules_and_records_provenance() {
+        let sources = vec![
+            source(
+                "core",
+                vec![("spell", module_type("spell"))],
+                vec![("fireball", content("spell", "Fireball"))],
+            ),
+            source(
+                "expansion",
+                vec![("item", module_type("item"))],
+                vec![("sword", content("item", "Sword"))],
+            ),
+        ];
+
+        let merged = merge(&sources, &MergeOptions::default()).unwrap();
+
+        assert_eq!(merged.module.title, "core title");
+        assert_eq!(merged.module.source, sources[0].module.source);
+        assert_eq!(
+            merged.module.types.unwrap().keys().collect::<Vec<_>>(),
+            vec!["item", "spell"]
+        );
+        assert_eq!(merged.provenance.contents["sword"].namespace, "expansion");
+        assert_eq!(merged.provenance.contents["fireball"].namespace, "core");
+    }
+
+    #[test]
+    fn identical_entries_are_not_conflicts() {
+        let sources = vec![
+            source("core", vec![("spell", module_type("spell"))], vec![]),
+            source("expansion", vec![("spell", module_type("spell"))], vec![]),
+        ];
+
+        let merged = merge(&sources, &MergeOptions::default()).unwrap();
+
+        assert_eq!(merged.module.types.unwrap().len(), 1);
+        assert_eq!(merged.provenance.types["spell"].namespace, "core");
+    }
+
+    #[test]
+    fn fail_policy_reports_conflicts() {
+        assert_eq!(
+            merge(&conflicting_sources(), &MergeOptions::default()).unwrap_err(),
+            MergeError::ConflictingType(
+                "spell".to_string(),
+                "core".to_string(),
+                "expansion".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn first_and_last_wins_policies_pick_the_right_entry() {
+        let first = merge(
+            &conflicting_sources(),
+            &MergeOptions {
+                types: ConflictPolicy::FirstWins,
+                contents: ConflictPolicy::FirstWins,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            first.module.contents.unwrap()["fireball"]["name"],
+            json!("Fireball")
+        );
+
+        let last = merge(
+            &conflicting_sources(),
+            &MergeOptions {
+                types: ConflictPolicy::LastWins,
+                contents: ConflictPolicy::LastWins,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            last.module.contents.unwrap()["fireball"]["name"],
+            json!("Big Fireball")
+        );
+        assert_eq!(last.provenance.types["spell"].namespace, "expansion");
+    }
+
+    #[test]
+    fn namespaced_policy_keeps_all_entries_and_updates_content_types() {
+        let merged = merge(
+            &conflicting_sources(),
+            &MergeOptions {
+                types: ConflictPolicy::Namespaced,
+                contents: ConflictPolicy::Namespaced,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let contents = merged.module.contents.unwrap();
+        assert_eq!(
+            contents.keys().collect::<Vec<_>>(),
+            vec!["core_fireball", "expansion_fireball"]
+        );
+        assert_eq!(
+            contents["expansion_fireball"][TYPE_KEY],
+            json!("expansion_spell")
+        );
+        assert_eq!(merged.provenance.contents["core_fireball"].id, "fireball");
+    }
+
+    #[test]
+    fn metadata_can_be_joined() {
+        let merged = merge(
+            &conflicting_sources(),
+            &MergeOptions {
+                types: ConflictPolicy::FirstWins,
+                contents: ConflictPolicy::FirstWins,
+                templates: ConflictPolicy::Fail,
+                title: MetadataPolicy::Join,
+                description: MetadataPolicy::Last,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(merged.module.title, "core title + expansion title");
+        assert_eq!(merged.module.description, "expansion description");
+    }
+
+    #[test]
+    fn metadata_can_be_replaced() {
+        let merged = merge(
+            &conflicting_sources(),
+            &MergeOptions {
+                types: ConflictPolicy::FirstWins,
+                contents: ConflictPolicy::FirstWins,
+                title: MetadataPolicy::Custom("Complete edition".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(merged.module.title, "Complete edition");
+    }
+
+    fn module_with_templates(namespace: &str, description: &str) -> MergeSource {
+        MergeSource {
+            namespace: namespace.to_string(),
+            module: serde_json::from_value(json!({
+                "title": namespace,
+                "description": "My description",
+                "source": "https://powerd6.org",
+                "rendering": {"md": "{{#> stats}}{{renderContent \"fireball\"}}{{/stats}}"},
+                "partials": {"stats": description},
+                "types": {
+                    "school": {"description": description},
+                    "spell": {
+                        "description": description,
+                        "extends": "school",
+                        "schema": {
+                            "properties": {
+                                "school": {"type": "string", "x-reference": {"types": ["school"]}}
+                            }
+                        },
+                        "rendering": {"md": "{{> stats}} {{lookupContent 'evocation'}}"}
+                    }
+                },
+                "contents": {
+                    "evocation": {"type": "school", "name": description},
+                    "fireball": {"type": "spell", "school": "evocation", "name": description}
+                }
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn namespaced_policy_updates_the_references_of_each_module() {
+        let merged = merge(
+            &[
+                module_with_templates("core", "core"),
+                module_with_templates("expansion", "expansion"),
+            ],
+            &MergeOptions {
+                types: ConflictPolicy::Namespaced,
+                contents: ConflictPolicy::Namespaced,
+                templates: ConflictPolicy::Namespaced,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let types = merged.module.types.unwrap();
+        let spell = &types["expansion_spell"];
+        assert_eq!(spell.extends.as_deref(), Some("expansion_school"));
+        assert_eq!(
+            spell.schema.as_ref().unwrap()["properties"]["school"]["x-reference"]["types"],
+            json!(["expansion_school"])
+        );
+        assert_eq!(
+            spell.rendering.as_ref().unwrap()["md"],
+            "{{> expansion_stats}} {{lookupContent 'expansion_evocation'}}"
+        );
+        let contents = merged.module.contents.unwrap();
+        assert_eq!(contents["core_fireball"]["school"], json!("core_evocation"));
+        assert_eq!(
+            contents["expansion_fireball"]["school"],
+            json!("expansion_evocation")
+        );
+        assert_eq!(
+            merged.module.partials.unwrap().keys().collect::<Vec<_>>(),
+            vec!["core_stats", "expansion_stats"]
+        );
+    }
+
+    #[test]
+    fn templates_follow_the_conflict_policy() {
+        let sources = [
+            module_with_templates("core", "core"),
+            module_with_templates("expansion", "expansion"),
+        ];
+        let options = MergeOptions {
+            types: ConflictPolicy::FirstWins,
+            contents: ConflictPolicy::FirstWins,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            merge(&sources, &options).unwrap_err(),
+            MergeError::ConflictingTemplate(
+                "stats".to_string(),
+                "core".to_string(),
+                "expansion".to_string()
+            )
+        );
+        let last = merge(
+            &sources,
+            &MergeOptions {
+                templates: ConflictPolicy::LastWins,
+                ..options.clone()
+            },
+        )
+        .unwrap();
+        assert_eq!(last.module.partials.unwrap()["stats"], "expansion");
+    }
+
+    #[test]
+    fn layouts_are_never_namespaced() {
+        let mut sources = [
+            module_with_templates("core", "core"),
+            module_with_templates("expansion", "expansion"),
+        ];
+        sources[1]
+            .module
+            .rendering
+            .as_mut()
+            .unwrap()
+            .insert("md".to_string(), "# Expansion".to_string());
+
+        assert_eq!(
+            merge(
+                &sources,
+                &MergeOptions {
+                    types: ConflictPolicy::Namespaced,
+                    contents: ConflictPolicy::Namespaced,
+                    templates: ConflictPolicy::Namespaced,
+                    ..Default::default()
+                },
+            )
+            .unwrap_err(),
+            MergeError::ConflictingTemplate(
+                "md".to_string(),
+                "core".to_string(),
+                "expansion".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn templates_use_the_namespaced_contents_and_partials() {
+        let fireball = "core_fireball".to_string();
+        let stats = "core_stats".to_string();
+        let rename_content = |id: &str| (id == "fireball").then_some(&fireball);
+        let rename_partial = |name: &str| (name == "stats").then_some(&stats);
+
+        assert_eq!(
+            rename_template_references(
+                "{{~#> stats title=\"a\"}}{{renderContent \"fireball\" \"md\"}}{{renderContent this}}{{/stats}}",
+                rename_content,
+                rename_partial
+            ),
+            "{{~#> core_stats title=\"a\"}}{{renderContent \"core_fireball\" \"md\"}}{{renderContent this}}{{/core_stats}}"
+        );
+        assert_eq!(
+            rename_template_references(
+                "{{#each stats}}{{> other}}{{/each}}{{stats}}{{lookupContent \"frost\"}}",
+                rename_content,
+                rename_partial
+            ),
+            "{{#each stats}}{{> other}}{{/each}}{{stats}}{{lookupContent \"frost\"}}"
+        );
+    }
+}
@@ -1,25 +1,39 @@
 use fs::file_system::FileSystem;
-use fs_data::EntryData;
+use fs_data::{EntryData, Localization};
 
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::BTreeMap;
-use tracing::{info, instrument};
+use std::path::PathBuf;
+use tracing::{debug, info, instrument};
 use url::Url;
 
-use crate::{module_type::ModuleType, JsonMap, ModuleError};
+use crate::{
+    builder::ModuleBuilder, locale::apply_locale_contents, module_type::ModuleType, JsonMap,
+    ModuleError, GROUP_HEADERS, INDEXES, TYPE_KEY,
+};
 
 /// A document that contains information for a powerd6 module.
 ///
 /// While this object does not perform validation on it's own,
 /// it creates an uniform structure to do so.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct Module {
     /// The title of the module.
-    title: String,
+    pub(crate) title: String,
     /// The human-readable description of what the module contains.
-    description: String,
+    pub(crate) description: String,
     /// A hyperlink to the where the module is hosted.
-    source: Url,
+    pub(crate) source: Url,
+    /// The layouts of the module for all the supported formats, which
+    /// receive the module and all of it's rendered contents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rendering: Option<BTreeMap<String, String>>,
+    /// The partial templates shared by the templates of all types and the
+    /// layouts, keyed by the name they are used with (`{{> name}}`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partials: Option<BTreeMap<String, String>>,
     /// A collection of types that are defined in this module.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub types: Option<BTreeMap<String, ModuleType>>,
@@ -30,19 +44,91 @@ pub struct Module {
 }
 
 impl Module {
+    /// Starts creating a module programmatically.
+    pub fn builder(
+        title: impl Into<String>,
+        description: impl Into<String>,
+        source: Url,
+    ) -> ModuleBuilder {
+        ModuleBuilder::new(title, description, source)
+    }
+
+    /// The title of the module.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The human-readable description of what the module contains.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// A hyperlink to the where the module is hosted.
+    pub fn source(&self) -> &Url {
+        &self.source
+    }
+
+    /// Reads every content of a type into a Rust type, keyed by their identifiers.
+    ///
+    /// # Example
+    /// ```
+    /// # use module::module::Module;
+    /// # use serde::Deserialize;
+    /// # use serde_json::json;
+    /// #[derive(Deserialize)]
+    /// struct Spell {
+    ///     name: String,
+    ///     level: u8,
+    /// }
+    ///
+    /// let module: Module = serde_json::from_value(json!({
+    ///     "title": "My title",
+    ///     "description": "My description",
+    ///     "source": "https://powerd6.org",
+    ///     "contents": {
+    ///         "fireball": {"type": "spell", "name": "Fireball", "level": 3},
+    ///         "sword": {"type": "item", "name": "Sword"}
+    ///     }
+    /// }))
+    /// .unwrap();
+    ///
+    /// let spells = module.contents_of::<Spell>("spell").unwrap();
+    /// assert_eq!(spells.len(), 1);
+    /// assert_eq!(spells["fireball"].level, 3);
+    /// ```
+    pub fn contents_of<T: DeserializeOwned>(
+        &self,
+        type_key: &str,
+    ) -> Result<BTreeMap<String, T>, ModuleError> {
+        let mut result = BTreeMap::new();
+        for (content_id, content) in self.contents.iter().flatten() {
+            if content.get(TYPE_KEY).and_then(Value::as_str) == Some(type_key) {
+                result.insert(
+                    content_id.clone(),
+                    deserialize_content(content_id, content)?,
+                );
+            }
+        }
+        Ok(result)
+    }
+
+    /// Reads a single content into a Rust type.
+    pub fn content_as<T: DeserializeOwned>(&self, content_id: &str) -> Result<T, ModuleError> {
+        match self.contents.as_ref().and_then(|c| c.get(content_id)) {
+            Some(content) => deserialize_content(content_id, content),
+            None => Err(ModuleError::MissingContent(content_id.to_string())),
+        }
+    }
+
     /// Extends the module with the provided types.
     ///
     /// If the Module does not already have types, then the provided types will
     /// simply be assigned. Otherwise, the provided types will be added,
     /// replacing the existing types when the identifiers match.
     fn extend_types(&mut self, mut extra_types: BTreeMap<String, ModuleType>) {
-        if self.types.is_none() {
-            self.types = Some(extra_types)
-        } else {
-            self.types
-                .as_mut()
-                .expect("Module should have types at this point")
-                .append(&mut extra_types);
+        match self.types.as_mut() {
+            Some(types) => types.append(&mut extra_types),
+            None => self.types = Some(extra_types),
         }
     }
 
@@ -52,32 +138,169 @@ impl Module {
     /// will simply be assigned. Otherwise, the provided contents will be added,
     /// replacing the existing contents when the identifiers match.
     fn extend_contents(&mut self, mut extra_contents: BTreeMap<String, JsonMap>) {
-        if self.contents.is_none() {
-            self.contents = Some(extra_contents)
-        } else {
-            self.contents
-                .as_mut()
-                .expect("Module should have contents at this point")
-                .append(&mut extra_contents);
+        match self.contents.as_mut() {
+            Some(contents) => contents.append(&mut extra_contents),
+            None => self.contents = Some(extra_contents),
         }
     }
 }
 
+impl Module {
+    /// Resolves the inheritance between the types of the module.
+    ///
+    /// Every type that `extends` another one is replaced by the combination of
+    /// itself and it's (resolved) parent, as described in
+    /// [ModuleType::inherit_from]. Modules without inheritance are unchanged.
+    #[instrument(skip(self))]
+    pub fn resolve_type_inheritance(&mut self) -> Result<(), ModuleError> {
+        if let Some(types) = &self.types {
+            let mut resolved: BTreeMap<String, ModuleType> = BTreeMap::new();
+            for type_key in types.keys() {
+                resolve_type(type_key, types, &mut resolved, &mut Vec::new())?;
+            }
+            self.types = Some(resolved);
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes a content, tying errors to it's identifier.
+fn deserialize_content<T: DeserializeOwned>(
+    content_id: &str,
+    content: &JsonMap,
+) -> Result<T, ModuleError> {
+    let value = Value::Object(content.clone().into_iter().collect());
+    serde_json::from_value(value)
+        .map_err(|e| ModuleError::InvalidContent(content_id.to_string(), e.into()))
+}
+
+/// Resolves a single type, and all of it's ancestors, into `resolved`.
+///
+/// The `chain` holds the types being resolved, to detect cycles.
+fn resolve_type<'a>(
+    type_key: &'a String,
+    types: &'a BTreeMap<String, ModuleType>,
+    resolved: &mut BTreeMap<String, ModuleType>,
+    chain: &mut Vec<&'a String>,
+) -> Result<(), ModuleError> {
+    if resolved.contains_key(type_key) {
+        return Ok(());
+    }
+    if chain.contains(&type_key) {
+        let mut cycle: Vec<&str> = chain.iter().map(|t| t.as_str()).collect();
+        cycle.push(type_key);
+        return Err(ModuleError::CyclicTypeInheritance(cycle.join(" -> ")));
+    }
+    let module_type = &types[type_key];
+    let result = match &module_type.extends {
+        Some(parent_key) => match types.get_key_value(parent_key) {
+            Some((parent_key, _)) => {
+                chain.push(type_key);
+                resolve_type(parent_key, types, resolved, chain)?;
+                chain.pop();
+                debug!("Type `{}` inherits from `{}`", type_key, parent_key);
+                let parent = &resolved[parent_key];
+                let mut ancestors = vec![parent_key.clone()];
+                ancestors.extend(parent.ancestors.iter().flatten().cloned());
+                ModuleType {
+                    ancestors: Some(ancestors),
+                    ..module_type.inherit_from(parent)?
+                }
+            }
+            None => {
+                return Err(ModuleError::MissingParentType(
+                    type_key.clone(),
+                    parent_key.clone(),
+                ))
+            }
+        },
+        None => module_type.clone(),
+    };
+    resolved.insert(type_key.clone(), result);
+    Ok(())
+}
+
 impl TryFrom<FileSystem> for Module {
     type Error = ModuleError;
 
     #[instrument(skip(filesystem))]
     fn try_from(filesystem: FileSystem) -> Result<Module, ModuleError> {
-        match filesystem.module.try_get_data() {
+        Module::try_from_localized(filesystem, None)
+    }
+}
+
+impl Module {
+    /// Creates a module from a FileSystem, translated into a locale.
+    ///
+    /// Translations can be defined next to the translated field, by adding
+    /// the locale before the extension (`title.pt.md` translates `title.md`),
+    /// or inside the `locales/{locale}` directory, which mirrors the contents
+    /// directory. Every field without a translation keeps the value of the
+    /// default locale, which is used when no locale is provided.
+    ///
+    /// Locales are declared by their directory inside `locales`, which may be
+    /// empty, and only declared locales are recognized in file names.
+    #[instrument(skip(filesystem))]
+    pub fn try_from_localized(
+        filesystem: FileSystem,
+        locale: Option<&str>,
+    ) -> Result<Module, ModuleError> {
+        let known_locales = filesystem.known_locales();
+        if let Some(locale) = locale.filter(|l| !known_locales.iter().any(|k| k == l)) {
+            return Err(ModuleError::UnknownLocale(locale.to_string()));
+        }
+        let localization = Localization {
+            locale,
+            known_locales: &known_locales,
+        };
+        match filesystem.module.try_get_localized_data(localization) {
             Ok(module_data) => match serde_json::from_value::<Module>(module_data.clone()) {
                 Ok(module) => {
                     let mut result = module;
-                    if let Some(fs_types) = try_populate_types_from_filesystem(&filesystem)? {
+                    if let Some(fs_rendering) =
+                        try_populate_templates_from_filesystem(&filesystem.rendering, localization)?
+                    {
+                        result
+                            .rendering
+                            .get_or_insert_with(BTreeMap::new)
+                            .extend(fs_rendering)
+                    }
+                    for (prefix, files) in [
+                        (GROUP_HEADERS, &filesystem.group_headers),
+                        (INDEXES, &filesystem.indexes),
+                    ] {
+                        if let Some(fs_templates) =
+                            try_populate_templates_from_filesystem(files, localization)?
+                        {
+                            result.rendering.get_or_insert_with(BTreeMap::new).extend(
+                                fs_templates.into_iter().map(|(format, template)| {
+                                    (format!("{}/{}", prefix, format), template)
+                                }),
+                            )
+                        }
+                    }
+                    if let Some(fs_partials) =
+                        try_populate_templates_from_filesystem(&filesystem.partials, localization)?
+                    {
+                        result
+                            .partials
+                            .get_or_insert_with(BTreeMap::new)
+                            .extend(fs_partials)
+                    }
+                    if let Some(fs_types) =
+                        try_populate_types_from_filesystem(&filesystem, localization)?
+                    {
                         result.extend_types(fs_types)
                     }
-                    if let Some(fs_contents) = try_populate_contents_from_filesystem(&filesystem)? {
+                    if let Some(fs_contents) =
+                        try_populate_contents_from_filesystem(&filesystem, localization)?
+                    {
                         result.extend_contents(fs_contents)
                     }
+                    if let Some(contents) = result.contents.as_mut() {
+                        apply_locale_contents(contents, &filesystem, localization)?;
+                    }
+                    result.resolve_type_inheritance()?;
                     Ok(result)
                 }
                 Err(_e) => Err(ModuleError::IncompatibleFieldType(module_data.into())),
@@ -87,8 +310,27 @@ impl TryFrom<FileSystem> for Module {
     }
 }
 
+/// Reads templates (layouts or partials) from their files, keyed by the
+/// name of the file without extension.
+fn try_populate_templates_from_filesystem(
+    files: &Vec<PathBuf>,
+    localization: Localization,
+) -> Result<Option<BTreeMap<String, String>>, ModuleError> {
+    if files.is_empty() {
+        return Ok(None);
+    }
+    info!("Loading templates from file system");
+    let rendering_data = files
+        .try_get_localized_data(localization)
+        .map_err(|e| ModuleError::UnableToGetRequiredData(e.into()))?;
+    serde_json::from_value(rendering_data.clone())
+        .map(Some)
+        .map_err(|_| ModuleError::IncompatibleFieldType(rendering_data.into()))
+}
+
 fn try_populate_types_from_filesystem(
     filesystem: &FileSystem,
+    localization: Localization,
 ) -> Result<Option<BTreeMap<String, ModuleType>>, ModuleError> {
     if let Some(fs_types) = &filesystem.types {
         let mut result: BTreeMap<String, ModuleType> = BTreeMap::new();
@@ -96,7 +338,8 @@ fn try_populate_types_from_filesystem(
         for type_entry in fs_types.entries.iter() {
             match &fs_types.get_identifier_for_entry(type_entry) {
                 Some(identifier) => {
-                    let module_type = ModuleType::try_from(type_entry.clone())?;
+                    let module_type =
+                        ModuleType::try_from_localized(type_entry.clone(), localization)?;
                     result.insert(identifier.to_string(), module_type);
                 }
                 None => {
@@ -111,6 +354,7 @@ fn try_populate_types_from_filesystem(
 
 fn try_populate_contents_from_filesystem(
     filesystem: &FileSystem,
+    localization: Localization,
 ) -> Result<Option<BTreeMap<String, JsonMap>>, ModuleError> {
     if let Some(fs_contents) = &filesystem.contents {
         let mut result: BTreeMap<String, JsonMap> = BTreeMap::new();
@@ -119,7 +363,7 @@ fn try_populate_contents_from_filesystem(
             match &fs_contents.get_identifier_for_entry(content_entry) {
                 Some(identifier) => {
                     let content_data = content_entry
-                        .try_get_data()
+                        .try_get_localized_data(localization)
                         .map_err(|e| ModuleError::UnableToGetRequiredData(e.into()))?;
                     let module_content = serde_json::from_value(content_data.clone())
                         .or(Err(ModuleError::IncompatibleFieldType(content_data.into())))?;
@@ -142,6 +386,9 @@ mod tests {
 
     use super::*;
     use fs::CONTENTS_DIRECTORY;
+    use fs::LOCALES_DIRECTORY;
+    use fs::PARTIALS_DIRECTORY;
+    use fs::RENDERING_DIRECTORY;
     use fs::TYPES_DIRECTORY;
     use path_utils::create_test_directory;
     use path_utils::create_test_file;
@@ -155,6 +402,8 @@ mod tests {
             title: "My title".to_string(),
             description: "My description".to_string(),
             source: Url::parse("https://powerd6.org").unwrap(),
+            rendering: None,
+            partials: None,
             types: None,
             contents: None,
         };
@@ -165,6 +414,10 @@ mod tests {
                 description: "my type".to_string(),
                 schema: None,
                 rendering: None,
+                partials: None,
+                escaping: None,
+                extends: None,
+                ancestors: None,
             },
         )]);
 
@@ -178,6 +431,8 @@ mod tests {
             title: "My title".to_string(),
             description: "My description".to_string(),
             source: Url::parse("https://powerd6.org").unwrap(),
+            rendering: None,
+            partials: None,
             types: None,
             contents: None,
         };
@@ -191,6 +446,130 @@ mod tests {
         assert!(module.contents.is_some());
     }
 
+    fn module_with_types(types: Vec<(&str, Option<&str>)>) -> Module {
+        Module {
+            title: "My title".to_string(),
+            description: "My description".to_string(),
+            source: Url::parse("https://powerd6.org").unwrap(),
+            rendering: None,
+            partials: None,
+            types: Some(
+                types
+                    .into_iter()
+                    .map(|(type_key, extends)| {
+                        (
+                            type_key.to_string(),
+                            ModuleType {
+                                description: type_key.to_string(),
+                                schema: Some(serde_json::json!({ "required": [type_key] })),
+                                rendering: None,
+                                partials: None,
+                                escaping: None,
+                                extends: extends.map(str::to_string),
+                                ancestors: None,
+                            },
+                        )
+                    })
+                    .collect(),
+            ),
+            contents: None,
+        }
+    }
+
+    #[test]
+    fn resolving_inheritance_composes_all_ancestors() {
+        let mut module = module_with_types(vec![
+            ("item", None),
+            ("weapon", Some("item")),
+            ("sword", Some("weapon")),
+        ]);
+
+        module.resolve_type_inheritance().unwrap();
+
+        let sword = &module.types.unwrap()["sword"];
+        assert_eq!(sword.extends, None);
+        assert_eq!(
+            sword.ancestors,
+            Some(vec!["weapon".to_string(), "item".to_string()])
+        );
+        assert_eq!(
+            sword.schema,
+            Some(serde_json::json!({
+                "allOf": [
+                    {"allOf": [{"required": ["item"]}, {"required": ["weapon"]}]},
+                    {"required": ["sword"]}
+                ]
+            }))
+        );
+    }
+
+    #[test]
+    fn resolving_inheritance_fails_with_missing_parent() {
+        let mut module = module_with_types(vec![("weapon", Some("item"))]);
+
+        assert!(matches!(
+            module.resolve_type_inheritance().unwrap_err(),
+            ModuleError::MissingParentType(child, parent) if child == "weapon" && parent == "item"
+        ));
+    }
+
+    #[test]
+    fn resolving_inheritance_fails_with_cycles() {
+        let mut module = module_with_types(vec![("a", Some("b")), ("b", Some("a"))]);
+
+        assert!(matches!(
+            module.resolve_type_inheritance().unwrap_err(),
+            ModuleError::CyclicTypeInheritance(cycle) if cycle == "a -> b -> a"
+        ));
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Spell {
+        name: String,
+        level: u8,
+    }
+
+    #[test]
+    fn typed_contents_report_the_invalid_content() {
+        let module = Module::builder(
+            "My title",
+            "My description",
+            Url::parse("https://powerd6.org").unwrap(),
+        )
+        .with_content(
+            "fireball",
+            BTreeMap::from([
+                (TYPE_KEY.to_string(), Value::String("spell".to_string())),
+                ("name".to_string(), Value::String("Fireball".to_string())),
+                ("level".to_string(), Value::from(3)),
+            ]),
+        )
+        .with_content(
+            "firebolt",
+            BTreeMap::from([
+                (TYPE_KEY.to_string(), Value::String("spell".to_string())),
+                ("name".to_string(), Value::String("Firebolt".to_string())),
+            ]),
+        )
+        .build();
+
+        assert!(matches!(
+            module.contents_of::<Spell>("spell").unwrap_err(),
+            ModuleError::InvalidContent(content_id, _) if content_id == "firebolt"
+        ));
+        assert_eq!(
+            module.content_as::<Spell>("fireball").unwrap(),
+            Spell {
+                name: "Fireball".to_string(),
+                level: 3
+            }
+        );
+        assert!(matches!(
+            module.content_as::<Spell>("iceball").unwrap_err(),
+            ModuleError::MissingContent(content_id) if content_id == "iceball"
+        ));
+    }
+
     #[test]
     fn works_with_only_mandatory_files() {
         let dir = testdir!();
@@ -210,6 +589,8 @@ mod tests {
                 title: "My title".to_string(),
                 description: "My description".to_string(),
                 source: Url::parse("https://powerd6.org").unwrap(),
+                rendering: None,
+                partials: None,
                 types: None,
                 contents: None
             }
@@ -255,13 +636,19 @@ mod tests {
                 title: "My title".to_string(),
                 description: "My description".to_string(),
                 source: Url::parse("https://powerd6.org").unwrap(),
+                rendering: None,
+                partials: None,
                 types: Some(BTreeMap::from([
                     (
                         "a".to_string(),
                         ModuleType {
                             description: "my replaced type".to_string(),
                             schema: None,
-                            rendering: None
+                            rendering: None,
+                            partials: None,
+                            escaping: None,
+                            extends: None,
+                            ancestors: None
                         }
                     ),
                     (
@@ -269,7 +656,11 @@ mod tests {
                         ModuleType {
                             description: "my new type".to_string(),
                             schema: None,
-                            rendering: None
+                            rendering: None,
+                            partials: None,
+                            escaping: None,
+                            extends: None,
+                            ancestors: None
                         }
                     )
                 ])),
@@ -278,6 +669,189 @@ mod tests {
         )
     }
 
+    #[test]
+    fn localized_modules_use_translations_and_fall_back_to_default_locale() {
+        let dir = testdir!();
+
+        let module_directory = create_test_directory(&dir.join("module"));
+        create_test_file(
+            &module_directory.join("_.json"),
+            r#"{
+                "source": "https://powerd6.org"
+            }"#,
+        );
+        create_test_file(&module_directory.join("title.md"), "My title");
+        create_test_file(&module_directory.join("title.pt.md"), "Meu titulo");
+        create_test_file(&module_directory.join("description.md"), "My description");
+
+        let contents_directory = create_test_directory(&dir.join(CONTENTS_DIRECTORY));
+        create_test_file(
+            &contents_directory.join("a.json"),
+            r#"{
+                "name": "Fireball",
+                "effect": "Burns"
+            }"#,
+        );
+        let locales_directory = create_test_directory(&dir.join(LOCALES_DIRECTORY));
+        let portuguese_directory = create_test_directory(&locales_directory.join("pt"));
+        create_test_file(
+            &portuguese_directory.join("a.json"),
+            r#"{
+                "name": "Bola de fogo"
+            }"#,
+        );
+
+        let default_module = Module::try_from(FileSystem::try_from(dir.clone()).unwrap()).unwrap();
+        assert_eq!(default_module.title, "My title");
+        assert_eq!(
+            default_module.contents.unwrap()["a"]["name"],
+            Value::String("Fireball".to_string())
+        );
+
+        let localized_module =
+            Module::try_from_localized(FileSystem::try_from(dir).unwrap(), Some("pt")).unwrap();
+        assert_eq!(localized_module.title, "Meu titulo");
+        assert_eq!(localized_module.description, "My description");
+        let content = &localized_module.contents.unwrap()["a"];
+        assert_eq!(content["name"], Value::String("Bola de fogo".to_string()));
+        assert_eq!(content["effect"], Value::String("Burns".to_string()));
+    }
+
+    #[test]
+    fn dotted_properties_are_not_translations() {
+        let dir = testdir!();
+
+        create_test_file(
+            &dir.join("module.json"),
+            r#"{
+                "title": "My title",
+                "description": "My description",
+                "source": "https://powerd6.org"
+            }"#,
+        );
+        let contents_directory = create_test_directory(&dir.join(CONTENTS_DIRECTORY));
+        create_test_file(
+            &contents_directory.join("sword.json"),
+            r#"{
+                "type": "item",
+                "stats.max": 5
+            }"#,
+        );
+
+        create_test_file(
+            &contents_directory.join("notes.old.json"),
+            r#"{"type": "note"}"#,
+        );
+        let locales_directory = create_test_directory(&dir.join(LOCALES_DIRECTORY));
+        create_test_directory(&locales_directory.join("pt"));
+
+        let module = Module::try_from(FileSystem::try_from(dir.clone()).unwrap()).unwrap();
+        let contents = module.contents.unwrap();
+        assert_eq!(contents["sword"]["stats.max"], Value::from(5));
+        assert!(contents.contains_key("notes.old"));
+        let localized_module =
+            Module::try_from_localized(FileSystem::try_from(dir.clone()).unwrap(), Some("pt"))
+                .unwrap();
+        assert_eq!(
+            localized_module.contents.unwrap()["sword"]["stats.max"],
+            Value::from(5)
+        );
+        assert!(matches!(
+            Module::try_from_localized(FileSystem::try_from(dir).unwrap(), Some("max")),
+            Err(ModuleError::UnknownLocale(locale)) if locale == "max"
+        ));
+    }
+
+    #[test]
+    fn layouts_are_populated_from_rendering_directory() {
+        let dir = testdir!();
+
+        create_test_file(
+            &dir.join("module.json"),
+            r#"{
+                "title": "My title",
+                "description": "My description",
+                "source": "https://powerd6.org",
+                "rendering": {
+                    "txt": "{{module.title}}"
+                }
+            }"#,
+        );
+        let rendering_directory = create_test_directory(&dir.join(RENDERING_DIRECTORY));
+        create_test_file(&rendering_directory.join("md.hjs"), "# {{module.title}}");
+        create_test_file(&rendering_directory.join("md.pt.hjs"), "# Módulo");
+        let groups_directory = create_test_directory(&rendering_directory.join("@group"));
+        create_test_file(&groups_directory.join("md.hjs"), "## {{group.key}}");
+        let indexes_directory = create_test_directory(&rendering_directory.join("@index"));
+        create_test_file(&indexes_directory.join("md.hjs"), "{{files}}");
+        let locales_directory = create_test_directory(&dir.join(LOCALES_DIRECTORY));
+        create_test_directory(&locales_directory.join("pt"));
+
+        assert_eq!(
+            Module::try_from(FileSystem::try_from(dir.clone()).unwrap())
+                .unwrap()
+                .rendering,
+            Some(BTreeMap::from([
+                ("@group/md".to_string(), "## {{group.key}}".to_string()),
+                ("@index/md".to_string(), "{{files}}".to_string()),
+                ("md".to_string(), "# {{module.title}}".to_string()),
+                ("txt".to_string(), "{{module.title}}".to_string()),
+            ]))
+        );
+        assert_eq!(
+            Module::try_from_localized(FileSystem::try_from(dir).unwrap(), Some("pt"))
+                .unwrap()
+                .rendering
+                .unwrap()["md"],
+            "# Módulo"
+        );
+    }
+
+    #[test]
+    fn partials_are_populated_from_rendering_directories() {
+        let dir = testdir!();
+
+        create_test_file(
+            &dir.join("module.json"),
+            r#"{
+                "title": "My title",
+                "description": "My description",
+                "source": "https://powerd6.org"
+            }"#,
+        );
+        let rendering_directory = create_test_directory(&dir.join(RENDERING_DIRECTORY));
+        let partials_directory =
+            create_test_directory(&rendering_directory.join(PARTIALS_DIRECTORY));
+        create_test_file(&partials_directory.join("diceBox.hjs"), "[{{this}}]");
+        let types_directory = create_test_directory(&dir.join(TYPES_DIRECTORY));
+        let spell_directory = create_test_directory(&types_directory.join("spell"));
+        create_test_file(
+            &spell_directory.join("_.json"),
+            r#"{"description": "a spell"}"#,
+        );
+        let spell_rendering = create_test_directory(&spell_directory.join(RENDERING_DIRECTORY));
+        create_test_file(&spell_rendering.join("md.hjs"), "{{> spell/statBlock}}");
+        let spell_partials = create_test_directory(&spell_rendering.join(PARTIALS_DIRECTORY));
+        create_test_file(&spell_partials.join("statBlock.hjs"), "{{self.name}}");
+
+        let module = Module::try_from(FileSystem::try_from(dir).unwrap()).unwrap();
+
+        assert_eq!(
+            module.partials,
+            Some(BTreeMap::from([(
+                "diceBox".to_string(),
+                "[{{this}}]".to_string()
+            )]))
+        );
+        assert_eq!(
+            module.types.unwrap()["spell"].partials,
+            Some(BTreeMap::from([(
+                "statBlock".to_string(),
+                "{{self.name}}".to_string()
+            )]))
+        );
+    }
+
     #[test]
     fn contents_are_populated_from_file_system_and_overwrite_contents_from_module() {
         let dir = testdir!();
@@ -317,6 +891,8 @@ mod tests {
                 title: "My title".to_string(),
                 description: "My description".to_string(),
                 source: Url::parse("https://powerd6.org").unwrap(),
+                rendering: None,
+                partials: None,
                 types: None,
                 contents: Some(BTreeMap::from([
                     (
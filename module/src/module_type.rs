@@ -1,15 +1,21 @@
 use std::collections::BTreeMap;
 
 use fs::entry::Entry;
-use fs_data::EntryData;
+use fs_data::{EntryData, Localization};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 use tracing::instrument;
 
 use crate::ModuleError;
 
+/// The key to the local definitions of a JSON schema.
+pub(crate) const DEFINITIONS: &str = "$defs";
+/// The key to the local definitions of a JSON schema, before draft 2019-09.
+pub(crate) const LEGACY_DEFINITIONS: &str = "definitions";
+
 /// The representation of a powerd6 type.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
 pub struct ModuleType {
     /// The human-readable description of what the type represents.
     pub description: String,
@@ -19,14 +25,118 @@ pub struct ModuleType {
     /// The rendering code for all the supported formats.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rendering: Option<BTreeMap<String, String>>,
+    /// The partial templates of the type, used as `{{> type/name}}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partials: Option<BTreeMap<String, String>>,
+    /// The escaping of the rendering code of a format (`html`, `markdown`,
+    /// `latex` or `none`), when it differs from the default of the format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "escaping_schema")]
+    pub escaping: Option<BTreeMap<String, String>>,
+    /// The identifier of another type this type inherits the schema and
+    /// rendering code from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// The types this type inherited from, starting with it's parent, once
+    /// the inheritance of the module is resolved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ancestors: Option<Vec<String>>,
 }
 
-impl TryFrom<Entry> for ModuleType {
-    type Error = ModuleError;
+/// The schema of the escaping of a type, which only allows the known escapings.
+fn escaping_schema(_: &mut SchemaGenerator) -> Schema {
+    serde_json::from_value(json!({
+        "type": ["object", "null"],
+        "additionalProperties": {"enum": ["html", "markdown", "latex", "none"]}
+    }))
+    .expect("The escaping schema should always be a valid Schema")
+}
+
+impl ModuleType {
+    /// Creates a new type that combines this type with the (already resolved) parent type.
+    ///
+    /// The schemas are composed with `allOf`, so contents must be valid
+    /// against both of them, and the rendering code, partials and escaping of
+    /// the parent are used for every format and name this type does not define.
+    ///
+    /// The local definitions (`$defs`, or `definitions` in older schemas) of
+    /// both schemas are copied to the composed schema, so local references
+    /// keep working. Both schemas can only use the same name for a definition
+    /// when they define it in the same way.
+    pub fn inherit_from(&self, parent: &ModuleType) -> Result<ModuleType, ModuleError> {
+        let schema = match (&parent.schema, &self.schema) {
+            (Some(parent_schema), Some(schema)) => {
+                let mut composed = json!({
+                    "allOf": [parent_schema, schema]
+                });
+                for key in [DEFINITIONS, LEGACY_DEFINITIONS] {
+                    let mut definitions = Map::new();
+                    for (name, definition) in [parent_schema, schema]
+                        .iter()
+                        .filter_map(|s| s.get(key))
+                        .filter_map(Value::as_object)
+                        .flatten()
+                    {
+                        match definitions.get(name) {
+                            Some(existing) if existing != definition => {
+                                return Err(ModuleError::ConflictingDefinition(format!(
+                                    "#/{}/{}",
+                                    key, name
+                                )));
+                            }
+                            Some(_) => {}
+                            None => {
+                                definitions.insert(name.clone(), definition.clone());
+                            }
+                        }
+                    }
+                    if !definitions.is_empty() {
+                        composed[key] = Value::Object(definitions);
+                    }
+                }
+                Some(composed)
+            }
+            (parent_schema, schema) => schema.clone().or(parent_schema.clone()),
+        };
+        Ok(ModuleType {
+            description: self.description.clone(),
+            schema,
+            rendering: inherit_templates(&parent.rendering, &self.rendering),
+            partials: inherit_templates(&parent.partials, &self.partials),
+            escaping: inherit_templates(&parent.escaping, &self.escaping),
+            extends: None,
+            ancestors: None,
+        })
+    }
+}
 
+/// Combines the templates of a parent and a child type, where the child wins.
+fn inherit_templates(
+    parent_templates: &Option<BTreeMap<String, String>>,
+    templates: &Option<BTreeMap<String, String>>,
+) -> Option<BTreeMap<String, String>> {
+    match (parent_templates, templates) {
+        (Some(parent_templates), Some(templates)) => {
+            let mut result = parent_templates.clone();
+            result.extend(templates.clone());
+            Some(result)
+        }
+        (parent_templates, templates) => templates.clone().or(parent_templates.clone()),
+    }
+}
+
+impl ModuleType {
+    /// Creates a type from an Entry, translated into a locale.
+    ///
+    /// Translated files, including rendering templates and partials, are
+    /// used when their locale is provided, like `rendering/md.pt.hjs` for `pt`
+    /// (see [EntryData::try_get_localized_data]).
     #[instrument]
-    fn try_from(entry: Entry) -> Result<ModuleType, ModuleError> {
-        match entry.try_get_data() {
+    pub fn try_from_localized(
+        entry: Entry,
+        localization: Localization,
+    ) -> Result<ModuleType, ModuleError> {
+        match entry.try_get_localized_data(localization) {
             Ok(entry_data) => match serde_json::from_value::<ModuleType>(entry_data.clone()) {
                 Ok(result) => Ok(result),
                 Err(_e) => Err(ModuleError::IncompatibleFieldType(entry_data.into())),
@@ -36,13 +146,21 @@ impl TryFrom<Entry> for ModuleType {
     }
 }
 
+impl TryFrom<Entry> for ModuleType {
+    type Error = ModuleError;
+
+    #[instrument]
+    fn try_from(entry: Entry) -> Result<ModuleType, ModuleError> {
+        ModuleType::try_from_localized(entry, Localization::default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use path_utils::create_test_file;
     use pretty_assertions::assert_eq;
-    use serde_json::json;
     use testdir::testdir;
 
     #[test]
@@ -60,7 +178,11 @@ mod tests {
             ModuleType {
                 description: "my type".to_string(),
                 schema: None,
-                rendering: None
+                rendering: None,
+                partials: None,
+                escaping: None,
+                extends: None,
+                ancestors: None
             }
         )
     }
@@ -111,8 +233,285 @@ mod tests {
                 rendering: Some(BTreeMap::from([(
                     "txt".to_string(),
                     "my template".to_string()
-                )]))
+                )])),
+                partials: None,
+                escaping: None,
+                extends: None,
+                ancestors: None
             }
         )
     }
+
+    #[test]
+    fn translations_replace_the_default_locale() {
+        let dir = testdir!();
+        let root_file = create_test_file(&dir.join("_.json"), r#"{"rendering": {}}"#);
+        let description = create_test_file(&dir.join("description.md"), "my type");
+        let translated_description = create_test_file(&dir.join("description.pt.md"), "meu tipo");
+        let entry = Entry::Directory {
+            root_file,
+            extra_files: vec![description, translated_description],
+        };
+        let known_locales = vec!["pt".to_string()];
+
+        assert_eq!(
+            ModuleType::try_from_localized(
+                entry.clone(),
+                Localization {
+                    locale: None,
+                    known_locales: &known_locales
+                }
+            )
+            .unwrap()
+            .description,
+            "my type"
+        );
+        assert_eq!(
+            ModuleType::try_from_localized(
+                entry,
+                Localization {
+                    locale: Some("pt"),
+                    known_locales: &known_locales
+                }
+            )
+            .unwrap()
+            .description,
+            "meu tipo"
+        );
+    }
+
+    #[test]
+    fn translations_replace_rendering_templates_and_partials() {
+        let dir = testdir!();
+        let root_file = create_test_file(&dir.join("_.json"), r#"{"description": "my type"}"#);
+        let rendering_files = vec![
+            create_test_file(&dir.join("md.hjs"), "# {{self.name}}"),
+            create_test_file(&dir.join("md.pt.hjs"), "# {{self.nome}}"),
+        ];
+        let partial_files = vec![
+            create_test_file(&dir.join("stats.hjs"), "Cost"),
+            create_test_file(&dir.join("stats.pt.hjs"), "Custo"),
+        ];
+        let entry = Entry::RenderingDirectory {
+            root_file,
+            extra_files: vec![],
+            rendering_files,
+            partial_files,
+        };
+        let known_locales = vec!["pt".to_string()];
+
+        let default_type = ModuleType::try_from_localized(
+            entry.clone(),
+            Localization {
+                locale: None,
+                known_locales: &known_locales,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            default_type.rendering,
+            Some(BTreeMap::from([(
+                "md".to_string(),
+                "# {{self.name}}".to_string()
+            )]))
+        );
+        assert_eq!(default_type.partials.unwrap()["stats"], "Cost");
+
+        let localized_type = ModuleType::try_from_localized(
+            entry,
+            Localization {
+                locale: Some("pt"),
+                known_locales: &known_locales,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            localized_type.rendering,
+            Some(BTreeMap::from([(
+                "md".to_string(),
+                "# {{self.nome}}".to_string()
+            )]))
+        );
+        assert_eq!(localized_type.partials.unwrap()["stats"], "Custo");
+    }
+
+    #[test]
+    fn inheriting_composes_schemas_and_overrides_rendering() {
+        let parent = ModuleType {
+            description: "an item".to_string(),
+            schema: Some(json!({"required": ["name"]})),
+            rendering: Some(BTreeMap::from([
+                ("md".to_string(), "parent md".to_string()),
+                ("txt".to_string(), "parent txt".to_string()),
+            ])),
+            partials: None,
+            escaping: None,
+            extends: None,
+            ancestors: None,
+        };
+        let child = ModuleType {
+            description: "a weapon".to_string(),
+            schema: Some(json!({"required": ["damage"]})),
+            rendering: Some(BTreeMap::from([("md".to_string(), "child md".to_string())])),
+            partials: None,
+            escaping: None,
+            extends: Some("item".to_string()),
+            ancestors: None,
+        };
+
+        assert_eq!(
+            child.inherit_from(&parent).unwrap(),
+            ModuleType {
+                description: "a weapon".to_string(),
+                schema: Some(json!({
+                    "allOf": [{"required": ["name"]}, {"required": ["damage"]}]
+                })),
+                rendering: Some(BTreeMap::from([
+                    ("md".to_string(), "child md".to_string()),
+                    ("txt".to_string(), "parent txt".to_string()),
+                ])),
+                partials: None,
+                escaping: None,
+                extends: None,
+                ancestors: None
+            }
+        )
+    }
+
+    #[test]
+    fn inheriting_overrides_partials_by_name() {
+        let parent = ModuleType {
+            description: "an item".to_string(),
+            schema: None,
+            rendering: None,
+            partials: Some(BTreeMap::from([
+                ("cost".to_string(), "parent cost".to_string()),
+                ("name".to_string(), "parent name".to_string()),
+            ])),
+            escaping: None,
+            extends: None,
+            ancestors: None,
+        };
+        let child = ModuleType {
+            description: "a weapon".to_string(),
+            schema: None,
+            rendering: None,
+            partials: Some(BTreeMap::from([(
+                "name".to_string(),
+                "child name".to_string(),
+            )])),
+            escaping: None,
+            extends: Some("item".to_string()),
+            ancestors: None,
+        };
+
+        assert_eq!(
+            child.inherit_from(&parent).unwrap().partials,
+            Some(BTreeMap::from([
+                ("cost".to_string(), "parent cost".to_string()),
+                ("name".to_string(), "child name".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn inheriting_keeps_local_definitions_reachable() {
+        let parent = ModuleType {
+            description: "an item".to_string(),
+            schema: Some(json!({
+                "properties": {"cost": {"$ref": "#/$defs/price"}},
+                "$defs": {"price": {"type": "integer"}}
+            })),
+            rendering: None,
+            partials: None,
+            escaping: None,
+            extends: None,
+            ancestors: None,
+        };
+        let child = ModuleType {
+            description: "a weapon".to_string(),
+            schema: Some(json!({
+                "properties": {"damage": {"$ref": "#/$defs/dice"}},
+                "$defs": {"dice": {"type": "string"}}
+            })),
+            rendering: None,
+            partials: None,
+            escaping: None,
+            extends: Some("item".to_string()),
+            ancestors: None,
+        };
+
+        assert_eq!(
+            child.inherit_from(&parent).unwrap().schema.unwrap()["$defs"],
+            json!({"price": {"type": "integer"}, "dice": {"type": "string"}})
+        );
+    }
+
+    #[test]
+    fn inheriting_without_own_schema_uses_parent_schema() {
+        let parent = ModuleType {
+            description: "an item".to_string(),
+            schema: Some(json!({"required": ["name"]})),
+            rendering: None,
+            partials: None,
+            escaping: None,
+            extends: None,
+            ancestors: None,
+        };
+        let child = ModuleType {
+            description: "a shield".to_string(),
+            schema: None,
+            rendering: None,
+            partials: None,
+            escaping: None,
+            extends: Some("item".to_string()),
+            ancestors: None,
+        };
+
+        assert_eq!(child.inherit_from(&parent).unwrap().schema, parent.schema);
+    }
+
+    #[test]
+    fn inheriting_keeps_legacy_definitions_and_rejects_conflicting_ones() {
+        let parent = ModuleType {
+            description: "an item".to_string(),
+            schema: Some(json!({
+                "properties": {"cost": {"$ref": "#/definitions/price"}},
+                "definitions": {"price": {"type": "integer"}}
+            })),
+            rendering: None,
+            partials: None,
+            escaping: None,
+            extends: None,
+            ancestors: None,
+        };
+        let child = ModuleType {
+            description: "a weapon".to_string(),
+            schema: Some(json!({
+                "properties": {"damage": {"$ref": "#/definitions/price"}},
+                "definitions": {"price": {"type": "integer"}}
+            })),
+            rendering: None,
+            partials: None,
+            escaping: None,
+            extends: Some("item".to_string()),
+            ancestors: None,
+        };
+        let conflicting_child = ModuleType {
+            schema: Some(json!({
+                "properties": {"damage": {"$ref": "#/definitions/price"}},
+                "definitions": {"price": {"type": "string"}}
+            })),
+            ..child.clone()
+        };
+
+        assert_eq!(
+            child.inherit_from(&parent).unwrap().schema.unwrap()["definitions"],
+            json!({"price": {"type": "integer"}})
+        );
+        assert!(matches!(
+            conflicting_child.inherit_from(&parent),
+            Err(ModuleError::ConflictingDefinition(definition)) if definition == "#/definitions/price"
+        ));
+    }
 }
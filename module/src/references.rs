@@ -0,0 +1,557 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use serde_json::Value;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    defaults::{follow_reference, MAX_DEPTH},
+    module::Module,
+    REFERENCE, TYPE_KEY,
+};
+
+/// A property of a content that holds the identifier of another content.
+///
+/// Properties become references when their schema contains the
+/// `x-reference` keyword, either as `true` (any content can be referenced)
+/// or as an object listing the allowed types:
+///
+/// ```json
+/// { "type": "string", "x-reference": { "types": ["school"] } }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    /// The identifier of the content that holds the reference.
+    pub content_id: String,
+    /// The [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) to the
+    /// property inside the content.
+    pub pointer: String,
+    /// The identifier of the referenced content.
+    pub target: String,
+    /// The types the referenced content is allowed to have, if constrained.
+    pub allowed_types: Option<Vec<String>>,
+}
+
+/// A problem with the references between contents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReferenceProblem {
+    /// The referenced content does not exist.
+    Dangling(Reference),
+    /// The referenced content exists, but has a type that is not allowed.
+    TypeMismatch(Reference, Option<String>),
+    /// The contents reference each other in a cycle, listed in order.
+    Cycle(Vec<String>),
+}
+
+impl Display for ReferenceProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceProblem::Dangling(reference) => write!(
+                f,
+                "content `{}` references `{}` at `{}`, which does not exist",
+                reference.content_id, reference.target, reference.pointer
+            ),
+            ReferenceProblem::TypeMismatch(reference, found_type) => write!(
+                f,
+                "content `{}` references `{}` at `{}`, which has type `{}` instead of one of {:?}",
+                reference.content_id,
+                reference.target,
+                reference.pointer,
+                found_type.as_deref().unwrap_or("(none)"),
+                reference.allowed_types.as_deref().unwrap_or_default()
+            ),
+            ReferenceProblem::Cycle(content_ids) => write!(
+                f,
+                "contents reference each other in a cycle: {}",
+                content_ids.join(" -> ")
+            ),
+        }
+    }
+}
+
+impl Module {
+    /// Finds every reference between the contents of the module,
+    /// using the schemas of their types.
+    #[instrument(skip(self))]
+    pub fn find_references(&self) -> Vec<Reference> {
+        let mut result = Vec::new();
+        let (Some(types), Some(contents)) = (&self.types, &self.contents) else {
+            return result;
+        };
+        for (content_id, content) in contents {
+            let schema = content
+                .get(TYPE_KEY)
+                .and_then(Value::as_str)
+                .and_then(|type_key| types.get(type_key))
+                .and_then(|module_type| module_type.schema.as_ref());
+            if let Some(schema) = schema {
+                let value = Value::Object(content.clone().into_iter().collect());
+                let content = ContentSchema {
+                    content_id,
+                    root: schema,
+                };
+                collect_references(
+                    &content,
+                    &value,
+                    schema,
+                    "",
+                    0,
+                    &mut Vec::new(),
+                    &mut result,
+                );
+            }
+        }
+        result
+    }
+
+    /// Checks that all references point to existing contents of the allowed
+    /// types, and that contents do not reference each other in cycles.
+    #[instrument(skip(self))]
+    pub fn check_references(&self) -> Vec<ReferenceProblem> {
+        let empty = BTreeMap::new();
+        let contents = self.contents.as_ref().unwrap_or(&empty);
+        let mut problems = Vec::new();
+        let mut graph: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        let references = self.find_references();
+        for reference in &references {
+            match contents.get_key_value(&reference.target) {
+                None => problems.push(ReferenceProblem::Dangling(reference.clone())),
+                Some((target_id, target)) => {
+                    let target_type = target.get(TYPE_KEY).and_then(Value::as_str);
+                    if let Some(allowed_types) = &reference.allowed_types {
+                        let lineage = target_type.map_or(vec![], |t| self.type_lineage(t));
+                        if !lineage.iter().any(|t| allowed_types.iter().any(|a| a == t)) {
+                            problems.push(ReferenceProblem::TypeMismatch(
+                                reference.clone(),
+                                target_type.map(str::to_string),
+                            ));
+                        }
+                    }
+                    graph
+                        .entry(reference.content_id.as_str())
+                        .or_default()
+                        .insert(target_id.as_str());
+                }
+            }
+        }
+        problems.extend(find_cycles(&graph).into_iter().map(ReferenceProblem::Cycle));
+        problems
+    }
+
+    /// Finds a type followed by every type it inherits from, so contents of
+    /// a type are also contents of it's ancestors.
+    ///
+    /// It uses the ancestors of resolved types, and follows `extends` otherwise.
+    fn type_lineage<'a>(&'a self, type_key: &'a str) -> Vec<&'a str> {
+        let mut lineage = vec![type_key];
+        let types = self.types.as_ref();
+        let mut current = types.and_then(|types| types.get(type_key));
+        if let Some(ancestors) = current.and_then(|t| t.ancestors.as_ref()) {
+            lineage.extend(ancestors.iter().map(String::as_str));
+            return lineage;
+        }
+        while let Some(parent_key) = current.and_then(|t| t.extends.as_deref()) {
+            if lineage.contains(&parent_key) {
+                warn!("The types that `{}` extends form a cycle", type_key);
+                break;
+            }
+            lineage.push(parent_key);
+            current = types.and_then(|types| types.get(parent_key));
+        }
+        lineage
+    }
+
+    /// Replaces every reference that points to an existing content with a
+    /// copy of that content, so templates can use it directly.
+    ///
+    /// The embedded contents receive an `id` property (unless they already
+    /// have one), and their own references are kept as identifiers, so cycles
+    /// are never expanded.
+    #[instrument(skip(self))]
+    pub fn embed_references(&mut self) {
+        let references = self.find_references();
+        let Some(contents) = &mut self.contents else {
+            return;
+        };
+        let originals = contents.clone();
+        for reference in references {
+            let Some(target) = originals.get(&reference.target) else {
+                warn!(
+                    "Unable to embed `{}` into `{}`, it does not exist",
+                    reference.target, reference.content_id
+                );
+                continue;
+            };
+            let mut embedded = target.clone();
+            embedded
+                .entry("id".to_string())
+                .or_insert_with(|| Value::String(reference.target.clone()));
+            let content = contents
+                .get_mut(&reference.content_id)
+                .expect("References are always found in existing contents");
+            let mut value = Value::Object(std::mem::take(content).into_iter().collect());
+            if let Some(property) = value.pointer_mut(&reference.pointer) {
+                *property = Value::Object(embedded.into_iter().collect());
+                debug!(
+                    "Embedded `{}` into `{}` at `{}`",
+                    reference.target, reference.content_id, reference.pointer
+                );
+            }
+            if let Value::Object(object) = value {
+                *content = object.into_iter().collect();
+            }
+        }
+    }
+}
+
+/// The content that references are collected from, with the schema of it's type.
+struct ContentSchema<'a> {
+    content_id: &'a str,
+    /// The schema that local references are resolved in.
+    root: &'a Value,
+}
+
+/// Collects the references in a value, walking it together with it's schema.
+///
+/// The `followed` references are the ones that were followed for this value,
+/// which are not followed again, so schemas that (indirectly) reference themselves stop.
+fn collect_references<'a>(
+    content: &ContentSchema<'a>,
+    value: &Value,
+    schema: &'a Value,
+    pointer: &str,
+    depth: usize,
+    followed: &mut Vec<&'a str>,
+    result: &mut Vec<Reference>,
+) {
+    if depth > MAX_DEPTH {
+        debug!("Stopped looking for references, the schema is too deep");
+        return;
+    }
+    if let Some((reference, target)) = follow_reference(schema, content.root, followed) {
+        followed.push(reference);
+        collect_references(content, value, target, pointer, depth + 1, followed, result);
+        followed.pop();
+    }
+    if let Some(branches) = schema.get("allOf").and_then(Value::as_array) {
+        for branch in branches {
+            collect_references(content, value, branch, pointer, depth + 1, followed, result);
+        }
+    }
+    let content_id = content.content_id;
+    match (value, schema.get(REFERENCE)) {
+        (Value::String(target), Some(reference)) if reference != &Value::Bool(false) => {
+            let allowed_types = reference.get("types").and_then(Value::as_array).map(|t| {
+                t.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            });
+            // The same property can be described by several schemas, like the
+            // branches of `allOf`, and keeps the first constraint on it's types
+            let existing = result
+                .iter_mut()
+                .rev()
+                .take_while(|r| r.content_id == content_id)
+                .find(|r| r.pointer == pointer);
+            match existing {
+                Some(existing) => {
+                    if existing.allowed_types.is_none() {
+                        existing.allowed_types = allowed_types;
+                    }
+                }
+                None => result.push(Reference {
+                    content_id: content_id.to_string(),
+                    pointer: pointer.to_string(),
+                    target: target.clone(),
+                    allowed_types,
+                }),
+            }
+        }
+        (Value::Object(object), _) => {
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, property_schema) in properties {
+                    if let Some(property) = object.get(name) {
+                        let property_pointer =
+                            format!("{}/{}", pointer, name.replace('~', "~0").replace('/', "~1"));
+                        collect_references(
+                            content,
+                            property,
+                            property_schema,
+                            &property_pointer,
+                            depth + 1,
+                            &mut Vec::new(),
+                            result,
+                        );
+                    }
+                }
+            }
+        }
+        (Value::Array(items), _) => {
+            if let Some(items_schema) = schema.get("items").filter(|s| s.is_object()) {
+                for (index, item) in items.iter().enumerate() {
+                    collect_references(
+                        content,
+                        item,
+                        items_schema,
+                        &format!("{}/{}", pointer, index),
+                        depth + 1,
+                        &mut Vec::new(),
+                        result,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Finds the cycles of a directed graph, each reported once.
+fn find_cycles<'a>(graph: &BTreeMap<&'a str, BTreeSet<&'a str>>) -> Vec<Vec<String>> {
+    let mut cycles = Vec::new();
+    let mut seen_cycles: BTreeSet<BTreeSet<&str>> = BTreeSet::new();
+    let mut finished: BTreeSet<&str> = BTreeSet::new();
+    for node in graph.keys() {
+        visit(node, graph, &mut Vec::new(), &mut finished, &mut |cycle| {
+            if seen_cycles.insert(cycle.iter().copied().collect()) {
+                let mut result: Vec<String> = cycle.iter().map(|n| n.to_string()).collect();
+                result.push(cycle[0].to_string());
+                cycles.push(result);
+            }
+        });
+    }
+    cycles
+}
+
+/// Depth-first search that reports every cycle reachable from `node`.
+fn visit<'a>(
+    node: &'a str,
+    graph: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+    path: &mut Vec<&'a str>,
+    finished: &mut BTreeSet<&'a str>,
+    on_cycle: &mut impl FnMut(&[&'a str]),
+) {
+    if let Some(start) = path.iter().position(|n| *n == node) {
+        on_cycle(&path[start..]);
+        return;
+    }
+    if finished.contains(node) {
+        return;
+    }
+    path.push(node);
+    for next in graph.get(node).into_iter().flatten() {
+        visit(next, graph, path, finished, on_cycle);
+    }
+    path.pop();
+    finished.insert(node);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn module(contents: Value) -> Module {
+        serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "school": {"description": "a school of magic"},
+                "spell": {
+                    "description": "a spell",
+                    "schema": {
+                        "properties": {
+                            "school": {"type": "string", "x-reference": {"types": ["school"]}},
+                            "related": {"type": "array", "items": {"$ref": "#/$defs/spell"}}
+                        },
+                        "$defs": {"spell": {"type": "string", "x-reference": true}}
+                    }
+                }
+            },
+            "contents": contents
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_references_in_properties_and_items() {
+        let module = module(json!({
+            "fire": {"type": "school"},
+            "fireball": {"type": "spell", "school": "fire", "related": ["firebolt"]}
+        }));
+
+        assert_eq!(
+            module.find_references(),
+            vec![
+                Reference {
+                    content_id: "fireball".to_string(),
+                    pointer: "/related/0".to_string(),
+                    target: "firebolt".to_string(),
+                    allowed_types: None
+                },
+                Reference {
+                    content_id: "fireball".to_string(),
+                    pointer: "/school".to_string(),
+                    target: "fire".to_string(),
+                    allowed_types: Some(vec!["school".to_string()])
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_dangling_references_and_type_mismatches() {
+        let module = module(json!({
+            "firebolt": {"type": "spell", "school": "fireball"},
+            "fireball": {"type": "spell", "school": "fire"}
+        }));
+
+        let problems = module.check_references();
+
+        assert_eq!(problems.len(), 2);
+        assert!(matches!(&problems[0], ReferenceProblem::Dangling(r) if r.target == "fire"));
+        assert!(
+            matches!(&problems[1], ReferenceProblem::TypeMismatch(r, Some(t)) if r.target == "fireball" && t == "spell")
+        );
+    }
+
+    #[test]
+    fn allows_contents_of_types_that_extend_the_allowed_types() {
+        let mut module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "item": {"description": "an item"},
+                "weapon": {"description": "a weapon", "extends": "item"},
+                "sword": {"description": "a sword", "extends": "weapon"},
+                "spell": {"description": "a spell"},
+                "hero": {
+                    "description": "a hero",
+                    "schema": {"properties": {
+                        "gear": {"type": "string", "x-reference": {"types": ["item"]}}
+                    }}
+                }
+            },
+            "contents": {
+                "excalibur": {"type": "sword"},
+                "fireball": {"type": "spell"},
+                "arthur": {"type": "hero", "gear": "excalibur"},
+                "merlin": {"type": "hero", "gear": "fireball"}
+            }
+        }))
+        .unwrap();
+
+        for _ in 0..2 {
+            let problems = module.check_references();
+            assert_eq!(problems.len(), 1);
+            assert!(
+                matches!(&problems[0], ReferenceProblem::TypeMismatch(r, _) if r.content_id == "merlin")
+            );
+            module.resolve_type_inheritance().unwrap();
+        }
+    }
+
+    #[test]
+    fn references_in_several_branches_are_found_once() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {
+                "description": "a spell",
+                "schema": {"allOf": [
+                    {"properties": {"school": {"type": "string", "x-reference": true}}},
+                    {"properties": {"school": {"x-reference": {"types": ["school"]}}}}
+                ]}
+            }},
+            "contents": {"fireball": {"type": "spell", "school": "fire"}}
+        }))
+        .unwrap();
+
+        assert_eq!(
+            module.find_references(),
+            vec![Reference {
+                content_id: "fireball".to_string(),
+                pointer: "/school".to_string(),
+                target: "fire".to_string(),
+                allowed_types: Some(vec!["school".to_string()])
+            }]
+        );
+    }
+
+    #[test]
+    fn stops_at_schemas_that_reference_themselves() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {
+                "description": "a spell",
+                "schema": {
+                    "$ref": "#/$defs/spell",
+                    "$defs": {"spell": {
+                        "allOf": [{"$ref": "#/$defs/spell"}, {"$ref": "#/$defs/spell"}],
+                        "properties": {
+                            "school": {"type": "string", "x-reference": true},
+                            "variants": {"items": {"$ref": "#/$defs/spell"}}
+                        }
+                    }}
+                }
+            }},
+            "contents": {"fireball": {"type": "spell", "variants": [{"school": "fire"}]}}
+        }))
+        .unwrap();
+
+        assert_eq!(
+            module.find_references(),
+            vec![Reference {
+                content_id: "fireball".to_string(),
+                pointer: "/variants/0/school".to_string(),
+                target: "fire".to_string(),
+                allowed_types: None
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_cycles_once() {
+        let module = module(json!({
+            "a": {"type": "spell", "related": ["b"]},
+            "b": {"type": "spell", "related": ["c"]},
+            "c": {"type": "spell", "related": ["a"]}
+        }));
+
+        assert_eq!(
+            module.check_references(),
+            vec![ReferenceProblem::Cycle(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ])]
+        );
+    }
+
+    #[test]
+    fn embeds_referenced_contents() {
+        let mut module = module(json!({
+            "fire": {"type": "school", "name": "Fire"},
+            "fireball": {"type": "spell", "school": "fire", "related": ["fireball"]}
+        }));
+
+        module.embed_references();
+
+        let fireball = &module.contents.unwrap()["fireball"];
+        assert_eq!(
+            fireball["school"],
+            json!({"id": "fire", "type": "school", "name": "Fire"})
+        );
+        assert_eq!(fireball["related"][0]["related"], json!(["fireball"]));
+    }
+}
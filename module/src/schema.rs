@@ -0,0 +1,237 @@
+use schemars::{gen::SchemaSettings, JsonSchema};
+use serde_json::{json, Map, Value};
+use tracing::{debug, instrument};
+
+use crate::{module::Module, module_type::ModuleType, module_type::DEFINITIONS, TYPE_KEY};
+
+/// The dialect of the generated schemas.
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The settings that generate schemas of the 2020-12 dialect.
+///
+/// Schemars only provides settings up to 2019-09, which keep local
+/// definitions under `definitions` instead of `$defs`.
+fn draft2020_12() -> SchemaSettings {
+    SchemaSettings::draft2019_09().with(|settings| {
+        settings.meta_schema = Some(SCHEMA_DIALECT.to_string());
+        settings.definitions_path = format!("#/{}/", DEFINITIONS);
+    })
+}
+
+/// Generates the JSON Schema of a type with the dialect used by the module schemas.
+fn schema_of<T: JsonSchema>() -> Value {
+    let schema = draft2020_12().into_generator().into_root_schema_for::<T>();
+    let mut schema =
+        serde_json::to_value(schema).expect("Serializing a JSON Schema should always succeed.");
+    if let Some(object) = schema.as_object_mut() {
+        if let Some(definitions) = object.remove("definitions") {
+            object.insert(DEFINITIONS.to_string(), definitions);
+        }
+    }
+    schema
+}
+
+/// The JSON Schema of a built module, like `module.json`.
+///
+/// Unlike the other schemas, it requires the properties that modules must have,
+/// since a built module holds all of them.
+pub fn module_schema() -> Value {
+    schema_of::<Module>()
+}
+
+/// The JSON Schema of a single type, like the files inside the `types` directory.
+///
+/// No property is required, since the properties of a type can be split
+/// into several files of a source directory.
+pub fn type_schema() -> Value {
+    let mut schema = schema_of::<ModuleType>();
+    remove_required(&mut schema);
+    schema
+}
+
+impl Module {
+    /// Builds a JSON Schema for the contents of this module.
+    ///
+    /// A content must match one of the types of the module, chosen by it's
+    /// `type` property, and the schema of that type when it has one.
+    /// The schemas of the types are kept under `$defs`, keyed by the type.
+    ///
+    /// No property is required, not even by the schemas of the types, since
+    /// the properties of a content can be split into several files of a
+    /// source directory.
+    #[instrument(skip(self))]
+    pub fn content_schema(&self) -> Value {
+        let mut definitions = Map::new();
+        let mut branches = Vec::new();
+        for (type_key, module_type) in self.types.iter().flatten() {
+            let discriminant = json!({"properties": {TYPE_KEY: {"const": type_key}}});
+            let branch = match &module_type.schema {
+                Some(schema) => {
+                    debug!("Including the schema of type `{}`", type_key);
+                    let prefix = format!("#/{}/{}", DEFINITIONS, escape_pointer(type_key));
+                    let mut schema = schema.clone();
+                    if let Value::Object(object) = &mut schema {
+                        object.remove("$schema");
+                        object.remove("$id");
+                    }
+                    rebase_references(&mut schema, &prefix);
+                    remove_required(&mut schema);
+                    definitions.insert(type_key.clone(), schema);
+                    json!({"allOf": [discriminant, {"$ref": prefix}]})
+                }
+                None => discriminant,
+            };
+            branches.push(branch);
+        }
+        json!({
+            "$schema": SCHEMA_DIALECT,
+            "title": format!("A content of {}", self.title),
+            "type": "object",
+            "anyOf": branches,
+            DEFINITIONS: definitions
+        })
+    }
+}
+
+/// Makes the local references of a schema relative to the location it is moved to.
+fn rebase_references(schema: &mut Value, prefix: &str) {
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(reference) if key == "$ref" && reference.starts_with('#') => {
+                        *reference = format!("{}{}", prefix, &reference[1..]);
+                    }
+                    value => rebase_references(value, prefix),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| rebase_references(value, prefix)),
+        _ => {}
+    }
+}
+
+/// Removes the `required` keyword from a schema, and from all of it's subschemas.
+fn remove_required(schema: &mut Value) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+    object.remove("required");
+    for (keyword, value) in object.iter_mut() {
+        match (keyword.as_str(), value) {
+            (
+                "properties" | "patternProperties" | "dependentSchemas" | DEFINITIONS
+                | "definitions",
+                Value::Object(schemas),
+            ) => schemas.values_mut().for_each(remove_required),
+            ("allOf" | "anyOf" | "oneOf" | "prefixItems" | "items", Value::Array(schemas)) => {
+                schemas.iter_mut().for_each(remove_required)
+            }
+            (
+                "items"
+                | "additionalItems"
+                | "additionalProperties"
+                | "unevaluatedItems"
+                | "unevaluatedProperties"
+                | "propertyNames"
+                | "contains"
+                | "not"
+                | "if"
+                | "then"
+                | "else",
+                schema,
+            ) => remove_required(schema),
+            _ => {}
+        }
+    }
+}
+
+fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn module_schema_describes_required_fields() {
+        let schema = module_schema();
+
+        assert_eq!(schema["$schema"], json!(SCHEMA_DIALECT));
+        assert_eq!(
+            schema["required"],
+            json!(["description", "source", "title"])
+        );
+        assert_eq!(schema.get("definitions"), None);
+        assert!(schema[DEFINITIONS]["ModuleType"].is_object());
+        assert_eq!(
+            schema["properties"]["types"]["additionalProperties"]["$ref"],
+            json!("#/$defs/ModuleType")
+        );
+        assert_eq!(schema["properties"]["source"]["format"], json!("uri"));
+    }
+
+    #[test]
+    fn type_schema_does_not_require_fields() {
+        let schema = type_schema();
+
+        assert_eq!(schema.get("required"), None);
+        assert!(schema["properties"]["description"].is_object());
+        assert!(schema["properties"]["extends"].is_object());
+    }
+
+    #[test]
+    fn content_schema_uses_the_module_types() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "note": {"description": "a note"},
+                "spell": {
+                    "description": "a spell",
+                    "schema": {
+                        "$schema": SCHEMA_DIALECT,
+                        "properties": {
+                            "cost": {"$ref": "#/$defs/cost"},
+                            "required": {"type": "boolean"}
+                        },
+                        "required": ["cost"],
+                        "$defs": {"cost": {"type": "object", "required": ["mana"]}}
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            module.content_schema(),
+            json!({
+                "$schema": SCHEMA_DIALECT,
+                "title": "A content of My title",
+                "type": "object",
+                "anyOf": [
+                    {"properties": {"type": {"const": "note"}}},
+                    {"allOf": [
+                        {"properties": {"type": {"const": "spell"}}},
+                        {"$ref": "#/$defs/spell"}
+                    ]}
+                ],
+                "$defs": {
+                    "spell": {
+                        "properties": {
+                            "cost": {"$ref": "#/$defs/spell/$defs/cost"},
+                            "required": {"type": "boolean"}
+                        },
+                        "$defs": {"cost": {"type": "object"}}
+                    }
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use ed25519_dalek::{
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    Signature, Signer, Verifier,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::SignatureError;
+
+/// The algorithm used for all signatures.
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// A detached signature of a Module.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModuleSignature {
+    /// The algorithm used to sign the module.
+    pub algorithm: String,
+    /// The public key that verifies the signature, encoded as hexadecimal.
+    pub public_key: String,
+    /// The signature, encoded as hexadecimal.
+    pub signature: String,
+}
+
+/// Reads a private key from a PKCS#8 PEM document,
+/// like the ones created by `openssl genpkey -algorithm ed25519`.
+pub fn read_signing_key(pem: &str) -> Result<SigningKey, SignatureError> {
+    SigningKey::from_pkcs8_pem(pem).map_err(|e| SignatureError::InvalidKey(e.to_string()))
+}
+
+/// Reads a public key from a PEM document,
+/// like the ones created by `openssl pkey -pubout`.
+pub fn read_verifying_key(pem: &str) -> Result<VerifyingKey, SignatureError> {
+    VerifyingKey::from_public_key_pem(pem).map_err(|e| SignatureError::InvalidKey(e.to_string()))
+}
+
+/// Signs the bytes of a module file, exactly as they are distributed.
+///
+/// The file is not parsed, so every change to it breaks the signature,
+/// including fields that are not part of a [Module](crate::module::Module).
+#[instrument(skip(bytes, key))]
+pub fn sign(bytes: &[u8], key: &SigningKey) -> ModuleSignature {
+    let signature = key.sign(bytes);
+    ModuleSignature {
+        algorithm: SIGNATURE_ALGORITHM.to_string(),
+        public_key: hex::encode(key.verifying_key().as_bytes()),
+        signature: hex::encode(signature.to_bytes()),
+    }
+}
+
+/// Checks that a signature matches the bytes of a module file, and was made
+/// by one of the trusted keys.
+#[instrument(skip(bytes, trusted_keys))]
+pub fn verify_signature(
+    bytes: &[u8],
+    signature: &ModuleSignature,
+    trusted_keys: &[VerifyingKey],
+) -> Result<(), SignatureError> {
+    if signature.algorithm != SIGNATURE_ALGORITHM {
+        return Err(SignatureError::UnsupportedAlgorithm(
+            signature.algorithm.clone(),
+        ));
+    }
+    let key = trusted_keys
+        .iter()
+        .find(|k| hex::encode(k.as_bytes()) == signature.public_key.to_lowercase())
+        .ok_or_else(|| SignatureError::UntrustedKey(signature.public_key.clone()))?;
+    let bytes_of_signature: [u8; 64] = hex::decode(&signature.signature)
+        .map_err(|e| SignatureError::InvalidEncoding(e.to_string()))?
+        .try_into()
+        .map_err(|_| SignatureError::InvalidEncoding("expected 64 bytes".to_string()))?;
+    debug!("Verifying signature with key {}", signature.public_key);
+    key.verify(bytes, &Signature::from_bytes(&bytes_of_signature))
+        .map_err(|_| SignatureError::InvalidSignature)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::module::Module;
+    use ed25519_dalek::pkcs8::{spki::der::pem::LineEnding, EncodePrivateKey, EncodePublicKey};
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    fn module_value(title: &str) -> Value {
+        json!({
+            "title": title,
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "contents": {"fireball": {"type": "spell"}}
+        })
+    }
+
+    fn module(title: &str) -> Vec<u8> {
+        serde_json::to_vec_pretty(&module_value(title)).unwrap()
+    }
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn signature_verifies_with_trusted_key() {
+        let signature = sign(&module("My title"), &key(1));
+
+        assert_eq!(
+            verify_signature(
+                &module("My title"),
+                &signature,
+                &[key(2).verifying_key(), key(1).verifying_key()]
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn signature_fails_for_changed_module() {
+        let signature = sign(&module("My title"), &key(1));
+
+        assert_eq!(
+            verify_signature(
+                &module("Other title"),
+                &signature,
+                &[key(1).verifying_key()]
+            ),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn signature_fails_for_fields_added_to_the_file() {
+        let signature = sign(&module("My title"), &key(1));
+        let mut tampered = module_value("My title");
+        tampered["homepage"] = json!("https://example.com");
+        let tampered = serde_json::to_vec_pretty(&tampered).unwrap();
+
+        // Both files are read into the same module, but they are not the same file
+        assert_eq!(
+            serde_json::from_slice::<Module>(&tampered).unwrap(),
+            serde_json::from_slice::<Module>(&module("My title")).unwrap()
+        );
+        assert_eq!(
+            verify_signature(&tampered, &signature, &[key(1).verifying_key()]),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn signature_fails_for_untrusted_key() {
+        let signature = sign(&module("My title"), &key(1));
+
+        assert_eq!(
+            verify_signature(&module("My title"), &signature, &[key(2).verifying_key()]),
+            Err(SignatureError::UntrustedKey(signature.public_key.clone()))
+        );
+    }
+
+    #[test]
+    fn signature_fails_for_other_algorithms() {
+        let mut signature = sign(&module("My title"), &key(1));
+        signature.algorithm = "rsa".to_string();
+
+        assert_eq!(
+            verify_signature(&module("My title"), &signature, &[key(1).verifying_key()]),
+            Err(SignatureError::UnsupportedAlgorithm("rsa".to_string()))
+        );
+    }
+
+    #[test]
+    fn keys_are_read_from_pem() {
+        let private_pem = key(1).to_pkcs8_pem(LineEnding::LF).unwrap();
+        let public_pem = key(1)
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+
+        assert_eq!(read_signing_key(&private_pem).unwrap(), key(1));
+        assert_eq!(
+            read_verifying_key(&public_pem).unwrap(),
+            key(1).verifying_key()
+        );
+        assert!(matches!(
+            read_signing_key("not a key"),
+            Err(SignatureError::InvalidKey(_))
+        ));
+    }
+}
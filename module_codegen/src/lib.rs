@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+/// The errors that can happen when generating code for a Module.
+#[derive(Error, Debug, PartialEq)]
+pub enum CodegenError {
+    #[error("none of the types of the module have a schema")]
+    NoSchemas,
+}
+
+/// Converts the type schemas of a Module into a language-independent model.
+pub(crate) mod model;
+/// Converts identifiers between naming conventions.
+pub(crate) mod naming;
+/// Generates Rust code.
+pub mod rust;
+/// Generates TypeScript declarations.
+pub mod typescript;
@@ -0,0 +1,584 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use module::module::Module;
+use serde_json::Value;
+use tracing::{debug, instrument, warn};
+
+use crate::{naming::pascal_case, CodegenError};
+
+/// The shape of a value, as described by a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Shape {
+    /// Any JSON value.
+    Any,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Null,
+    /// A single, fixed, string.
+    Constant(String),
+    Array(Box<Shape>),
+    /// An object with arbitrary keys, and values of the same shape.
+    Map(Box<Shape>),
+    /// A value that may also be `null`.
+    Nullable(Box<Shape>),
+    /// A [Definition] of the model, by name.
+    Named(String),
+}
+
+/// A property of a structure.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Field {
+    /// The name of the property, as it appears in the data.
+    pub name: String,
+    pub shape: Shape,
+    pub required: bool,
+    pub description: Option<String>,
+}
+
+/// What a named definition represents.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DefinitionKind {
+    /// An object with known properties.
+    Struct(Vec<Field>),
+    /// One of a set of strings.
+    Enum(Vec<String>),
+    /// Another name for a shape.
+    Alias(Shape),
+}
+
+/// A named type of the model.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Definition {
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: DefinitionKind,
+}
+
+/// The definition that corresponds to the contents of a module type.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ContentType {
+    /// The identifier of the type in the module.
+    pub type_key: String,
+    /// The name of the definition of it's contents.
+    pub name: String,
+}
+
+/// A language-independent description of the types of a Module.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Model {
+    /// The title of the module.
+    pub title: String,
+    /// All definitions, in the order they should be written.
+    pub definitions: Vec<Definition>,
+    /// The definitions of the structure of the Module itself, when it's schema was provided.
+    pub module_definitions: Vec<Definition>,
+    /// The types of the module that have a schema.
+    pub content_types: Vec<ContentType>,
+}
+
+/// The maximum depth of schemas that will be followed,
+/// to protect against recursive references.
+const MAX_DEPTH: usize = 64;
+
+/// The name of the definition of the Module itself.
+pub(crate) const MODULE_NAME: &str = "Module";
+
+/// The name of the union of all contents, which types can not use.
+pub(crate) const CONTENT_NAME: &str = "Content";
+
+impl Model {
+    /// Creates a model from the schemas of the types of a Module.
+    ///
+    /// Types without a schema are skipped.
+    #[instrument(skip(module))]
+    pub(crate) fn try_from_module(module: &Module) -> Result<Model, CodegenError> {
+        Model::try_from_module_and_schema(module, None)
+    }
+
+    /// Creates a model from the schemas of the types of a Module, that also
+    /// describes the structure of the Module from it's schema, like
+    /// [module_schema](module::schema::module_schema).
+    ///
+    /// The definitions of the schema keep the names of it's `$defs`, and
+    /// `Content` is reserved for the union of all contents.
+    #[instrument(skip(module, module_schema))]
+    pub(crate) fn try_from_module_and_schema(
+        module: &Module,
+        module_schema: Option<&Value>,
+    ) -> Result<Model, CodegenError> {
+        let mut names = BTreeSet::new();
+        let mut module_definitions = Vec::new();
+        if let Some(schema) = module_schema {
+            names.insert(CONTENT_NAME.to_string());
+            let mut builder = SchemaConverter {
+                root: schema,
+                root_name: unique_name(&mut names, MODULE_NAME),
+                definitions_prefix: String::new(),
+                references: BTreeMap::new(),
+                names: &mut names,
+                definitions: Vec::new(),
+            };
+            builder.convert_root(schema, description_of(schema));
+            module_definitions = builder.definitions;
+        }
+        let mut definitions = Vec::new();
+        let mut content_types = Vec::new();
+        for (type_key, module_type) in module.types.iter().flatten() {
+            let Some(schema) = &module_type.schema else {
+                warn!("Type `{}` has no schema, and will be skipped", type_key);
+                continue;
+            };
+            let name = unique_name(&mut names, &pascal_case(type_key));
+            debug!(
+                "Generating definitions for type `{}` as `{}`",
+                type_key, name
+            );
+            let mut builder = SchemaConverter {
+                root: schema,
+                root_name: name.clone(),
+                definitions_prefix: name.clone(),
+                references: BTreeMap::new(),
+                names: &mut names,
+                definitions: Vec::new(),
+            };
+            builder.convert_root(schema, Some(module_type.description.clone()));
+            definitions.extend(builder.definitions);
+            content_types.push(ContentType {
+                type_key: type_key.clone(),
+                name,
+            });
+        }
+        if content_types.is_empty() {
+            return Err(CodegenError::NoSchemas);
+        }
+        Ok(Model {
+            title: module.title().to_string(),
+            definitions,
+            module_definitions,
+            content_types,
+        })
+    }
+}
+
+/// Returns a name that was not used before, adding a number to it if required.
+fn unique_name(names: &mut BTreeSet<String>, name: &str) -> String {
+    let name = if name.is_empty() { "Unnamed" } else { name };
+    let mut result = name.to_string();
+    let mut counter = 2;
+    while names.contains(&result) {
+        result = format!("{}{}", name, counter);
+        counter += 1;
+    }
+    names.insert(result.clone());
+    result
+}
+
+/// Converts the schema of a single type into definitions.
+struct SchemaConverter<'a> {
+    root: &'a Value,
+    root_name: String,
+    /// The prefix of the names of local definitions (`$defs`).
+    definitions_prefix: String,
+    /// The names of the definitions created for local references, by pointer.
+    references: BTreeMap<String, String>,
+    names: &'a mut BTreeSet<String>,
+    definitions: Vec<Definition>,
+}
+
+impl SchemaConverter<'_> {
+    fn convert_root(&mut self, schema: &Value, description: Option<String>) {
+        // Reserve names for local definitions first, so they can be referenced anywhere.
+        let mut local_definitions = Vec::new();
+        if let Some(defs) = schema.get("$defs").and_then(Value::as_object) {
+            for (def_name, def_schema) in defs {
+                let name = unique_name(
+                    self.names,
+                    &format!("{}{}", self.definitions_prefix, pascal_case(def_name)),
+                );
+                self.references
+                    .insert(format!("/$defs/{}", escape_pointer(def_name)), name.clone());
+                local_definitions.push((name, def_schema));
+            }
+        }
+        self.references
+            .insert(String::new(), self.root_name.clone());
+
+        let root_name = self.root_name.clone();
+        let fields = self.collect_fields(schema, &root_name, 0);
+        self.definitions.push(Definition {
+            name: root_name,
+            description,
+            kind: DefinitionKind::Struct(fields),
+        });
+        for (name, def_schema) in local_definitions {
+            self.define(&name, def_schema, 0);
+        }
+    }
+
+    /// Creates a definition with a name that was already reserved.
+    fn define(&mut self, name: &str, schema: &Value, depth: usize) {
+        let description = description_of(schema);
+        let kind = if has_properties(schema, self.root, depth) {
+            DefinitionKind::Struct(self.collect_fields(schema, name, depth + 1))
+        } else if let Some(values) = string_enum(schema) {
+            DefinitionKind::Enum(values)
+        } else {
+            DefinitionKind::Alias(self.shape_of_inline(schema, name, depth + 1))
+        };
+        self.definitions.push(Definition {
+            name: name.to_string(),
+            description,
+            kind,
+        });
+    }
+
+    /// Finds the shape of a schema, creating definitions for objects and enums.
+    fn shape_of(&mut self, schema: &Value, hint: &str, depth: usize) -> Shape {
+        if depth > MAX_DEPTH {
+            return Shape::Any;
+        }
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return match reference.strip_prefix('#') {
+                Some(pointer) => match self.references.get(pointer) {
+                    Some(name) => Shape::Named(name.clone()),
+                    None => match self.root.pointer(pointer) {
+                        Some(target) => self.shape_of(target, hint, depth + 1),
+                        None => {
+                            warn!("Unable to resolve the reference `{}`", reference);
+                            Shape::Any
+                        }
+                    },
+                },
+                None => {
+                    warn!("Only local references are supported, found `{}`", reference);
+                    Shape::Any
+                }
+            };
+        }
+        if has_properties(schema, self.root, depth) || string_enum(schema).is_some() {
+            let name = unique_name(self.names, hint);
+            self.define(&name, schema, depth);
+            return Shape::Named(name);
+        }
+        self.shape_of_inline(schema, hint, depth)
+    }
+
+    /// Finds the shape of a schema that is not an object with properties or an enum.
+    fn shape_of_inline(&mut self, schema: &Value, hint: &str, depth: usize) -> Shape {
+        if let Some(branches) = schema
+            .get("anyOf")
+            .or_else(|| schema.get("oneOf"))
+            .and_then(Value::as_array)
+        {
+            let non_null: Vec<&Value> = branches.iter().filter(|b| !is_null_schema(b)).collect();
+            return match non_null.as_slice() {
+                [single] if non_null.len() < branches.len() => {
+                    Shape::Nullable(Box::new(self.shape_of(single, hint, depth + 1)))
+                }
+                [single] => self.shape_of(single, hint, depth + 1),
+                _ => Shape::Any,
+            };
+        }
+        if let Some(first) = schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .and_then(|b| b.first())
+        {
+            return self.shape_of(first, hint, depth + 1);
+        }
+        if let Some(Value::String(constant)) = schema.get("const") {
+            return Shape::Constant(constant.clone());
+        }
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(t)) => t.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        let non_null: Vec<&str> = types.iter().copied().filter(|t| *t != "null").collect();
+        let shape = match non_null.as_slice() {
+            ["boolean"] => Shape::Boolean,
+            ["integer"] => Shape::Integer,
+            ["number"] => Shape::Number,
+            ["string"] => Shape::String,
+            ["array"] => match schema.get("items").filter(|i| i.is_object()) {
+                Some(items) => Shape::Array(Box::new(self.shape_of(
+                    items,
+                    &format!("{}Item", hint),
+                    depth + 1,
+                ))),
+                None => Shape::Array(Box::new(Shape::Any)),
+            },
+            ["object"] => match schema.get("additionalProperties").filter(|a| a.is_object()) {
+                Some(values) => Shape::Map(Box::new(self.shape_of(
+                    values,
+                    &format!("{}Value", hint),
+                    depth + 1,
+                ))),
+                None => Shape::Map(Box::new(Shape::Any)),
+            },
+            [] if types.contains(&"null") => return Shape::Null,
+            _ => Shape::Any,
+        };
+        if non_null.len() < types.len() {
+            Shape::Nullable(Box::new(shape))
+        } else {
+            shape
+        }
+    }
+
+    /// Collects the properties of an object schema, including the ones of
+    /// references and `allOf` compositions.
+    fn collect_fields(&mut self, schema: &Value, parent_name: &str, depth: usize) -> Vec<Field> {
+        let mut properties: BTreeMap<String, &Value> = BTreeMap::new();
+        let mut required: BTreeSet<String> = BTreeSet::new();
+        gather_properties(schema, self.root, &mut properties, &mut required, depth);
+        properties
+            .into_iter()
+            .map(|(name, property_schema)| Field {
+                shape: self.shape_of(
+                    property_schema,
+                    &format!("{}{}", parent_name, pascal_case(&name)),
+                    depth + 1,
+                ),
+                required: required.contains(&name),
+                description: description_of(property_schema),
+                name,
+            })
+            .collect()
+    }
+}
+
+/// Gathers the properties and required properties of a schema,
+/// following local references and `allOf` compositions.
+fn gather_properties<'a>(
+    schema: &'a Value,
+    root: &'a Value,
+    properties: &mut BTreeMap<String, &'a Value>,
+    required: &mut BTreeSet<String>,
+    depth: usize,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    if let Some(target) = local_reference(schema, root) {
+        gather_properties(target, root, properties, required, depth + 1);
+    }
+    for branch in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        gather_properties(branch, root, properties, required, depth + 1);
+    }
+    if let Some(object) = schema.get("properties").and_then(Value::as_object) {
+        properties.extend(object.iter().map(|(k, v)| (k.clone(), v)));
+    }
+    if let Some(names) = schema.get("required").and_then(Value::as_array) {
+        required.extend(names.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+}
+
+/// Checks if a schema (or it's `allOf` compositions) defines properties.
+fn has_properties(schema: &Value, root: &Value, depth: usize) -> bool {
+    let mut properties = BTreeMap::new();
+    gather_properties(schema, root, &mut properties, &mut BTreeSet::new(), depth);
+    !properties.is_empty()
+}
+
+/// The values of an enum, if all of them are strings.
+fn string_enum(schema: &Value) -> Option<Vec<String>> {
+    schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+}
+
+fn is_null_schema(schema: &Value) -> bool {
+    schema.get("type") == Some(&Value::String("null".to_string()))
+}
+
+fn description_of(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn local_reference<'a>(schema: &Value, root: &'a Value) -> Option<&'a Value> {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+}
+
+fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn model(types: Value) -> Model {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": types
+        }))
+        .unwrap();
+        Model::try_from_module(&module).unwrap()
+    }
+
+    #[test]
+    fn fails_without_schemas() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {"description": "a spell"}}
+        }))
+        .unwrap();
+
+        assert_eq!(
+            Model::try_from_module(&module).unwrap_err(),
+            CodegenError::NoSchemas
+        );
+    }
+
+    #[test]
+    fn nested_objects_and_enums_become_definitions() {
+        let model = model(json!({
+            "spell": {
+                "description": "a spell",
+                "schema": {
+                    "required": ["name"],
+                    "properties": {
+                        "name": {"type": "string"},
+                        "school": {"enum": ["fire", "ice"]},
+                        "cost": {"type": "object", "properties": {"mana": {"type": "integer"}}}
+                    }
+                }
+            }
+        }));
+
+        assert_eq!(
+            model
+                .definitions
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["SpellCost", "SpellSchool", "Spell"]
+        );
+        assert_eq!(
+            model.definitions[2].kind,
+            DefinitionKind::Struct(vec![
+                Field {
+                    name: "cost".to_string(),
+                    shape: Shape::Named("SpellCost".to_string()),
+                    required: false,
+                    description: None
+                },
+                Field {
+                    name: "name".to_string(),
+                    shape: Shape::String,
+                    required: true,
+                    description: None
+                },
+                Field {
+                    name: "school".to_string(),
+                    shape: Shape::Named("SpellSchool".to_string()),
+                    required: false,
+                    description: None
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn module_schema_keeps_the_names_of_it_s_definitions() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"content": {"description": "a content", "schema": {}}}
+        }))
+        .unwrap();
+        let schema = module::schema::module_schema();
+
+        let model = Model::try_from_module_and_schema(&module, Some(&schema)).unwrap();
+
+        assert_eq!(
+            model
+                .module_definitions
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Module", "ModuleTypeEscapingValue", "ModuleType"]
+        );
+        assert_eq!(model.content_types[0].name, "Content2");
+    }
+
+    #[test]
+    fn references_and_compositions_are_followed() {
+        let model = model(json!({
+            "weapon": {
+                "description": "a weapon",
+                "schema": {
+                    "allOf": [
+                        {"required": ["name"], "properties": {"name": {"type": "string"}}},
+                        {"properties": {
+                            "damage": {"$ref": "#/$defs/dice"},
+                            "tags": {"type": "array", "items": {"type": ["string", "null"]}}
+                        }}
+                    ],
+                    "$defs": {"dice": {"type": "string"}}
+                }
+            }
+        }));
+
+        assert_eq!(
+            model.definitions[0].kind,
+            DefinitionKind::Struct(vec![
+                Field {
+                    name: "damage".to_string(),
+                    shape: Shape::Named("WeaponDice".to_string()),
+                    required: false,
+                    description: None
+                },
+                Field {
+                    name: "name".to_string(),
+                    shape: Shape::String,
+                    required: true,
+                    description: None
+                },
+                Field {
+                    name: "tags".to_string(),
+                    shape: Shape::Array(Box::new(Shape::Nullable(Box::new(Shape::String)))),
+                    required: false,
+                    description: None
+                }
+            ])
+        );
+        assert_eq!(
+            model.definitions[1],
+            Definition {
+                name: "WeaponDice".to_string(),
+                description: None,
+                kind: DefinitionKind::Alias(Shape::String)
+            }
+        );
+    }
+}
@@ -0,0 +1,69 @@
+/// Splits an identifier into lowercase words, on separators and case changes.
+fn words(value: &str) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut previous_lowercase = false;
+    for c in value.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                result.push(std::mem::take(&mut current));
+            }
+            previous_lowercase = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lowercase && !current.is_empty() {
+            result.push(std::mem::take(&mut current));
+        }
+        previous_lowercase = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    result
+}
+
+/// Converts an identifier into `PascalCase`, like `fire_ball` into `FireBall`.
+pub(crate) fn pascal_case(value: &str) -> String {
+    let result: String = words(value)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    if result.starts_with(|c: char| c.is_numeric()) {
+        format!("_{}", result)
+    } else {
+        result
+    }
+}
+
+/// Converts an identifier into `snake_case`, like `fireBall` into `fire_ball`.
+pub(crate) fn snake_case(value: &str) -> String {
+    let result = words(value).join("_");
+    if result.starts_with(|c: char| c.is_numeric()) {
+        format!("_{}", result)
+    } else {
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn converts_between_conventions() {
+        assert_eq!(pascal_case("fire_ball"), "FireBall");
+        assert_eq!(pascal_case("magic-school"), "MagicSchool");
+        assert_eq!(snake_case("castingTime"), "casting_time");
+        assert_eq!(snake_case("Range (meters)"), "range_meters");
+        assert_eq!(pascal_case("3d6"), "_3d6");
+    }
+}
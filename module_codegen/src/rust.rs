@@ -0,0 +1,464 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use module::module::Module;
+use tracing::instrument;
+
+use crate::{
+    model::{Definition, DefinitionKind, Field, Model, Shape},
+    naming::{pascal_case, snake_case},
+    CodegenError,
+};
+
+/// The keywords that can not be used as identifiers without being escaped.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// The keywords that can not be escaped as raw identifiers.
+const RESERVED: &[&str] = &["crate", "self", "super", "Self"];
+
+/// Generates Rust structs and enums, with serde derives, for the contents of every type of a Module.
+///
+/// Required properties become plain fields, while optional ones become [Option]s.
+/// Nested objects and string enums get their own definitions, named after the property that holds them.
+///
+/// # Example
+///
+/// ```
+/// # use module::module::Module;
+/// # use module_codegen::rust::generate_rust;
+/// let module: Module = serde_json::from_value(serde_json::json!({
+///     "title": "My module",
+///     "description": "A module",
+///     "source": "https://powerd6.org",
+///     "types": {"spell": {
+///         "description": "A spell",
+///         "schema": {"properties": {"name": {"type": "string"}}, "required": ["name"]}
+///     }}
+/// })).unwrap();
+///
+/// let code = generate_rust(&module).unwrap();
+/// assert!(code.contains("pub struct Spell {"));
+/// assert!(code.contains("    pub name: String,"));
+/// ```
+#[instrument(skip(module))]
+pub fn generate_rust(module: &Module) -> Result<String, CodegenError> {
+    let model = Model::try_from_module(module)?;
+    let mut result = String::new();
+    writeln!(
+        result,
+        "//! The contents of the `{}` module.\n//!\n//! This file was generated by `powerd6_cli codegen`, and should not be edited by hand.\n",
+        model.title
+    )
+    .expect("Writing to a String should always succeed.");
+    result.push_str("use serde::{Deserialize, Serialize};\n");
+    let definitions: BTreeMap<&str, &Definition> = model
+        .definitions
+        .iter()
+        .map(|d| (d.name.as_str(), d))
+        .collect();
+    for definition in &model.definitions {
+        result.push('\n');
+        write_definition(&mut result, definition, &definitions);
+    }
+    Ok(result)
+}
+
+fn write_definition(
+    result: &mut String,
+    definition: &Definition,
+    definitions: &BTreeMap<&str, &Definition>,
+) {
+    write_documentation(result, "", definition.description.as_deref());
+    match &definition.kind {
+        DefinitionKind::Struct(fields) => {
+            result.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+            if fields.is_empty() {
+                let _ = writeln!(result, "pub struct {} {{}}", definition.name);
+                return;
+            }
+            let _ = writeln!(result, "pub struct {} {{", definition.name);
+            // Definitions that contain this one again need to be boxed,
+            // or the struct would have an infinite size
+            let recursive = |name: &str| {
+                name == definition.name
+                    || contains(name, &definition.name, definitions, &mut BTreeSet::new())
+            };
+            let mut used = BTreeSet::new();
+            for field in fields {
+                let base = identifier(&field.name);
+                let mut identifier = base.clone();
+                let mut counter = 2;
+                while !used.insert(identifier.clone()) {
+                    identifier = format!("{}_{}", base, counter);
+                    counter += 1;
+                }
+                write_field(result, field, &identifier, &recursive);
+            }
+            result.push_str("}\n");
+        }
+        DefinitionKind::Enum(values) => {
+            result
+                .push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]\n");
+            let _ = writeln!(result, "pub enum {} {{", definition.name);
+            let mut used = BTreeSet::new();
+            for value in values {
+                let base = match pascal_case(value) {
+                    name if name.is_empty() => "Empty".to_string(),
+                    name => name,
+                };
+                let mut variant = base.clone();
+                let mut counter = 2;
+                while !used.insert(variant.clone()) {
+                    variant = format!("{}{}", base, counter);
+                    counter += 1;
+                }
+                if &variant != value {
+                    let _ = writeln!(result, "    #[serde(rename = {:?})]", value);
+                }
+                let _ = writeln!(result, "    {},", variant);
+            }
+            result.push_str("}\n");
+        }
+        DefinitionKind::Alias(shape)
+            if refers_to(shape, &definition.name, definitions, &mut BTreeSet::new()) =>
+        {
+            // Type aliases can not refer to themselves, so they become a new type instead
+            let recursive = |name: &str| {
+                name == definition.name
+                    || contains(name, &definition.name, definitions, &mut BTreeSet::new())
+            };
+            result.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+            result.push_str("#[serde(transparent)]\n");
+            let _ = writeln!(
+                result,
+                "pub struct {}(pub {});",
+                definition.name,
+                field_type(shape, &recursive)
+            );
+        }
+        DefinitionKind::Alias(shape) => {
+            let _ = writeln!(
+                result,
+                "pub type {} = {};",
+                definition.name,
+                rust_type(shape)
+            );
+        }
+    }
+}
+
+fn write_field(
+    result: &mut String,
+    field: &Field,
+    identifier: &str,
+    recursive: &dyn Fn(&str) -> bool,
+) {
+    write_documentation(result, "    ", field.description.as_deref());
+    let mut attributes = Vec::new();
+    if identifier.trim_start_matches("r#") != field.name {
+        attributes.push(format!("rename = {:?}", field.name));
+    }
+    let field_type = match (&field.shape, field.required) {
+        (shape, true) => field_type(shape, recursive),
+        (shape, false) => {
+            let inner = match shape {
+                Shape::Nullable(inner) => inner,
+                shape => shape,
+            };
+            attributes.push("default".to_string());
+            attributes.push("skip_serializing_if = \"Option::is_none\"".to_string());
+            format!("Option<{}>", field_type(inner, recursive))
+        }
+    };
+    if !attributes.is_empty() {
+        let _ = writeln!(result, "    #[serde({})]", attributes.join(", "));
+    }
+    let _ = writeln!(result, "    pub {}: {},", identifier, field_type);
+}
+
+fn write_documentation(result: &mut String, indentation: &str, description: Option<&str>) {
+    for line in description.into_iter().flat_map(str::lines) {
+        let _ = writeln!(result, "{}/// {}", indentation, line);
+    }
+}
+
+/// Converts a property name into a valid field identifier.
+fn identifier(name: &str) -> String {
+    let result = match snake_case(name) {
+        value if value.is_empty() => "field".to_string(),
+        value => value,
+    };
+    if RESERVED.contains(&result.as_str()) {
+        format!("{}_", result)
+    } else if KEYWORDS.contains(&result.as_str()) {
+        format!("r#{}", result)
+    } else {
+        result
+    }
+}
+
+/// The Rust type of a field, boxing the definitions that are recursive.
+fn field_type(shape: &Shape, recursive: &dyn Fn(&str) -> bool) -> String {
+    match shape {
+        Shape::Named(name) if recursive(name) => format!("Box<{}>", name),
+        Shape::Nullable(inner) => format!("Option<{}>", field_type(inner, recursive)),
+        shape => rust_type(shape),
+    }
+}
+
+/// Checks if a definition contains another one directly, or through the
+/// definitions it contains, without the indirection of a [Vec] or a map.
+fn contains(
+    name: &str,
+    target: &str,
+    definitions: &BTreeMap<&str, &Definition>,
+    visited: &mut BTreeSet<String>,
+) -> bool {
+    if !visited.insert(name.to_string()) {
+        return false;
+    }
+    let shapes: Vec<&Shape> = match definitions.get(name).map(|d| &d.kind) {
+        Some(DefinitionKind::Struct(fields)) => fields.iter().map(|f| &f.shape).collect(),
+        Some(DefinitionKind::Alias(shape)) => vec![shape],
+        _ => vec![],
+    };
+    shapes
+        .into_iter()
+        .filter_map(contained_name)
+        .any(|contained| contained == target || contains(contained, target, definitions, visited))
+}
+
+/// Checks if a shape refers to a definition, directly or through the aliases it refers to.
+fn refers_to(
+    shape: &Shape,
+    target: &str,
+    definitions: &BTreeMap<&str, &Definition>,
+    visited: &mut BTreeSet<String>,
+) -> bool {
+    match shape {
+        Shape::Array(inner) | Shape::Map(inner) | Shape::Nullable(inner) => {
+            refers_to(inner, target, definitions, visited)
+        }
+        Shape::Named(name) if name == target => true,
+        Shape::Named(name) if visited.insert(name.clone()) => {
+            match definitions.get(name.as_str()).map(|d| &d.kind) {
+                Some(DefinitionKind::Alias(shape)) => {
+                    refers_to(shape, target, definitions, visited)
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// The definition a shape holds without indirection, if any.
+fn contained_name(shape: &Shape) -> Option<&str> {
+    match shape {
+        Shape::Named(name) => Some(name),
+        Shape::Nullable(inner) => contained_name(inner),
+        _ => None,
+    }
+}
+
+/// The Rust type that represents a shape.
+fn rust_type(shape: &Shape) -> String {
+    match shape {
+        Shape::Any => "serde_json::Value".to_string(),
+        Shape::Boolean => "bool".to_string(),
+        Shape::Integer => "i64".to_string(),
+        Shape::Number => "f64".to_string(),
+        Shape::String | Shape::Constant(_) => "String".to_string(),
+        Shape::Null => "()".to_string(),
+        Shape::Array(items) => format!("Vec<{}>", rust_type(items)),
+        Shape::Map(values) => format!("std::collections::BTreeMap<String, {}>", rust_type(values)),
+        Shape::Nullable(inner) => format!("Option<{}>", rust_type(inner)),
+        Shape::Named(name) => name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn generates_structs_and_enums() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "spell": {
+                    "description": "A magical effect.",
+                    "schema": {
+                        "required": ["name", "type"],
+                        "properties": {
+                            "name": {"type": "string", "description": "The name of the spell."},
+                            "type": {"const": "spell"},
+                            "castingTime": {"type": "integer"},
+                            "school": {"enum": ["fire", "ice"]},
+                            "targets": {"type": "array", "items": {"$ref": "#/$defs/target"}}
+                        },
+                        "$defs": {
+                            "target": {"properties": {"range": {"type": ["number", "null"]}}}
+                        }
+                    }
+                },
+                "note": {"description": "No schema"}
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            generate_rust(&module).unwrap(),
+            r#"//! The contents of the `My title` module.
+//!
+//! This file was generated by `powerd6_cli codegen`, and should not be edited by hand.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpellSchool {
+    #[serde(rename = "fire")]
+    Fire,
+    #[serde(rename = "ice")]
+    Ice,
+}
+
+/// A magical effect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spell {
+    #[serde(rename = "castingTime", default, skip_serializing_if = "Option::is_none")]
+    pub casting_time: Option<i64>,
+    /// The name of the spell.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub school: Option<SpellSchool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub targets: Option<Vec<SpellTarget>>,
+    pub r#type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpellTarget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<f64>,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn boxes_recursive_definitions() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"list": {
+                "description": "A list",
+                "schema": {
+                    "required": ["head"],
+                    "properties": {
+                        "head": {"$ref": "#/$defs/node"},
+                        "pair": {"$ref": "#/$defs/left"}
+                    },
+                    "$defs": {
+                        "node": {"properties": {
+                            "next": {"$ref": "#/$defs/node"},
+                            "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}
+                        }},
+                        "left": {"properties": {"right": {"$ref": "#/$defs/right"}}},
+                        "right": {"properties": {"left": {"$ref": "#/$defs/left"}}}
+                    }
+                }
+            }}
+        }))
+        .unwrap();
+
+        let code = generate_rust(&module).unwrap();
+
+        assert!(code.contains("    pub head: ListNode,\n"));
+        assert!(code.contains("    pub next: Option<Box<ListNode>>,\n"));
+        assert!(code.contains("    pub children: Option<Vec<ListNode>>,\n"));
+        assert!(code.contains("    pub right: Option<Box<ListRight>>,\n"));
+        assert!(code.contains("    pub left: Option<Box<ListLeft>>,\n"));
+    }
+
+    #[test]
+    fn recursive_aliases_become_new_types() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"list": {
+                "description": "A list",
+                "schema": {
+                    "properties": {
+                        "tree": {"$ref": "#/$defs/tree"},
+                        "maybe": {"$ref": "#/$defs/maybe"},
+                        "names": {"$ref": "#/$defs/names"}
+                    },
+                    "$defs": {
+                        "tree": {"type": "array", "items": {"$ref": "#/$defs/tree"}},
+                        "maybe": {"anyOf": [{"type": "null"}, {"$ref": "#/$defs/maybe"}]},
+                        "names": {"type": "array", "items": {"type": "string"}}
+                    }
+                }
+            }}
+        }))
+        .unwrap();
+
+        let code = generate_rust(&module).unwrap();
+
+        assert!(code.contains("#[serde(transparent)]\npub struct ListTree(pub Vec<ListTree>);\n"));
+        assert!(code.contains(
+            "#[serde(transparent)]\npub struct ListMaybe(pub Option<Box<ListMaybe>>);\n"
+        ));
+        assert!(code.contains("pub type ListNames = Vec<String>;\n"));
+    }
+
+    #[test]
+    fn suffixes_colliding_identifiers() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {
+                "description": "A spell",
+                "schema": {"properties": {
+                    "fireBall": {"type": "string"},
+                    "fire_ball": {"type": "integer"}
+                }}
+            }}
+        }))
+        .unwrap();
+
+        let code = generate_rust(&module).unwrap();
+
+        assert!(code.contains(
+            "    #[serde(rename = \"fireBall\", default, skip_serializing_if = \"Option::is_none\")]\n    pub fire_ball: Option<String>,\n"
+        ));
+        assert!(code.contains(
+            "    #[serde(rename = \"fire_ball\", default, skip_serializing_if = \"Option::is_none\")]\n    pub fire_ball_2: Option<i64>,\n"
+        ));
+    }
+
+    #[test]
+    fn escapes_identifiers() {
+        assert_eq!(identifier("type"), "r#type");
+        assert_eq!(identifier("self"), "self_");
+        assert_eq!(identifier("hitPoints"), "hit_points");
+        assert_eq!(identifier("1st"), "_1st");
+    }
+}
@@ -0,0 +1,338 @@
+use std::fmt::Write;
+
+use module::{module::Module, schema::module_schema};
+use tracing::instrument;
+
+use crate::{
+    model::{Definition, DefinitionKind, Field, Model, Shape, CONTENT_NAME, MODULE_NAME},
+    CodegenError,
+};
+
+/// Generates TypeScript declarations for a Module and the contents of every one of it's types.
+///
+/// Every content interface has it's `type` property fixed to the identifier of it's type,
+/// so the `Content` union of all of them can be narrowed on it.
+///
+/// The `Module` interface, and the interfaces it uses, are generated from the
+/// [schema of modules](module_schema), with it's contents being the `Content` union.
+///
+/// # Example
+///
+/// ```
+/// # use module::module::Module;
+/// # use module_codegen::typescript::generate_typescript;
+/// let module: Module = serde_json::from_value(serde_json::json!({
+///     "title": "My module",
+///     "description": "A module",
+///     "source": "https://powerd6.org",
+///     "types": {"spell": {
+///         "description": "A spell",
+///         "schema": {"properties": {"name": {"type": "string"}}, "required": ["name"]}
+///     }}
+/// })).unwrap();
+///
+/// let code = generate_typescript(&module).unwrap();
+/// assert!(code.contains("export interface Spell {"));
+/// assert!(code.contains("export type Content = Spell;"));
+/// ```
+#[instrument(skip(module))]
+pub fn generate_typescript(module: &Module) -> Result<String, CodegenError> {
+    let mut model = Model::try_from_module_and_schema(module, Some(&module_schema()))?;
+    if let Some(Definition {
+        kind: DefinitionKind::Struct(fields),
+        ..
+    }) = model
+        .module_definitions
+        .iter_mut()
+        .find(|d| d.name == MODULE_NAME)
+    {
+        if let Some(contents) = fields.iter_mut().find(|f| f.name == "contents") {
+            contents.shape = with_map_values(&contents.shape, Shape::Named(CONTENT_NAME.into()));
+        }
+    }
+    for content_type in &model.content_types {
+        if let Some(Definition {
+            kind: DefinitionKind::Struct(fields),
+            ..
+        }) = model
+            .definitions
+            .iter_mut()
+            .find(|d| d.name == content_type.name)
+        {
+            let discriminant = Field {
+                name: "type".to_string(),
+                shape: Shape::Constant(content_type.type_key.clone()),
+                required: true,
+                description: None,
+            };
+            match fields.iter_mut().find(|f| f.name == "type") {
+                Some(field) => *field = discriminant,
+                None => fields.insert(0, discriminant),
+            }
+        }
+    }
+
+    let mut result = String::new();
+    let _ = writeln!(
+        result,
+        "// The contents of the `{}` module.\n//\n// This file was generated by `powerd6_cli codegen`, and should not be edited by hand.\n",
+        model.title
+    );
+    for definition in &model.module_definitions {
+        write_definition(&mut result, definition);
+        result.push('\n');
+    }
+    result.push_str("/** Any of the contents of the module, identified by their `type`. */\n");
+    let names: Vec<&str> = model
+        .content_types
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    let _ = writeln!(
+        result,
+        "export type {} = {};",
+        CONTENT_NAME,
+        names.join(" | ")
+    );
+    for definition in &model.definitions {
+        result.push('\n');
+        write_definition(&mut result, definition);
+    }
+    Ok(result)
+}
+
+fn write_definition(result: &mut String, definition: &Definition) {
+    write_documentation(result, "", definition.description.as_deref());
+    match &definition.kind {
+        DefinitionKind::Struct(fields) => {
+            let _ = writeln!(result, "export interface {} {{", definition.name);
+            for field in fields {
+                write_documentation(result, "  ", field.description.as_deref());
+                let optional = if field.required { "" } else { "?" };
+                let _ = writeln!(
+                    result,
+                    "  {}{}: {};",
+                    property_name(&field.name),
+                    optional,
+                    typescript_type(&field.shape)
+                );
+            }
+            result.push_str("}\n");
+        }
+        DefinitionKind::Enum(values) => {
+            let values: Vec<String> = values.iter().map(|v| string_literal(v)).collect();
+            let _ = writeln!(
+                result,
+                "export type {} = {};",
+                definition.name,
+                values.join(" | ")
+            );
+        }
+        DefinitionKind::Alias(shape) => {
+            let _ = writeln!(
+                result,
+                "export type {} = {};",
+                definition.name,
+                typescript_type(shape)
+            );
+        }
+    }
+}
+
+fn write_documentation(result: &mut String, indentation: &str, description: Option<&str>) {
+    let Some(description) = description else {
+        return;
+    };
+    // The end of the comment is escaped, so it can't be closed early
+    let description = description.replace("*/", "*\\/");
+    let lines: Vec<&str> = description.lines().collect();
+    match lines.as_slice() {
+        [] => {}
+        [line] => {
+            let _ = writeln!(result, "{}/** {} */", indentation, line);
+        }
+        lines => {
+            let _ = writeln!(result, "{}/**", indentation);
+            for line in lines {
+                let separator = if line.is_empty() { "" } else { " " };
+                let _ = writeln!(result, "{} *{}{}", indentation, separator, line);
+            }
+            let _ = writeln!(result, "{} */", indentation);
+        }
+    }
+}
+
+/// Quotes a property name if it is not a valid identifier.
+fn property_name(name: &str) -> String {
+    let valid = name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if valid {
+        name.to_string()
+    } else {
+        string_literal(name)
+    }
+}
+
+/// Quotes a string as a TypeScript string literal.
+fn string_literal(value: &str) -> String {
+    serde_json::to_string(value).expect("Serializing a string should always succeed.")
+}
+
+/// Replaces the values of a (possibly nullable) map.
+fn with_map_values(shape: &Shape, values: Shape) -> Shape {
+    match shape {
+        Shape::Nullable(inner) => Shape::Nullable(Box::new(with_map_values(inner, values))),
+        _ => Shape::Map(Box::new(values)),
+    }
+}
+
+/// The TypeScript type that represents a shape.
+fn typescript_type(shape: &Shape) -> String {
+    match shape {
+        Shape::Any => "unknown".to_string(),
+        Shape::Boolean => "boolean".to_string(),
+        Shape::Integer | Shape::Number => "number".to_string(),
+        Shape::String => "string".to_string(),
+        Shape::Null => "null".to_string(),
+        Shape::Constant(value) => string_literal(value),
+        Shape::Array(items) => match items.as_ref() {
+            Shape::Nullable(_) => format!("({})[]", typescript_type(items)),
+            items => format!("{}[]", typescript_type(items)),
+        },
+        Shape::Map(values) => format!("Record<string, {}>", typescript_type(values)),
+        Shape::Nullable(inner) => format!("{} | null", typescript_type(inner)),
+        Shape::Named(name) => name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn generates_interfaces_and_content_union() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "spell": {
+                    "description": "A magical effect.",
+                    "schema": {
+                        "required": ["name"],
+                        "properties": {
+                            "name": {"type": "string", "description": "The name of the spell."},
+                            "school": {"enum": ["fire", "ice"]},
+                            "hit-dice": {"type": "array", "items": {"type": ["integer", "null"]}}
+                        }
+                    }
+                },
+                "weapon": {
+                    "description": "Something to hit with.",
+                    "schema": {
+                        "required": ["type"],
+                        "properties": {
+                            "type": {"type": "string"},
+                            "weight": {"type": "number"}
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let code = generate_typescript(&module).unwrap();
+
+        assert_eq!(
+            code.split_once("export type Content").unwrap().1,
+            r#" = Spell | Weapon;
+
+export type SpellSchool = "fire" | "ice";
+
+/** A magical effect. */
+export interface Spell {
+  type: "spell";
+  "hit-dice"?: (number | null)[];
+  /** The name of the spell. */
+  name: string;
+  school?: SpellSchool;
+}
+
+/** Something to hit with. */
+export interface Weapon {
+  type: "weapon";
+  weight?: number;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generates_the_module_interfaces_from_it_s_schema() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {"description": "A spell.", "schema": {}}}
+        }))
+        .unwrap();
+
+        let code = generate_typescript(&module).unwrap();
+
+        for declaration in [
+            "export interface Module {",
+            "  contents?: Record<string, Content> | null;",
+            "  types?: Record<string, ModuleType> | null;",
+            "  title: string;",
+            "export interface ModuleType {",
+            "  escaping?: Record<string, ModuleTypeEscapingValue> | null;",
+            "  schema?: unknown;",
+            r#"export type ModuleTypeEscapingValue = "html" | "markdown" | "latex" | "none";"#,
+        ] {
+            assert!(code.contains(declaration), "missing `{}`", declaration);
+        }
+    }
+
+    #[test]
+    fn documents_multiple_lines() {
+        let mut result = String::new();
+        write_documentation(&mut result, "  ", Some("First\n\nSecond"));
+
+        assert_eq!(result, "  /**\n   * First\n   *\n   * Second\n   */\n");
+    }
+
+    #[test]
+    fn escapes_literals_and_comments() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {
+                "description": "Ends early */ or not",
+                "schema": {
+                    "properties": {
+                        "bell\u{7}": {"enum": ["\u{1b}[0m", "it's"]},
+                        "line": {"const": "line\u{2028}break"}
+                    }
+                }
+            }}
+        }))
+        .unwrap();
+
+        let code = generate_typescript(&module).unwrap();
+
+        for declaration in [
+            "/** Ends early *\\/ or not */",
+            "  \"bell\\u0007\"?: SpellBell;",
+            r#"export type SpellBell = "\u001b[0m" | "it's";"#,
+            "  line?: \"line\u{2028}break\";",
+        ] {
+            assert!(code.contains(declaration), "missing `{}`", declaration);
+        }
+    }
+}
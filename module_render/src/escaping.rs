@@ -0,0 +1,157 @@
+use std::{cell::Cell, str::FromStr};
+
+use crate::RenderingError;
+
+/// How the values written by templates with `{{value}}` are escaped.
+///
+/// Values written with `{{{value}}}` are never escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escaping {
+    /// Escapes the characters that are special in HTML (and XML).
+    Html,
+    /// Escapes the characters that would become formatting in Markdown.
+    Markdown,
+    /// Escapes the characters that are special in LaTeX.
+    Latex,
+    /// Writes values unchanged.
+    None,
+}
+
+impl Escaping {
+    /// The default escaping of a format, identified by it's extension.
+    ///
+    /// Markdown and text are not escaped by default, since their templates
+    /// usually write values that are already formatted.
+    pub fn for_format(format: &str) -> Escaping {
+        match format {
+            "html" | "htm" | "xhtml" | "xml" | "svg" => Escaping::Html,
+            "tex" | "latex" => Escaping::Latex,
+            _ => Escaping::None,
+        }
+    }
+
+    /// Escapes a value.
+    pub fn escape(&self, value: &str) -> String {
+        match self {
+            Escaping::Html => handlebars::html_escape(value),
+            Escaping::Markdown => {
+                let mut result = String::with_capacity(value.len());
+                for character in value.chars() {
+                    if matches!(
+                        character,
+                        '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+                    ) {
+                        result.push('\\');
+                    }
+                    result.push(character);
+                }
+                result
+            }
+            Escaping::Latex => {
+                let mut result = String::with_capacity(value.len());
+                for character in value.chars() {
+                    match character {
+                        '\\' => result.push_str("\\textbackslash{}"),
+                        '~' => result.push_str("\\textasciitilde{}"),
+                        '^' => result.push_str("\\textasciicircum{}"),
+                        '{' | '}' | '$' | '&' | '%' | '#' | '_' => {
+                            result.push('\\');
+                            result.push(character);
+                        }
+                        _ => result.push(character),
+                    }
+                }
+                result
+            }
+            Escaping::None => value.to_string(),
+        }
+    }
+}
+
+impl FromStr for Escaping {
+    type Err = RenderingError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "html" => Ok(Escaping::Html),
+            "markdown" => Ok(Escaping::Markdown),
+            "latex" => Ok(Escaping::Latex),
+            "none" => Ok(Escaping::None),
+            _ => Err(RenderingError::InvalidEscaping(value.to_string())),
+        }
+    }
+}
+
+thread_local! {
+    /// The escaping of the template that is being rendered by this thread.
+    static CURRENT_ESCAPING: Cell<Escaping> = const { Cell::new(Escaping::Html) };
+}
+
+/// Escapes a value with the escaping of the template that is being rendered.
+pub(crate) fn escape_current(value: &str) -> String {
+    CURRENT_ESCAPING.with(Cell::get).escape(value)
+}
+
+/// The escaping of a template while it is rendered, which is restored to the
+/// escaping of the enclosing template when it is dropped.
+///
+/// Since it belongs to the thread that renders, contents rendered inside
+/// other contents use the escaping of their own template, and concurrent
+/// renders do not change each other's escaping.
+pub(crate) struct EscapingScope(Escaping);
+
+impl EscapingScope {
+    pub(crate) fn enter(escaping: Escaping) -> Self {
+        EscapingScope(CURRENT_ESCAPING.with(|current| current.replace(escaping)))
+    }
+}
+
+impl Drop for EscapingScope {
+    fn drop(&mut self) {
+        CURRENT_ESCAPING.with(|current| current.set(self.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn escapes_per_format() {
+        let value = r"Fire & *ice* {50%} <b>_\";
+
+        assert_eq!(
+            Escaping::for_format("html").escape(value),
+            r"Fire &amp; *ice* {50%} &lt;b&gt;_\"
+        );
+        assert_eq!(Escaping::for_format("md").escape(value), value);
+        assert_eq!(
+            Escaping::Markdown.escape(value),
+            r"Fire & \*ice\* {50%} \<b\>\_\\"
+        );
+        assert_eq!(
+            Escaping::for_format("tex").escape(value),
+            r"Fire \& *ice* \{50\%\} <b>\_\textbackslash{}"
+        );
+        assert_eq!(Escaping::for_format("txt").escape(value), value);
+    }
+
+    #[test]
+    fn scopes_restore_the_enclosing_escaping() {
+        let outer = EscapingScope::enter(Escaping::Latex);
+        {
+            let _inner = EscapingScope::enter(Escaping::None);
+            assert_eq!(escape_current("50%"), "50%");
+        }
+        assert_eq!(escape_current("50%"), r"50\%");
+        std::thread::spawn(|| assert_eq!(escape_current("<b>"), "&lt;b&gt;"))
+            .join()
+            .unwrap();
+        drop(outer);
+
+        assert_eq!("none".parse::<Escaping>().unwrap(), Escaping::None);
+        assert!("yaml".parse::<Escaping>().is_err());
+    }
+}
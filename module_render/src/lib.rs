@@ -15,13 +15,33 @@ pub enum RenderingError {
         "tried to render the content, but it did not define the corresponding type (key `type`): {0:#?}"
     )]
     ContentHasNoType(Box<BTreeMap<String, Value>>),
+    #[error("failed to register the module template `{0}`")]
+    FailedToRegisterLayout(String, #[source] Box<dyn Error>),
+    #[error("failed to register the partial `{0}`")]
+    FailedToRegisterPartial(String, #[source] Box<dyn Error>),
+    #[error("no content matches the identifier `{0}`")]
+    UnknownContent(String),
+    #[error("the selection of contents `{0}` is not valid")]
+    InvalidSelection(String),
+    #[error("the escaping `{0}` is not one of `html`, `markdown`, `latex` or `none`")]
+    InvalidEscaping(String),
+    #[error("the escaping of type `{0}` for format `{1}` is not valid")]
+    InvalidTypeEscaping(String, String, #[source] Box<dyn Error>),
     #[error("failed to render the content piece")]
     FailedToRender(#[source] Box<dyn Error>),
 }
 
 const TYPE_KEY: &str = "type";
 
+/// Handles how values are escaped by templates, depending on the format.
+pub mod escaping;
+/// Checks templates without rendering them.
+pub mod lint;
+/// Converts Markdown into the rendered formats.
+pub mod markdown;
 /// Handles integration with the [Module](module::module::Module) type.
 pub mod module;
 /// Handles the rendering setup and logic.
 pub mod renderer;
+/// Handles choosing which contents are rendered, and in which order.
+pub mod selection;
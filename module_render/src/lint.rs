@@ -0,0 +1,506 @@
+use std::{collections::HashMap, fmt::Display};
+
+use handlebars::{
+    template::{Parameter, Template, TemplateElement},
+    Handlebars,
+};
+use serde_json::Value;
+
+use crate::{
+    renderer::{content_template, helpers::helpers, module_template, ModuleRenderer},
+    TYPE_KEY,
+};
+
+/// The helpers of Handlebars that are not replaced by [our own](crate::renderer::helpers).
+const BUILT_IN_HELPERS: [&str; 13] = [
+    "if", "unless", "each", "with", "lookup", "raw", "log", "eq", "ne", "and", "or", "not", "len",
+];
+
+/// The keywords that compose schemas, any of which may define a property.
+const COMPOSITION_KEYWORDS: [&str; 3] = ["allOf", "anyOf", "oneOf"];
+
+/// How many local references are followed before giving up, to avoid cycles.
+const MAX_REFERENCE_DEPTH: usize = 16;
+
+/// A problem found in a template, without rendering it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateProblem {
+    /// The template uses a property that is not defined by the schema of it's type.
+    UnknownProperty(String, String),
+    /// The template uses a helper that does not exist.
+    UnknownHelper(String, String),
+    /// The template uses a partial that does not exist.
+    UnknownPartial(String, String),
+}
+
+impl Display for TemplateProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateProblem::UnknownProperty(template, path) => write!(
+                f,
+                "template `{}` uses `{}`, which is not defined by the schema",
+                template, path
+            ),
+            TemplateProblem::UnknownHelper(template, name) => write!(
+                f,
+                "template `{}` uses the helper `{}`, which does not exist",
+                template, name
+            ),
+            TemplateProblem::UnknownPartial(template, name) => write!(
+                f,
+                "template `{}` uses the partial `{}`, which does not exist",
+                template, name
+            ),
+        }
+    }
+}
+
+/// What is known about the value a template is using.
+#[derive(Debug, Clone, Copy)]
+enum Scope<'a> {
+    /// The data of a type template: `self`, `module` and `format`.
+    Root,
+    /// A value described by a schema.
+    Schema(&'a Value),
+    /// A value that can not be checked.
+    Unknown,
+}
+
+/// Walks through a compiled template, collecting the problems it finds.
+struct Linter<'a> {
+    handlebars: &'a Handlebars<'a>,
+    helpers: Vec<&'static str>,
+    /// The name of the template being checked.
+    template: &'a str,
+    /// The schema of the type the template renders, if any.
+    schema: Option<&'a Value>,
+    /// The partials used by the template, checked once the inline partials are known.
+    partials: Vec<&'a str>,
+    /// The partials defined by the template itself, with `{{#*inline "name"}}`.
+    inline_partials: Vec<&'a str>,
+    problems: Vec<TemplateProblem>,
+}
+
+impl<'a> Linter<'a> {
+    fn lint(mut self, template: &'a Template, scope: Scope<'a>) -> Vec<TemplateProblem> {
+        self.template(template, scope);
+        for partial in std::mem::take(&mut self.partials) {
+            if !self.inline_partials.contains(&partial) && !self.handlebars.has_template(partial) {
+                self.problems.push(TemplateProblem::UnknownPartial(
+                    self.template.to_string(),
+                    partial.to_string(),
+                ));
+            }
+        }
+        self.problems
+    }
+
+    fn template(&mut self, template: &'a Template, scope: Scope<'a>) {
+        for element in &template.elements {
+            self.element(element, scope);
+        }
+    }
+
+    fn element(&mut self, element: &'a TemplateElement, scope: Scope<'a>) {
+        match element {
+            TemplateElement::Expression(helper) | TemplateElement::HtmlExpression(helper) => {
+                self.helper(&helper.name, &helper.params, &helper.hash, scope);
+            }
+            TemplateElement::HelperBlock(helper) => {
+                let first = self.helper(&helper.name, &helper.params, &helper.hash, scope);
+                // Block parameters introduce names that are not part of any schema
+                let inner = match helper.name.as_name() {
+                    _ if helper.block_param.is_some() => Scope::Unknown,
+                    Some("if") | Some("unless") => scope,
+                    Some("each") => self.items(first),
+                    Some("with") => first,
+                    _ => Scope::Unknown,
+                };
+                if let Some(template) = &helper.template {
+                    self.template(template, inner);
+                }
+                if let Some(inverse) = &helper.inverse {
+                    self.template(inverse, scope);
+                }
+            }
+            TemplateElement::PartialExpression(partial)
+            | TemplateElement::PartialBlock(partial) => {
+                match partial.name.as_name() {
+                    // `@partial-block` is provided by the caller of the partial
+                    Some(name) if !name.starts_with('@') => self.partials.push(name),
+                    Some(_) => {}
+                    None => {
+                        self.parameter(&partial.name, scope);
+                    }
+                }
+                self.arguments(&partial.params, &partial.hash, scope);
+                if let Some(template) = &partial.template {
+                    self.template(template, scope);
+                }
+            }
+            TemplateElement::DecoratorExpression(decorator)
+            | TemplateElement::DecoratorBlock(decorator) => {
+                if decorator.name.as_name() == Some("inline") {
+                    if let Some(Parameter::Literal(Value::String(name))) = decorator.params.first()
+                    {
+                        self.inline_partials.push(name);
+                    }
+                }
+                if let Some(template) = &decorator.template {
+                    self.template(template, scope);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks an expression, returning the value of it's first parameter.
+    fn helper(
+        &mut self,
+        name: &'a Parameter,
+        params: &'a [Parameter],
+        hash: &'a HashMap<String, Parameter>,
+        scope: Scope<'a>,
+    ) -> Scope<'a> {
+        match name.as_name() {
+            Some(name) if self.is_helper(name) => {}
+            // Only helpers receive parameters, blocks without them iterate a property
+            Some(name) if !params.is_empty() || !hash.is_empty() => {
+                self.problems.push(TemplateProblem::UnknownHelper(
+                    self.template.to_string(),
+                    name.to_string(),
+                ));
+            }
+            _ => {
+                self.parameter(name, scope);
+            }
+        }
+        self.arguments(params, hash, scope)
+    }
+
+    /// Checks the parameters and hash of an expression, returning the value
+    /// of the first parameter.
+    fn arguments(
+        &mut self,
+        params: &'a [Parameter],
+        hash: &'a HashMap<String, Parameter>,
+        scope: Scope<'a>,
+    ) -> Scope<'a> {
+        let first = params
+            .iter()
+            .map(|param| self.parameter(param, scope))
+            .reduce(|first, _| first)
+            .unwrap_or(Scope::Unknown);
+        let mut hash: Vec<_> = hash.iter().collect();
+        hash.sort_by_key(|(key, _)| *key);
+        for (_, param) in hash {
+            self.parameter(param, scope);
+        }
+        first
+    }
+
+    fn parameter(&mut self, param: &'a Parameter, scope: Scope<'a>) -> Scope<'a> {
+        match param {
+            Parameter::Name(_) | Parameter::Path(_) => {
+                self.path(param.as_name().unwrap_or_default(), scope)
+            }
+            Parameter::Subexpression(subexpression) => {
+                self.element(&subexpression.element, scope);
+                Scope::Unknown
+            }
+            Parameter::Literal(_) => Scope::Unknown,
+        }
+    }
+
+    /// Checks a path against the current scope, returning the value it points to.
+    fn path(&mut self, path: &str, scope: Scope<'a>) -> Scope<'a> {
+        // Local variables and parent contexts are not tracked
+        if path.starts_with('@') || path.starts_with("../") {
+            return Scope::Unknown;
+        }
+        let mut current = scope;
+        for segment in path_segments(path) {
+            current = match current {
+                Scope::Root => match segment {
+                    "self" => self.schema.map_or(Scope::Unknown, Scope::Schema),
+                    "module" | "format" => Scope::Unknown,
+                    _ => return self.unknown_property(path),
+                },
+                Scope::Schema(schema) => match self.property(schema, segment) {
+                    Ok(Some(property)) => Scope::Schema(property),
+                    Ok(None) => Scope::Unknown,
+                    Err(()) => return self.unknown_property(path),
+                },
+                Scope::Unknown => return Scope::Unknown,
+            };
+        }
+        current
+    }
+
+    fn unknown_property(&mut self, path: &str) -> Scope<'a> {
+        self.problems.push(TemplateProblem::UnknownProperty(
+            self.template.to_string(),
+            path.to_string(),
+        ));
+        Scope::Unknown
+    }
+
+    /// Finds the schema of a property, or of an item when the segment is an index.
+    ///
+    /// It is `Ok(None)` when the schema does not describe it's properties, and
+    /// an error when it does, but not this one.
+    fn property(&self, schema: &'a Value, name: &str) -> Result<Option<&'a Value>, ()> {
+        if name.parse::<usize>().is_ok() {
+            return Ok(match self.items(Scope::Schema(schema)) {
+                Scope::Schema(items) => Some(items),
+                _ => None,
+            });
+        }
+        // Every content has a type, even if the schema does not mention it
+        if Some(schema) == self.schema && name == TYPE_KEY {
+            return Ok(None);
+        }
+        let mut defines_properties = false;
+        match self.find_property(schema, name, &mut defines_properties) {
+            Some(property) => Ok(Some(property)),
+            None if defines_properties => Err(()),
+            None => Ok(None),
+        }
+    }
+
+    /// Searches a property in the schema, and in the schemas it is composed of.
+    fn find_property(
+        &self,
+        schema: &'a Value,
+        name: &str,
+        defines_properties: &mut bool,
+    ) -> Option<&'a Value> {
+        let schema = self.dereference(schema);
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            *defines_properties = true;
+            if let Some(property) = properties.get(name) {
+                return Some(property);
+            }
+        }
+        COMPOSITION_KEYWORDS
+            .iter()
+            .filter_map(|keyword| schema.get(keyword).and_then(Value::as_array))
+            .flatten()
+            .find_map(|composed| self.find_property(composed, name, defines_properties))
+    }
+
+    /// The value of the items of an array, as iterated by `{{#each}}`.
+    fn items(&self, scope: Scope<'a>) -> Scope<'a> {
+        match scope {
+            Scope::Schema(schema) => self
+                .dereference(schema)
+                .get("items")
+                .filter(|items| items.is_object())
+                .map_or(Scope::Unknown, Scope::Schema),
+            _ => Scope::Unknown,
+        }
+    }
+
+    /// Follows the local references (`#/...`) of a schema.
+    fn dereference(&self, mut schema: &'a Value) -> &'a Value {
+        for _ in 0..MAX_REFERENCE_DEPTH {
+            let target = schema
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix('#'))
+                .and_then(|pointer| self.schema?.pointer(pointer));
+            match target {
+                Some(target) => schema = target,
+                None => break,
+            }
+        }
+        schema
+    }
+
+    fn is_helper(&self, name: &str) -> bool {
+        BUILT_IN_HELPERS.contains(&name) || self.helpers.contains(&name)
+    }
+}
+
+impl ModuleRenderer<'_> {
+    /// Checks every template of the module without rendering it, finding the
+    /// helpers and partials that do not exist.
+    ///
+    /// The templates of types, and their partials, are also checked against the
+    /// schema of the type: they may only use `self`, `module` and `format`, and
+    /// the properties of `self` must be defined by the schema.
+    /// Properties inside `{{#each}}` and `{{#with}}` blocks are checked too.
+    pub fn lint_templates(&self) -> Vec<TemplateProblem> {
+        let helpers: Vec<_> = helpers().into_iter().map(|(name, _)| name).collect();
+        let mut templates = Vec::new();
+        for (type_key, module_type) in self.module.types.iter().flatten() {
+            let type_templates = module_type
+                .rendering
+                .iter()
+                .flatten()
+                .map(|(format, _)| content_template(type_key, format));
+            let partials = module_type
+                .partials
+                .iter()
+                .flatten()
+                .map(|(name, _)| format!("{}/{}", type_key, name));
+            for name in type_templates.chain(partials) {
+                templates.push((name, Scope::Root, module_type.schema.as_ref()));
+            }
+        }
+        for key in self.module.rendering.iter().flatten().map(|(key, _)| key) {
+            templates.push((module_template(key), Scope::Unknown, None));
+        }
+        for name in self.module.partials.iter().flatten().map(|(name, _)| name) {
+            templates.push((name.clone(), Scope::Unknown, None));
+        }
+
+        let mut result = Vec::new();
+        for (name, scope, schema) in &templates {
+            if let Some(template) = self.renderer.get_template(name) {
+                let linter = Linter {
+                    handlebars: &self.renderer,
+                    helpers: helpers.clone(),
+                    template: name,
+                    schema: *schema,
+                    partials: Vec::new(),
+                    inline_partials: Vec::new(),
+                    problems: Vec::new(),
+                };
+                result.extend(linter.lint(template, *scope));
+            }
+        }
+        result
+    }
+}
+
+/// Splits a path into the names of it's segments, keeping segments written
+/// between brackets, like `[stats.max]`, whole.
+fn path_segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        let (segment, remaining) = match rest.strip_prefix('[') {
+            Some(bracketed) => match bracketed.find(']') {
+                Some(end) => (&bracketed[..end], &bracketed[end + 1..]),
+                None => (bracketed, ""),
+            },
+            None => match rest.find(['.', '/']) {
+                Some(end) => (&rest[..end], &rest[end..]),
+                None => (rest, ""),
+            },
+        };
+        if !segment.is_empty() && (segment != "this" || rest.starts_with('[')) {
+            segments.push(segment);
+        }
+        rest = remaining.trim_start_matches(['.', '/']);
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::module::RenderableModule;
+    use module::module::Module;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn lint(rendering: &str, partials: Value) -> Vec<TemplateProblem> {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "partials": partials,
+            "types": {"spell": {
+                "description": "a spell",
+                "schema": {
+                    "properties": {
+                        "name": {"type": "string"},
+                        "cost": {"$ref": "#/$defs/cost"},
+                        "effects": {"type": "array", "items": {"properties": {"text": {}}}},
+                        "stats.max": {"type": "integer"}
+                    },
+                    "allOf": [{"properties": {"school": {"type": "string"}}}],
+                    "$defs": {"cost": {"properties": {"mana": {"type": "integer"}}}}
+                },
+                "rendering": {"md": rendering}
+            }}
+        }))
+        .unwrap();
+        let renderer = module.get_renderer().unwrap();
+        renderer.lint_templates()
+    }
+
+    #[test]
+    fn accepts_properties_defined_by_the_schema() {
+        assert_eq!(
+            lint(
+                "{{module.title}} {{format}} {{self.type}} {{upper self.name}} {{self.school}} \
+                 {{self.cost.mana}} {{#each self.effects}}{{@index}} {{text}}{{/each}} \
+                 {{#with self.cost}}{{mana}}{{/with}} {{> dice self.cost}}",
+                json!({"dice": "{{this}}"})
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn finds_unknown_properties() {
+        assert_eq!(
+            lint(
+                "{{self.nmae}} {{name}} {{self.cost.gold}} {{#each self.effects}}{{txt}}{{/each}} \
+                 {{#if (gt self.level 1)}}{{/if}}",
+                json!({})
+            ),
+            vec![
+                TemplateProblem::UnknownProperty("spell_md".to_string(), "self.nmae".to_string()),
+                TemplateProblem::UnknownProperty("spell_md".to_string(), "name".to_string()),
+                TemplateProblem::UnknownProperty(
+                    "spell_md".to_string(),
+                    "self.cost.gold".to_string()
+                ),
+                TemplateProblem::UnknownProperty("spell_md".to_string(), "txt".to_string()),
+                TemplateProblem::UnknownProperty("spell_md".to_string(), "self.level".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bracketed_segments_are_whole_properties() {
+        assert_eq!(
+            lint(
+                "{{self.[stats.max]}} {{self.[stats.min]}} {{self.stats.max}} {{[self].[name]}}",
+                json!({})
+            ),
+            vec![
+                TemplateProblem::UnknownProperty(
+                    "spell_md".to_string(),
+                    "self.[stats.min]".to_string()
+                ),
+                TemplateProblem::UnknownProperty(
+                    "spell_md".to_string(),
+                    "self.stats.max".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_unknown_helpers_and_partials() {
+        assert_eq!(
+            lint(
+                "{{shout self.name}} {{#loop self.effects}}{{/loop}} {{> missing}} \
+                 {{#*inline \"local\"}}{{/inline}}{{> local}}",
+                json!({"dice": "{{roll this}}"})
+            ),
+            vec![
+                TemplateProblem::UnknownHelper("spell_md".to_string(), "shout".to_string()),
+                TemplateProblem::UnknownHelper("spell_md".to_string(), "loop".to_string()),
+                TemplateProblem::UnknownPartial("spell_md".to_string(), "missing".to_string()),
+                TemplateProblem::UnknownHelper("dice".to_string(), "roll".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use pulldown_cmark::{html, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde_json::Value;
+
+/// The media type of properties that hold Markdown, set with the
+/// `contentMediaType` keyword of their schema.
+pub const MARKDOWN_MEDIA_TYPE: &str = "text/markdown";
+
+/// How Markdown is converted into other formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// How many levels headings are moved down, so `# Title` becomes
+    /// `<h2>` with an offset of 1. Headings never go below `<h6>`.
+    pub heading_offset: u8,
+    /// Converts footnotes, like `[^1]`.
+    pub footnotes: bool,
+    /// Converts tables.
+    pub tables: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        MarkdownOptions {
+            heading_offset: 0,
+            footnotes: true,
+            tables: true,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    fn parser<'a>(&self, markdown: &'a str) -> Parser<'a> {
+        let mut options = Options::ENABLE_STRIKETHROUGH;
+        options.set(Options::ENABLE_FOOTNOTES, self.footnotes);
+        options.set(Options::ENABLE_TABLES, self.tables);
+        Parser::new_ext(markdown, options)
+    }
+
+    fn offset(&self, level: HeadingLevel) -> HeadingLevel {
+        HeadingLevel::try_from((level as usize + self.heading_offset as usize).min(6))
+            .expect("Heading levels are always between 1 and 6")
+    }
+}
+
+/// Checks if a format is HTML, which Markdown is converted into.
+pub fn is_html(format: &str) -> bool {
+    matches!(format, "html" | "htm")
+}
+
+/// Converts Markdown into sanitized HTML.
+pub fn to_html(markdown: &str, options: &MarkdownOptions) -> String {
+    let events = options.parser(markdown).map(|event| match event {
+        Event::Start(Tag::Heading {
+            level,
+            id,
+            classes,
+            attrs,
+        }) => Event::Start(Tag::Heading {
+            level: options.offset(level),
+            id,
+            classes,
+            attrs,
+        }),
+        Event::End(TagEnd::Heading(level)) => Event::End(TagEnd::Heading(options.offset(level))),
+        event => event,
+    });
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events);
+    ammonia::Builder::default()
+        .add_tag_attributes("div", &["class", "id"])
+        .add_tag_attributes("sup", &["class"])
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// Converts Markdown into plain text, without any formatting.
+pub fn to_text(markdown: &str, options: &MarkdownOptions) -> String {
+    let mut text = String::new();
+    for event in options.parser(markdown) {
+        match event {
+            Event::Text(value) | Event::Code(value) => text.push_str(&value),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak
+            | Event::End(TagEnd::Paragraph)
+            | Event::End(TagEnd::Heading(_))
+            | Event::End(TagEnd::Item)
+            | Event::End(TagEnd::TableRow)
+            | Event::End(TagEnd::TableHead) => text.push('\n'),
+            Event::End(TagEnd::TableCell) => text.push('\t'),
+            _ => {}
+        }
+    }
+    text.trim_end().to_string()
+}
+
+/// Converts Markdown for a format: into sanitized HTML for HTML,
+/// unchanged for Markdown (`md`), and into plain text otherwise.
+pub fn convert(markdown: &str, format: &str, options: &MarkdownOptions) -> String {
+    match format {
+        format if is_html(format) => to_html(markdown, options),
+        "md" => markdown.to_string(),
+        _ => to_text(markdown, options),
+    }
+}
+
+/// Finds the properties of a type schema that hold Markdown, including those
+/// of the schemas it is composed of with `allOf`.
+pub fn markdown_properties(schema: &Value) -> Vec<String> {
+    let mut result: Vec<String> = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(_, property)| {
+            property.get("contentMediaType").and_then(Value::as_str) == Some(MARKDOWN_MEDIA_TYPE)
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    for composed in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        result.extend(markdown_properties(composed));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn converts_to_sanitized_html() {
+        assert_eq!(
+            to_html(
+                "# Fire\n\nA *hot* spell<script>alert(1)</script>",
+                &MarkdownOptions::default()
+            ),
+            "<h1>Fire</h1>\n<p>A <em>hot</em> spell</p>\n"
+        );
+    }
+
+    #[test]
+    fn moves_headings_down() {
+        let options = MarkdownOptions {
+            heading_offset: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_html("# Fire\n###### Small", &options),
+            "<h3>Fire</h3>\n<h6>Small</h6>\n"
+        );
+    }
+
+    #[test]
+    fn tables_and_footnotes_can_be_disabled() {
+        let markdown = "| a |\n|---|\n| 1 |\n\nText[^n]\n\n[^n]: Note";
+
+        let html = to_html(markdown, &MarkdownOptions::default());
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<sup class=\"footnote-reference\">"));
+
+        let html = to_html(
+            markdown,
+            &MarkdownOptions {
+                footnotes: false,
+                tables: false,
+                ..Default::default()
+            },
+        );
+        assert!(!html.contains("<table>"));
+        assert!(!html.contains("footnote"));
+    }
+
+    #[test]
+    fn converts_per_format() {
+        let options = MarkdownOptions::default();
+
+        assert_eq!(convert("A *hot*\nspell", "md", &options), "A *hot*\nspell");
+        assert_eq!(convert("A *hot*\nspell", "txt", &options), "A hot spell");
+        assert_eq!(
+            convert("A *hot* spell", "html", &options),
+            "<p>A <em>hot</em> spell</p>\n"
+        );
+    }
+
+    #[test]
+    fn finds_markdown_properties() {
+        let schema = json!({
+            "allOf": [
+                {"properties": {"effect": {"type": "string", "contentMediaType": "text/markdown"}}},
+                {"properties": {
+                    "name": {"type": "string"},
+                    "notes": {"type": "string", "contentMediaType": "text/markdown"}
+                }}
+            ]
+        });
+
+        assert_eq!(markdown_properties(&schema), vec!["effect", "notes"]);
+    }
+}
@@ -1,9 +1,16 @@
+use std::collections::BTreeMap;
+
 use handlebars::Handlebars;
 use module::module::Module;
 use tracing::warn;
 
 use crate::{
-    renderer::{helpers::split_lines, ModuleRenderer},
+    escaping::{escape_current, Escaping},
+    markdown::markdown_properties,
+    renderer::{
+        content_template, helpers::register_helpers, module_template, ModuleRenderer,
+        RenderSettings,
+    },
     RenderingError,
 };
 
@@ -11,17 +18,53 @@ use crate::{
 pub trait RenderableModule {
     /// Uses the stored information to create a ModuleRenderer that holds all
     /// necessary information required to render content.
-    fn get_renderer(&self) -> Result<ModuleRenderer, RenderingError>;
+    fn get_renderer(&self) -> Result<ModuleRenderer<'_>, RenderingError>;
 }
 
 impl RenderableModule for Module {
-    fn get_renderer(&self) -> Result<ModuleRenderer, RenderingError> {
+    fn get_renderer(&self) -> Result<ModuleRenderer<'_>, RenderingError> {
         match &self.types {
             Some(types) => {
                 let mut handlebars = Handlebars::new();
 
                 // Register helpers
-                handlebars.register_helper("splitLines", Box::new(split_lines::split_lines));
+                register_helpers(&mut handlebars);
+
+                // Escape values depending on the template being rendered
+                handlebars.register_escape_fn(escape_current);
+                let mut template_escaping = BTreeMap::new();
+                let mut type_markdown_properties = BTreeMap::new();
+                for (type_key, module_type) in types {
+                    if let Some(schema) = &module_type.schema {
+                        type_markdown_properties
+                            .insert(type_key.to_string(), markdown_properties(schema));
+                    }
+                    for (format, value) in module_type.escaping.iter().flatten() {
+                        let type_escaping = value.parse::<Escaping>().map_err(|e| {
+                            RenderingError::InvalidTypeEscaping(
+                                type_key.to_string(),
+                                format.to_string(),
+                                e.into(),
+                            )
+                        })?;
+                        template_escaping.insert(content_template(type_key, format), type_escaping);
+                    }
+                }
+
+                // Register the partials of the module, and of every type under it's identifier
+                let partials = self.partials.iter().flatten().map(|p| (p.0.clone(), p.1));
+                let type_partials = types.iter().flat_map(|(type_key, module_type)| {
+                    module_type
+                        .partials
+                        .iter()
+                        .flatten()
+                        .map(move |(name, template)| (format!("{}/{}", type_key, name), template))
+                });
+                for (name, template) in partials.chain(type_partials) {
+                    handlebars
+                        .register_partial(&name, template)
+                        .map_err(|e| RenderingError::FailedToRegisterPartial(name, e.into()))?;
+                }
 
                 // Compile templates for all types and formats
                 for (type_key, module_type) in types {
@@ -29,7 +72,7 @@ impl RenderableModule for Module {
                         for (format, template) in type_rendering {
                             handlebars
                                 .register_template_string(
-                                    &format!("{}_{}", type_key, format),
+                                    &content_template(type_key, format),
                                     template,
                                 )
                                 .map_err(|e| {
@@ -45,10 +88,29 @@ impl RenderableModule for Module {
                     }
                 }
 
-                Ok(ModuleRenderer {
+                // Compile the layouts of the module, and the templates with reserved keys
+                for (key, template) in self.rendering.iter().flatten() {
+                    handlebars
+                        .register_template_string(&module_template(key), template)
+                        .map_err(|e| {
+                            RenderingError::FailedToRegisterLayout(key.to_string(), e.into())
+                        })?;
+                }
+
+                let renderer = ModuleRenderer {
                     module: self.clone(),
+                    module_data: serde_json::to_value(self)
+                        .expect("Module should always be a valid JSON Value"),
                     renderer: handlebars,
-                })
+                    settings: Default::default(),
+                };
+                Ok(renderer.with_settings(|settings| {
+                    *settings = RenderSettings {
+                        markdown_properties: type_markdown_properties,
+                        template_escaping,
+                        ..Default::default()
+                    }
+                }))
             }
             None => Err(RenderingError::NoRenderableTypes),
         }
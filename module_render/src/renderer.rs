@@ -1,44 +1,360 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, io::Write, sync::Arc};
 
 use handlebars::Handlebars;
-use module::module::Module;
+use module::{module::Module, GROUP_HEADERS, INDEXES};
+use serde::Serialize;
 use serde_json::Value;
 
-use crate::{RenderingError, TYPE_KEY};
+use crate::{
+    escaping::{Escaping, EscapingScope},
+    markdown::{convert, MarkdownOptions},
+    renderer::helpers::render_content::RenderContent,
+    selection::ContentSelection,
+    RenderingError, TYPE_KEY,
+};
 
 /// The object responsible for rendering content of a [Module](module::module::Module).
 ///
 /// It holds the compiled templates and helpers for the module,
 /// and exposes a simpler interface to render contents with a specific format.
 pub struct ModuleRenderer<'handlebars> {
-    pub module: Module,
+    pub(crate) module: Module,
+    /// The module as it is provided to templates, serialized only once for all of them.
+    pub(crate) module_data: Value,
     pub(crate) renderer: Handlebars<'handlebars>,
+    /// How contents are prepared and escaped, shared with the helpers that render contents.
+    pub(crate) settings: Arc<RenderSettings>,
+}
+
+/// How contents are prepared and how values are escaped when they are rendered.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RenderSettings {
+    /// How the Markdown properties of contents are converted, if they are.
+    pub(crate) markdown: Option<MarkdownOptions>,
+    /// The Markdown properties of each type, by the key of the type.
+    pub(crate) markdown_properties: BTreeMap<String, Vec<String>>,
+    /// The escaping of templates that do not use the default of their format.
+    pub(crate) template_escaping: BTreeMap<String, Escaping>,
+    /// The escaping used by all templates, if any.
+    pub(crate) forced_escaping: Option<Escaping>,
+}
+
+impl RenderSettings {
+    /// Chooses how the values written by a template are escaped: as forced,
+    /// as configured by the type of the template, or by the default of the format.
+    pub(crate) fn escaping(&self, template: &str, format: &str) -> Escaping {
+        self.forced_escaping
+            .or_else(|| self.template_escaping.get(template).copied())
+            .unwrap_or_else(|| Escaping::for_format(format))
+    }
+
+    /// Converts the Markdown properties of a content, as declared by the schema of it's type.
+    pub(crate) fn convert_markdown(&self, type_key: &str, content: &mut Value, format: &str) {
+        if let Some(options) = &self.markdown {
+            for property in self.markdown_properties.get(type_key).into_iter().flatten() {
+                if let Some(Value::String(markdown)) = content.get_mut(property) {
+                    *markdown = convert(markdown, format, options);
+                }
+            }
+        }
+    }
+}
+
+/// A content that was already rendered, as it is provided to layouts.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RenderedContent {
+    /// The identifier of the content.
+    pub id: String,
+    /// The type of the content.
+    #[serde(rename = "type")]
+    pub type_key: String,
+    /// The result of rendering the content.
+    pub rendered: String,
+}
+
+/// Rendered contents that were grouped together, as they are provided to layouts.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RenderedGroup {
+    /// The value the contents were grouped by, if any.
+    pub key: Option<Value>,
+    /// The rendered contents of the group.
+    pub contents: Vec<RenderedContent>,
+}
+
+/// The name of the template of a type for a format.
+pub(crate) fn content_template(type_key: &str, format: &str) -> String {
+    format!("{}_{}", type_key, format)
+}
+
+/// The name of the template of the module layout for a format.
+pub(crate) fn layout_template(format: &str) -> String {
+    format!("@layout/{}", format)
+}
+
+/// The name of the template of a key of the module rendering.
+///
+/// Group headers and indexes keep their reserved keys, so they are never layouts of a format.
+pub(crate) fn module_template(key: &str) -> String {
+    match key.split_once('/') {
+        Some((GROUP_HEADERS | INDEXES, _)) => key.to_string(),
+        _ => layout_template(key),
+    }
+}
+
+/// The key of the module rendering that holds the group header for a format.
+pub fn group_header_key(format: &str) -> String {
+    format!("{}/{}", GROUP_HEADERS, format)
+}
+
+/// The key of the module rendering that holds the index of split outputs for a format.
+pub fn index_key(format: &str) -> String {
+    format!("{}/{}", INDEXES, format)
+}
+
+/// Renders a template that is not part of the module, like the name of a file,
+/// without escaping the result.
+pub fn render_standalone<T: Serialize>(template: &str, data: &T) -> Result<String, RenderingError> {
+    let mut renderer = Handlebars::new();
+    renderer.register_escape_fn(handlebars::no_escape);
+    renderer.set_strict_mode(true);
+    renderer
+        .render_template(template, data)
+        .map_err(|e| RenderingError::FailedToRender(e.into()))
 }
 
 impl ModuleRenderer<'_> {
+    /// The module that is rendered.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Converts the Markdown properties of contents before they are rendered,
+    /// into sanitized HTML for HTML, or plain text for formats other than
+    /// Markdown (see [convert]).
+    ///
+    /// Properties hold Markdown when their schema has
+    /// `"contentMediaType": "text/markdown"`.
+    ///
+    /// Converted properties are values like any other, so templates must
+    /// write the converted HTML with `{{{self.effect}}}`, since
+    /// `{{self.effect}}` escapes it again.
+    pub fn with_markdown_conversion(self, options: MarkdownOptions) -> Self {
+        self.with_settings(|settings| settings.markdown = Some(options))
+    }
+
+    /// Escapes the values of every template the same way, instead of
+    /// depending on the format and the escaping of types.
+    pub fn with_escaping(self, escaping: Escaping) -> Self {
+        self.with_settings(|settings| settings.forced_escaping = Some(escaping))
+    }
+
+    /// Changes the settings of the renderer, and of the helpers that render contents.
+    pub(crate) fn with_settings(mut self, change: impl FnOnce(&mut RenderSettings)) -> Self {
+        change(Arc::make_mut(&mut self.settings));
+        self.renderer.register_helper(
+            "renderContent",
+            Box::new(RenderContent::new(self.settings.clone())),
+        );
+        self
+    }
+
+    /// Fails rendering when a template uses a property that does not exist,
+    /// instead of rendering it as an empty string.
+    pub fn with_strict_mode(mut self) -> Self {
+        self.renderer.set_strict_mode(true);
+        self
+    }
+
+    /// Starts escaping values as the template requires, until the scope is dropped.
+    fn escaping_scope(&self, template: &str, format: &str) -> EscapingScope {
+        EscapingScope::enter(self.settings.escaping(template, format))
+    }
+
+    /// Checks if the module has a layout for a format.
+    pub fn has_layout(&self, format: &str) -> bool {
+        self.renderer.has_template(&layout_template(format))
+    }
+
+    /// Renders the layout of the module for a format, directly into a writer.
+    ///
+    /// It injects a `module` property with the entire module contents, the
+    /// `format` being rendered, a `contents` property with the list of all
+    /// [rendered contents](RenderedContent), and a `groups` property with the
+    /// [groups](RenderedGroup) they belong to.
+    ///
+    /// Layouts are rendered with every content at once, so all of them are
+    /// kept in memory until the layout is rendered, unlike contents that are
+    /// rendered without a layout, which are written as they are rendered.
+    pub fn render_layout_to_write<W: Write>(
+        &self,
+        groups: &[RenderedGroup],
+        format: &str,
+        writer: W,
+    ) -> Result<(), RenderingError> {
+        let contents: Vec<&RenderedContent> = groups.iter().flat_map(|g| &g.contents).collect();
+        let data: BTreeMap<String, Value> = BTreeMap::from([
+            (
+                "contents".to_string(),
+                serde_json::to_value(contents)
+                    .expect("Rendered contents should always be a valid JSON Value"),
+            ),
+            (
+                "groups".to_string(),
+                serde_json::to_value(groups)
+                    .expect("Rendered groups should always be a valid JSON Value"),
+            ),
+        ]);
+        self.render_template_to_write(&layout_template(format), format, data, writer)
+    }
+
+    /// Checks if the module has a group header for a format.
+    pub fn has_group_header(&self, format: &str) -> bool {
+        self.has_module_template(&group_header_key(format))
+    }
+
+    /// Renders the header of a group of contents, directly into a writer.
+    ///
+    /// It injects a `module` property with the entire module contents, the
+    /// `format` being rendered, and a `group` property with the `key` the
+    /// contents were grouped by and their `count`.
+    pub fn render_group_header_to_write<W: Write>(
+        &self,
+        key: &Option<Value>,
+        count: usize,
+        format: &str,
+        writer: W,
+    ) -> Result<(), RenderingError> {
+        self.render_module_template_to_write(
+            &group_header_key(format),
+            format,
+            BTreeMap::from([(
+                "group".to_string(),
+                serde_json::json!({"key": key, "count": count}),
+            )]),
+            writer,
+        )
+    }
+
+    /// Checks if the module has a template, by it's key in the module rendering.
+    pub fn has_module_template(&self, key: &str) -> bool {
+        self.renderer.has_template(&module_template(key))
+    }
+
+    /// Renders a template of the module, by it's key in the module rendering,
+    /// directly into a writer.
+    ///
+    /// It injects a `module` property with the entire module contents, and
+    /// the `format` being rendered, next to the provided data.
+    pub fn render_module_template_to_write<W: Write>(
+        &self,
+        key: &str,
+        format: &str,
+        data: BTreeMap<String, Value>,
+        writer: W,
+    ) -> Result<(), RenderingError> {
+        self.render_template_to_write(&module_template(key), format, data, writer)
+    }
+
+    /// Renders a template of the module, by it's name, with the module and the format.
+    fn render_template_to_write<W: Write>(
+        &self,
+        template: &str,
+        format: &str,
+        mut data: BTreeMap<String, Value>,
+        writer: W,
+    ) -> Result<(), RenderingError> {
+        data.insert("module".to_string(), self.module_data.clone());
+        data.insert("format".to_string(), Value::from(format));
+        let _escaping = self.escaping_scope(template, format);
+        self.renderer
+            .render_to_write(template, &data, writer)
+            .map_err(|e| RenderingError::FailedToRender(e.into()))
+    }
+
     /// Renders a content with a registered template.
     ///
-    /// It injects a `self` property with the content, and a `module` property
-    /// with the entire module contents.
+    /// It injects a `self` property with the content, a `module` property
+    /// with the entire module contents, and the `format` being rendered.
     pub fn render(
         &self,
         content: &BTreeMap<String, Value>,
         format: &str,
     ) -> Result<String, RenderingError> {
+        let (template, data) = self.template_and_data(content, format)?;
+        let _escaping = self.escaping_scope(&template, format);
+        self.renderer
+            .render(&template, &data)
+            .map_err(|e| RenderingError::FailedToRender(e.into()))
+    }
+
+    /// Renders a content with a registered template, directly into a writer.
+    ///
+    /// The data available to the template is the same as in [render](ModuleRenderer::render).
+    pub fn render_to_write<W: Write>(
+        &self,
+        content: &BTreeMap<String, Value>,
+        format: &str,
+        writer: W,
+    ) -> Result<(), RenderingError> {
+        let (template, data) = self.template_and_data(content, format)?;
+        let _escaping = self.escaping_scope(&template, format);
+        self.renderer
+            .render_to_write(&template, &data, writer)
+            .map_err(|e| RenderingError::FailedToRender(e.into()))
+    }
+
+    /// Renders the contents with some identifiers, in the order of their identifiers.
+    ///
+    /// The identifiers may use `*` and `?` as wildcards, and each of them must
+    /// match at least one content.
+    pub fn render_ids(
+        &self,
+        ids: &[String],
+        format: &str,
+    ) -> Result<Vec<RenderedContent>, RenderingError> {
+        let no_contents = BTreeMap::new();
+        let contents = self.module.contents.as_ref().unwrap_or(&no_contents);
+        let selection = ContentSelection {
+            ids: ids.to_vec(),
+            ..Default::default()
+        };
+        selection.check_ids(contents)?;
+        selection
+            .apply(contents)
+            .into_iter()
+            .flat_map(|group| group.contents)
+            .map(|(id, content)| {
+                Ok(RenderedContent {
+                    rendered: self.render(content, format)?,
+                    id: id.clone(),
+                    type_key: content
+                        .get(TYPE_KEY)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Finds the name of the template for a content, and the data it is rendered with.
+    fn template_and_data(
+        &self,
+        content: &BTreeMap<String, Value>,
+        format: &str,
+    ) -> Result<(String, BTreeMap<String, Value>), RenderingError> {
         match content.get(TYPE_KEY).and_then(|t| t.as_str()) {
             Some(type_key) => {
-                let self_data = serde_json::to_value(content)
+                let mut self_data = serde_json::to_value(content)
                     .expect("Content should always be a valid JSON Value");
-                let module_data = serde_json::to_value(&self.module)
-                    .expect("Module should always be a valid JSON Value");
+                self.settings
+                    .convert_markdown(type_key, &mut self_data, format);
                 let data: BTreeMap<String, Value> = BTreeMap::from([
                     ("self".to_string(), self_data),
-                    ("module".to_string(), module_data),
+                    ("module".to_string(), self.module_data.clone()),
+                    ("format".to_string(), Value::from(format)),
                 ]);
-
-                self.renderer
-                    .render(&format!("{}_{}", type_key, format), &data)
-                    .map_err(|e| RenderingError::FailedToRender(e.into()))
+                Ok((content_template(type_key, format), data))
             }
             None => Err(RenderingError::ContentHasNoType(content.clone().into())),
         }
@@ -47,3 +363,371 @@ impl ModuleRenderer<'_> {
 
 /// A collection of [Handlebars helpers](https://handlebarsjs.com/guide/#custom-helpers).
 pub mod helpers;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::module::RenderableModule;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn layouts_receive_module_and_rendered_contents() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "rendering": {
+                "md": "# {{module.title}}\n{{#each contents}}\n{{this.id}}: {{{this.rendered}}}{{/each}}"
+            },
+            "types": {"spell": {"description": "a spell", "rendering": {"md": "*{{self.name}}*"}}},
+            "contents": {"fireball": {"type": "spell", "name": "Fireball"}}
+        }))
+        .unwrap();
+        let renderer = module.get_renderer().unwrap();
+        let groups = vec![RenderedGroup {
+            key: None,
+            contents: vec![RenderedContent {
+                id: "fireball".to_string(),
+                type_key: "spell".to_string(),
+                rendered: renderer
+                    .render(&module.contents.as_ref().unwrap()["fireball"], "md")
+                    .unwrap(),
+            }],
+        }];
+
+        let mut result = Vec::new();
+        renderer
+            .render_layout_to_write(&groups, "md", &mut result)
+            .unwrap();
+
+        assert!(renderer.has_layout("md"));
+        assert!(!renderer.has_layout("html"));
+        assert_eq!(
+            String::from_utf8(result).unwrap(),
+            "# My title\nfireball: *Fireball*"
+        );
+    }
+
+    #[test]
+    fn partials_are_shared_by_module_and_type_templates() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "partials": {"dice": "[{{this}}]"},
+            "types": {"spell": {
+                "description": "a spell",
+                "rendering": {"md": "{{> spell/statBlock}} {{> dice self.damage}}"},
+                "partials": {"statBlock": "*{{self.name}}*"}
+            }},
+            "contents": {"fireball": {"type": "spell", "name": "Fireball", "damage": "3d6"}}
+        }))
+        .unwrap();
+        let renderer = module.get_renderer().unwrap();
+
+        assert_eq!(
+            renderer
+                .render(&module.contents.as_ref().unwrap()["fireball"], "md")
+                .unwrap(),
+            "*Fireball* [3d6]"
+        );
+    }
+
+    #[test]
+    fn contents_render_other_contents_by_identifier() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "spell": {"description": "a spell", "rendering": {"md": "*{{self.name}}*", "txt": "{{self.name}}"}},
+                "list": {"description": "a list", "rendering": {
+                    "md": "{{#each self.spells}}{{renderContent this}} {{/each}}{{renderContent \"fireball\" \"txt\"}}"
+                }},
+                "loop": {"description": "a loop", "rendering": {"md": "{{renderContent \"loop\"}}"}}
+            },
+            "contents": {
+                "fireball": {"type": "spell", "name": "Fireball"},
+                "frost": {"type": "spell", "name": "Frost"},
+                "fire": {"type": "list", "spells": ["fireball", "frost"]},
+                "missing": {"type": "list", "spells": ["spark"]},
+                "loop": {"type": "loop"}
+            }
+        }))
+        .unwrap();
+        let renderer = module.get_renderer().unwrap();
+        let contents = module.contents.as_ref().unwrap();
+
+        assert_eq!(
+            renderer.render(&contents["fire"], "md").unwrap(),
+            "*Fireball* *Frost* Fireball"
+        );
+        assert!(renderer.render(&contents["missing"], "md").is_err());
+        assert!(renderer.render(&contents["loop"], "md").is_err());
+    }
+
+    #[test]
+    fn markdown_properties_are_converted_when_enabled() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {
+                "description": "a spell",
+                "schema": {"properties": {
+                    "effect": {"type": "string", "contentMediaType": "text/markdown"}
+                }},
+                "rendering": {"html": "{{{self.effect}}}", "txt": "{{self.effect}}"}
+            }},
+            "contents": {"fireball": {"type": "spell", "effect": "A *hot* spell"}}
+        }))
+        .unwrap();
+        let fireball = &module.contents.as_ref().unwrap()["fireball"];
+
+        let renderer = module.get_renderer().unwrap();
+        assert_eq!(renderer.render(fireball, "html").unwrap(), "A *hot* spell");
+
+        let renderer = renderer.with_markdown_conversion(MarkdownOptions::default());
+        assert_eq!(
+            renderer.render(fireball, "html").unwrap(),
+            "<p>A <em>hot</em> spell</p>\n"
+        );
+        assert_eq!(renderer.render(fireball, "txt").unwrap(), "A hot spell");
+    }
+
+    #[test]
+    fn converted_markdown_is_escaped_with_double_stashes() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {
+                "description": "a spell",
+                "schema": {"properties": {
+                    "effect": {"type": "string", "contentMediaType": "text/markdown"}
+                }},
+                "rendering": {"html": "{{{self.effect}}}{{self.effect}}"}
+            }},
+            "contents": {"fireball": {"type": "spell", "effect": "A *hot* spell"}}
+        }))
+        .unwrap();
+        let renderer = module
+            .get_renderer()
+            .unwrap()
+            .with_markdown_conversion(MarkdownOptions::default());
+
+        assert_eq!(
+            renderer
+                .render(&module.contents.as_ref().unwrap()["fireball"], "html")
+                .unwrap(),
+            "<p>A <em>hot</em> spell</p>\n&lt;p&gt;A &lt;em&gt;hot&lt;/em&gt; spell&lt;/p&gt;\n"
+        );
+    }
+
+    #[test]
+    fn values_are_escaped_by_format_and_type() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "spell": {"description": "a spell", "rendering": {
+                    "md": "{{self.name}}", "html": "{{self.name}}", "txt": "{{self.name}}",
+                    "tex": "{{self.name}} {{{self.name}}}"
+                }},
+                "raw": {"description": "raw text", "rendering": {"html": "{{self.name}}"}, "escaping": {"html": "none"}},
+                "note": {"description": "a note", "rendering": {"md": "{{self.name}}"}, "escaping": {"md": "markdown"}}
+            },
+            "contents": {
+                "fireball": {"type": "spell", "name": "Fire & *ice*"},
+                "markup": {"type": "raw", "name": "<b>bold</b>"},
+                "aside": {"type": "note", "name": "Fire & *ice*"}
+            }
+        }))
+        .unwrap();
+        let contents = module.contents.as_ref().unwrap();
+        let renderer = module.get_renderer().unwrap();
+
+        assert_eq!(
+            renderer.render(&contents["fireball"], "md").unwrap(),
+            "Fire & *ice*"
+        );
+        assert_eq!(
+            renderer.render(&contents["aside"], "md").unwrap(),
+            "Fire & \\*ice\\*"
+        );
+        assert_eq!(
+            renderer.render(&contents["fireball"], "html").unwrap(),
+            "Fire &amp; *ice*"
+        );
+        assert_eq!(
+            renderer.render(&contents["fireball"], "txt").unwrap(),
+            "Fire & *ice*"
+        );
+        assert_eq!(
+            renderer.render(&contents["fireball"], "tex").unwrap(),
+            "Fire \\& *ice* Fire & *ice*"
+        );
+        assert_eq!(
+            renderer.render(&contents["markup"], "html").unwrap(),
+            "<b>bold</b>"
+        );
+
+        let renderer = renderer.with_escaping(Escaping::Html);
+        assert_eq!(
+            renderer.render(&contents["fireball"], "md").unwrap(),
+            "Fire &amp; *ice*"
+        );
+    }
+
+    #[test]
+    fn nested_contents_are_prepared_and_escaped_by_their_type() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {
+                "spell": {
+                    "description": "a spell",
+                    "schema": {"properties": {
+                        "effect": {"type": "string", "contentMediaType": "text/markdown"}
+                    }},
+                    "rendering": {"html": "{{self.name}}: {{{self.effect}}}"},
+                    "escaping": {"html": "latex"}
+                },
+                "list": {"description": "a list", "rendering": {
+                    "html": "{{self.name}} {{renderContent \"fireball\"}} {{self.name}}"
+                }}
+            },
+            "contents": {
+                "fireball": {"type": "spell", "name": "Fire & ice", "effect": "A *hot* spell"},
+                "all": {"type": "list", "name": "<All>"}
+            }
+        }))
+        .unwrap();
+        let renderer = module
+            .get_renderer()
+            .unwrap()
+            .with_markdown_conversion(MarkdownOptions::default());
+        let all = &module.contents.as_ref().unwrap()["all"];
+
+        let expected = "&lt;All&gt; Fire \\& ice: <p>A <em>hot</em> spell</p>\n &lt;All&gt;";
+        assert_eq!(renderer.render(all, "html").unwrap(), expected);
+        std::thread::scope(|scope| {
+            let renders: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| renderer.render(all, "html").unwrap()))
+                .collect();
+            for render in renders {
+                assert_eq!(render.join().unwrap(), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn invalid_type_escaping_fails() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {"description": "a spell", "escaping": {"md": "yaml"}}}
+        }))
+        .unwrap();
+
+        assert!(module.get_renderer().is_err());
+    }
+
+    #[test]
+    fn standalone_templates_are_not_escaped() {
+        assert_eq!(
+            render_standalone("{{type}}/{{id}}.md", &json!({"type": "spell", "id": "a&b"}))
+                .unwrap(),
+            "spell/a&b.md"
+        );
+        assert!(render_standalone("{{missing}}.md", &json!({})).is_err());
+    }
+
+    #[test]
+    fn contents_are_rendered_by_identifier() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "types": {"spell": {"description": "a spell", "rendering": {"md": "*{{self.name}}*"}}},
+            "contents": {
+                "fireball": {"type": "spell", "name": "Fireball"},
+                "frost": {"type": "spell", "name": "Frost"},
+                "spark": {"type": "spell", "name": "Spark"}
+            }
+        }))
+        .unwrap();
+        let renderer = module.get_renderer().unwrap();
+
+        assert_eq!(
+            renderer
+                .render_ids(&["spark".to_string(), "f*".to_string()], "md")
+                .unwrap()
+                .into_iter()
+                .map(|c| c.rendered)
+                .collect::<Vec<_>>(),
+            vec!["*Fireball*", "*Frost*", "*Spark*"]
+        );
+        assert!(matches!(
+            renderer.render_ids(&["ice*".to_string()], "md"),
+            Err(RenderingError::UnknownContent(id)) if id == "ice*"
+        ));
+    }
+
+    #[test]
+    fn group_headers_receive_group_key_and_count() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "rendering": {
+                "@group/md": "## {{group.key}} ({{group.count}})"
+            },
+            "types": {"spell": {"description": "a spell"}}
+        }))
+        .unwrap();
+        let renderer = module.get_renderer().unwrap();
+
+        let mut result = Vec::new();
+        renderer
+            .render_group_header_to_write(&Some(json!("fire")), 2, "md", &mut result)
+            .unwrap();
+
+        assert!(renderer.has_group_header("md"));
+        assert!(!renderer.has_layout("md"));
+        assert!(!renderer.has_layout("@group/md"));
+        assert_eq!(String::from_utf8(result).unwrap(), "## fire (2)");
+    }
+
+    #[test]
+    fn indexes_are_not_layouts() {
+        let module: Module = serde_json::from_value(json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "rendering": {"@index/md": "{{#each files}}{{this}} {{/each}}"},
+            "types": {"spell": {"description": "a spell"}}
+        }))
+        .unwrap();
+        let renderer = module.get_renderer().unwrap();
+
+        let mut result = Vec::new();
+        renderer
+            .render_module_template_to_write(
+                &index_key("md"),
+                "md",
+                BTreeMap::from([("files".to_string(), json!(["a.md", "b.md"]))]),
+                &mut result,
+            )
+            .unwrap();
+
+        assert!(renderer.has_module_template(&index_key("md")));
+        assert!(!renderer.has_layout("@index/md"));
+        assert_eq!(String::from_utf8(result).unwrap(), "a.md b.md ");
+    }
+}
@@ -0,0 +1,147 @@
+use handlebars::{handlebars_helper, RenderError};
+use serde_json::{json, Value};
+
+use crate::selection::{compare_values, ContentFilter, GroupBy, SortKey};
+use crate::TYPE_KEY;
+
+/// Parses the argument of a helper the same way as the selection of contents.
+fn parse<T: std::str::FromStr<Err = crate::RenderingError>>(value: &str) -> Result<T, RenderError> {
+    value
+        .parse()
+        .map_err(|e: crate::RenderingError| RenderError::new(e.to_string()))
+}
+
+handlebars_helper!(sort_by: |items: array, key: str| {
+    let key: SortKey = parse(key)?;
+    let mut sorted = items.clone();
+    sorted.sort_by(|a, b| {
+        let ordering = compare_values(a.pointer(&key.pointer), b.pointer(&key.pointer));
+        if key.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    sorted
+});
+
+handlebars_helper!(group_by: |items: array, key: str| {
+    let pointer = match parse(key)? {
+        GroupBy::Type => format!("/{}", TYPE_KEY),
+        GroupBy::Field(pointer) => pointer,
+    };
+    let mut groups: Vec<(Value, Vec<Value>)> = Vec::new();
+    for item in items {
+        let key = item.pointer(&pointer).cloned().unwrap_or(Value::Null);
+        match groups.iter_mut().find(|(k, _)| k == &key) {
+            Some((_, group)) => group.push(item.clone()),
+            None => groups.push((key, vec![item.clone()])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(key, items)| json!({"key": key, "items": items}))
+        .collect::<Vec<Value>>()
+});
+
+handlebars_helper!(filter: |items: array, condition: str| {
+    let content_filter: ContentFilter = parse(condition)?;
+    items
+        .iter()
+        .filter(|item| content_filter.matches(item))
+        .cloned()
+        .collect::<Vec<Value>>()
+});
+
+handlebars_helper!(first: |items: array| items.first().cloned().unwrap_or(Value::Null));
+
+handlebars_helper!(last: |items: array| items.last().cloned().unwrap_or(Value::Null));
+
+handlebars_helper!(length: |value: Json| {
+    match value {
+        Value::Array(items) => items.len(),
+        Value::Object(properties) => properties.len(),
+        Value::String(text) => text.chars().count(),
+        _ => 0,
+    }
+});
+
+#[cfg(test)]
+mod tests {
+
+    use crate::renderer::helpers::tests::render_with_helpers;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    fn spells() -> Value {
+        json!({"spells": [
+            {"name": "Fireball", "school": "fire", "cost": 3},
+            {"name": "Frost", "school": "ice", "cost": 1},
+            {"name": "Spark", "school": "fire", "cost": 2},
+        ]})
+    }
+
+    #[test]
+    fn sorts_items_by_property() {
+        assert_eq!(
+            render_with_helpers(
+                "{{#each (sortBy spells \"/cost\")}}{{name}} {{/each}}",
+                &spells()
+            ),
+            "Frost Spark Fireball "
+        );
+        assert_eq!(
+            render_with_helpers(
+                "{{#each (sortBy spells \"/name:desc\")}}{{name}} {{/each}}",
+                &spells()
+            ),
+            "Spark Frost Fireball "
+        );
+    }
+
+    #[test]
+    fn groups_items_by_property() {
+        assert_eq!(
+            render_with_helpers(
+                "{{#each (groupBy spells \"/school\")}}{{key}}: {{#each items}}{{name}} {{/each}}| {{/each}}",
+                &spells()
+            ),
+            "fire: Fireball Spark | ice: Frost | "
+        );
+    }
+
+    #[test]
+    fn filters_items() {
+        assert_eq!(
+            render_with_helpers(
+                "{{#each (filter spells \"/cost>=2\")}}{{name}} {{/each}}",
+                &spells()
+            ),
+            "Fireball Spark "
+        );
+    }
+
+    #[test]
+    fn finds_first_last_and_length() {
+        assert_eq!(
+            render_with_helpers(
+                "{{#with (first spells)}}{{name}}{{/with}} {{#with (last spells)}}{{name}}{{/with}} {{length spells}} {{length \"dé\"}}",
+                &spells()
+            ),
+            "Fireball Spark 3 2"
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_fail() {
+        let handlebars = {
+            let mut handlebars = handlebars::Handlebars::new();
+            crate::renderer::helpers::register_helpers(&mut handlebars);
+            handlebars
+        };
+
+        assert!(handlebars
+            .render_template("{{sortBy spells \"cost\"}}", &spells())
+            .is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use dice::{expression::DiceExpression, probability::format_chance as write_chance};
+use handlebars::{handlebars_helper, RenderError};
+use serde_json::{json, Value};
+
+/// Reads the dice expression of a helper.
+fn parse(expression: &str) -> Result<DiceExpression, RenderError> {
+    expression
+        .parse()
+        .map_err(|e: dice::DiceError| RenderError::new(e.to_string()))
+}
+
+handlebars_helper!(dice_normalize: |expression: str| parse(expression)?.to_string());
+
+handlebars_helper!(dice_pretty: |expression: str| parse(expression)?.pretty());
+
+handlebars_helper!(dice_min: |expression: str| parse(expression)?.minimum());
+
+handlebars_helper!(dice_max: |expression: str| parse(expression)?.maximum());
+
+handlebars_helper!(dice_average: |expression: str| parse(expression)?.average());
+
+handlebars_helper!(dice_chance: |expression: str, target: i64| parse(expression)?.chance_at_least(target));
+
+handlebars_helper!(dice_chance_at_most: |expression: str, target: i64| parse(expression)?.chance_at_most(target));
+
+handlebars_helper!(dice_table: |expression: str| {
+    parse(expression)?
+        .probabilities()
+        .into_iter()
+        .map(|outcome| {
+            json!({
+                "total": outcome.total,
+                "chance": outcome.chance,
+                "atLeast": outcome.at_least,
+                "atMost": outcome.at_most,
+            })
+        })
+        .collect::<Vec<Value>>()
+});
+
+handlebars_helper!(format_chance: |chance: f64, {decimals: u64 = 0}| write_chance(chance, decimals as usize));
+
+#[cfg(test)]
+mod tests {
+
+    use crate::renderer::helpers::tests::render_with_helpers;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn describes_expressions() {
+        let data = json!({"damage": "2 + d6+2D6"});
+
+        assert_eq!(
+            render_with_helpers("{{diceNormalize damage}}", &data),
+            "3d6+2"
+        );
+        assert_eq!(
+            render_with_helpers("{{dicePretty damage}}", &data),
+            "3d6 + 2"
+        );
+        assert_eq!(
+            render_with_helpers(
+                "{{diceMin damage}}-{{diceMax damage}} ({{diceAverage damage}})",
+                &data
+            ),
+            "5-20 (12.5)"
+        );
+    }
+
+    #[test]
+    fn formats_chances_of_targets() {
+        let data = json!({"roll": "3d6"});
+
+        assert_eq!(
+            render_with_helpers("{{formatChance (diceChance roll 11)}}", &data),
+            "50%"
+        );
+        assert_eq!(
+            render_with_helpers(
+                "{{formatChance (diceChanceAtMost roll 4) decimals=2}}",
+                &data
+            ),
+            "1.85%"
+        );
+    }
+
+    #[test]
+    fn lists_probabilities() {
+        assert_eq!(
+            render_with_helpers(
+                "{{#each (diceTable roll)}}{{total}}: {{formatChance atLeast}} {{/each}}",
+                &json!({"roll": "1d4"})
+            ),
+            "1: 100% 2: 75% 3: 50% 4: 25% "
+        );
+    }
+
+    #[test]
+    fn invalid_expressions_fail() {
+        let mut handlebars = handlebars::Handlebars::new();
+        crate::renderer::helpers::register_helpers(&mut handlebars);
+
+        assert!(handlebars
+            .render_template("{{diceMax roll}}", &json!({"roll": "3x6"}))
+            .is_err());
+        assert!(handlebars
+            .render_template("{{diceMax \"9223372036854775807+1\"}}", &json!({}))
+            .is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use handlebars::handlebars_helper;
+use serde_json::Value;
+
+use crate::selection::{comparable, compare_values};
+
+/// Compares two values of the same kind (numbers or strings), which is
+/// false for values that can not be compared.
+fn compare(a: &Value, b: &Value, expected: fn(std::cmp::Ordering) -> bool) -> bool {
+    comparable(a, b) && expected(compare_values(Some(a), Some(b)))
+}
+
+handlebars_helper!(gt: |a: Json, b: Json| compare(a, b, std::cmp::Ordering::is_gt));
+
+handlebars_helper!(gte: |a: Json, b: Json| compare(a, b, std::cmp::Ordering::is_ge));
+
+handlebars_helper!(lt: |a: Json, b: Json| compare(a, b, std::cmp::Ordering::is_lt));
+
+handlebars_helper!(lte: |a: Json, b: Json| compare(a, b, std::cmp::Ordering::is_le));
+
+#[cfg(test)]
+mod tests {
+
+    use crate::renderer::helpers::tests::render_with_helpers;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn compares_numbers_and_strings() {
+        let data = json!({"cost": 2.5, "name": "b"});
+
+        assert_eq!(render_with_helpers("{{gt cost 2}}", &data), "true");
+        assert_eq!(render_with_helpers("{{gte cost 2.5}}", &data), "true");
+        assert_eq!(render_with_helpers("{{lt cost 2}}", &data), "false");
+        assert_eq!(render_with_helpers("{{lte name \"c\"}}", &data), "true");
+        assert_eq!(render_with_helpers("{{gt name 1}}", &data), "false");
+    }
+
+    #[test]
+    fn combines_conditions() {
+        let data = json!({"cost": 3, "school": "fire"});
+
+        assert_eq!(
+            render_with_helpers(
+                "{{#if (and (eq school \"fire\") (not (lt cost 2)))}}yes{{else}}no{{/if}}",
+                &data
+            ),
+            "yes"
+        );
+        assert_eq!(
+            render_with_helpers(
+                "{{#if (or (ne school \"fire\") (gt cost 5))}}yes{{else}}no{{/if}}",
+                &data
+            ),
+            "no"
+        );
+    }
+}
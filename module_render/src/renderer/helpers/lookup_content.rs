@@ -0,0 +1,61 @@
+use handlebars::{Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, ScopedJson};
+use serde_json::Value;
+
+/// Finds a content of the module being rendered by it's identifier.
+pub(crate) fn content_from_context<'rc>(
+    ctx: &'rc Context,
+    id: &str,
+) -> Result<&'rc Value, RenderError> {
+    ctx.data()
+        .get("module")
+        .ok_or_else(|| RenderError::new("could not find the module being rendered"))?
+        .get("contents")
+        .and_then(|contents| contents.get(id))
+        .ok_or_else(|| RenderError::new(format!("the content `{}` does not exist", id)))
+}
+
+/// Finds a content of the module by it's identifier, like
+/// `{{#with (lookupContent "fireball")}}{{name}}{{/with}}`.
+pub(crate) struct LookupContent;
+
+impl HelperDef for LookupContent {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let id = h.param(0).and_then(|p| p.value().as_str()).ok_or_else(|| {
+            RenderError::new("`lookupContent` expects the identifier of a content")
+        })?;
+        Ok(ScopedJson::Derived(content_from_context(ctx, id)?.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::renderer::helpers::tests::render_with_helpers;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn finds_contents_of_the_module() {
+        let data = json!({
+            "self": {"spells": ["frost", "fireball"]},
+            "module": {"contents": {
+                "fireball": {"type": "spell", "name": "Fireball"},
+                "frost": {"type": "spell", "name": "Frost"}
+            }}
+        });
+
+        assert_eq!(
+            render_with_helpers(
+                "{{#each self.spells}}{{#with (lookupContent this)}}{{name}} {{/with}}{{/each}}",
+                &data
+            ),
+            "Frost Fireball "
+        );
+    }
+}
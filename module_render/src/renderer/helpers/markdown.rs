@@ -0,0 +1,81 @@
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use serde_json::Value;
+
+use crate::markdown::{convert, MarkdownOptions};
+
+/// Converts Markdown for the format that is being rendered, like
+/// `{{markdown self.effect headingOffset=1}}`, which is written without escaping.
+///
+/// It becomes sanitized HTML for HTML, stays unchanged for Markdown, and
+/// becomes plain text for other formats. The format can also be provided
+/// with `format="html"`, and footnotes and tables can be disabled with
+/// `footnotes=false` and `tables=false`.
+pub(crate) struct Markdown;
+
+impl HelperDef for Markdown {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let markdown = match h.param(0).map(|p| p.value()) {
+            Some(Value::String(markdown)) => markdown,
+            Some(Value::Null) | None => return Ok(()),
+            Some(other) => {
+                return Err(RenderError::new(format!(
+                    "`markdown` expects text, but got {}",
+                    other
+                )))
+            }
+        };
+        let format = h
+            .hash_get("format")
+            .and_then(|f| f.value().as_str())
+            .or_else(|| ctx.data().get("format").and_then(Value::as_str))
+            .ok_or_else(|| RenderError::new("`markdown` could not find the format to render"))?;
+        let defaults = MarkdownOptions::default();
+        let flag = |name: &str, default: bool| {
+            h.hash_get(name)
+                .and_then(|v| v.value().as_bool())
+                .unwrap_or(default)
+        };
+        let options = MarkdownOptions {
+            heading_offset: h
+                .hash_get("headingOffset")
+                .and_then(|v| v.value().as_u64())
+                .map_or(defaults.heading_offset, |offset| offset.min(5) as u8),
+            footnotes: flag("footnotes", defaults.footnotes),
+            tables: flag("tables", defaults.tables),
+        };
+        out.write(&convert(markdown, format, &options))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::renderer::helpers::tests::render_with_helpers;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn converts_markdown_for_the_format() {
+        let data = json!({"format": "html", "effect": "# Burn\nA *hot* spell"});
+
+        assert_eq!(
+            render_with_helpers("{{markdown effect headingOffset=1}}", &data),
+            "<h2>Burn</h2>\n<p>A <em>hot</em> spell</p>\n"
+        );
+        assert_eq!(
+            render_with_helpers("{{markdown effect format=\"txt\"}}", &data),
+            "Burn\nA hot spell"
+        );
+        assert_eq!(render_with_helpers("{{markdown missing}}", &data), "");
+    }
+}
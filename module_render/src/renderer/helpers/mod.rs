@@ -1,3 +1,118 @@
-// TODO: Implement tests for helpers
-// TODO: Populate handlebars helpers in this file instead
+//! The helpers available to every template, besides the
+//! [built-in helpers](https://docs.rs/handlebars/latest/handlebars/#built-in-helpers)
+//! (`if`, `each`, `with`, `lookup`, `eq`, `ne`, `and`, `or`, `not`, ...).
+//!
+//! | Helper | Example | Result |
+//! |---|---|---|
+//! | `upper` | `{{upper "Fire"}}` | `FIRE` |
+//! | `lower` | `{{lower "Fire"}}` | `fire` |
+//! | `titleCase` | `{{titleCase "magic missile"}}` | `Magic Missile` |
+//! | `slugify` | `{{slugify "Fire & Ice"}}` | `fire-ice` |
+//! | `truncate` | `{{truncate "A ball of fire" 6 ellipsis="..."}}` | `A ball...` |
+//! | `join` | `{{join self.tags ", "}}` | `fire, area` |
+//! | `pluralize` | `{{pluralize 2 "die" plural="dice"}}` | `dice` |
+//! | `splitLines` | `{{#each (splitLines self.text)}}...{{/each}}` | the lines of the text |
+//! | `markdown` | `{{markdown self.effect headingOffset=1 footnotes=true tables=true}}` | HTML, Markdown or text, by the format |
+//! | `formatNumber` | `{{formatNumber 1234.5 decimals=2 thousands="," point="."}}` | `1,234.50` |
+//! | `gt`, `gte`, `lt`, `lte` | `{{#if (gte self.cost 2)}}...{{/if}}` | compares numbers or strings |
+//! | `sortBy` | `{{#each (sortBy items "/cost:desc")}}...{{/each}}` | the items, sorted |
+//! | `groupBy` | `{{#each (groupBy items "/school")}}{{key}}: {{items}}{{/each}}` | the items, grouped |
+//! | `filter` | `{{#each (filter items "/cost>=2")}}...{{/each}}` | the matching items |
+//! | `first`, `last` | `{{#with (first items)}}...{{/with}}` | an item, or `null` |
+//! | `length` | `{{length items}}` | the number of items, properties or characters |
+//! | `diceNormalize` | `{{diceNormalize "2 + d6+2d6"}}` | `3d6+2` |
+//! | `dicePretty` | `{{dicePretty "2 + d6+2d6"}}` | `3d6 + 2` |
+//! | `diceMin`, `diceMax`, `diceAverage` | `{{diceAverage "3d6+2"}}` | `12.5` |
+//! | `diceChance`, `diceChanceAtMost` | `{{diceChance "3d6" 11}}` | `0.5`, the chance of rolling the target or more (or less) |
+//! | `diceTable` | `{{#each (diceTable "2d6")}}{{total}}: {{chance}} {{atLeast}} {{atMost}}{{/each}}` | the chance of every result |
+//! | `formatChance` | `{{formatChance (diceChance "3d6" 11) decimals=1}}` | `50.0%` |
+//! | `lookupContent` | `{{#with (lookupContent "fireball")}}...{{/with}}` | a content of the module |
+//! | `renderContent` | `{{renderContent "fireball"}}` | a content, rendered with it's own template |
+//!
+//! Properties are identified by JSON pointers, and the arguments of `sortBy`,
+//! `groupBy` and `filter` are written like the options of the selection of
+//! contents (see [selection](crate::selection)).
+use handlebars::{Handlebars, HelperDef};
+
+pub(crate) mod collections;
+pub(crate) mod dice;
+pub(crate) mod logic;
+pub(crate) mod lookup_content;
+pub(crate) mod markdown;
+pub(crate) mod numbers;
+pub(crate) mod render_content;
 pub(crate) mod split_lines;
+pub(crate) mod strings;
+
+/// The helpers, by the name templates use them with.
+pub(crate) fn helpers() -> Vec<(&'static str, Box<dyn HelperDef + Send + Sync>)> {
+    vec![
+        ("upper", Box::new(strings::upper)),
+        ("lower", Box::new(strings::lower)),
+        ("titleCase", Box::new(strings::title_case)),
+        ("slugify", Box::new(strings::slugify)),
+        ("truncate", Box::new(strings::truncate)),
+        ("join", Box::new(strings::join)),
+        ("pluralize", Box::new(strings::pluralize)),
+        ("splitLines", Box::new(split_lines::split_lines)),
+        ("markdown", Box::new(markdown::Markdown)),
+        ("formatNumber", Box::new(numbers::format_number)),
+        ("gt", Box::new(logic::gt)),
+        ("gte", Box::new(logic::gte)),
+        ("lt", Box::new(logic::lt)),
+        ("lte", Box::new(logic::lte)),
+        ("sortBy", Box::new(collections::sort_by)),
+        ("groupBy", Box::new(collections::group_by)),
+        ("filter", Box::new(collections::filter)),
+        ("first", Box::new(collections::first)),
+        ("last", Box::new(collections::last)),
+        ("length", Box::new(collections::length)),
+        ("diceNormalize", Box::new(dice::dice_normalize)),
+        ("dicePretty", Box::new(dice::dice_pretty)),
+        ("diceMin", Box::new(dice::dice_min)),
+        ("diceMax", Box::new(dice::dice_max)),
+        ("diceAverage", Box::new(dice::dice_average)),
+        ("diceChance", Box::new(dice::dice_chance)),
+        ("diceChanceAtMost", Box::new(dice::dice_chance_at_most)),
+        ("diceTable", Box::new(dice::dice_table)),
+        ("formatChance", Box::new(dice::format_chance)),
+        ("lookupContent", Box::new(lookup_content::LookupContent)),
+        (
+            "renderContent",
+            Box::<render_content::RenderContent>::default(),
+        ),
+    ]
+}
+
+/// Registers all the helpers, replacing built-in helpers with the same name.
+pub(crate) fn register_helpers(handlebars: &mut Handlebars) {
+    for (name, helper) in helpers() {
+        handlebars.register_helper(name, helper);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    /// Renders a template with all the helpers registered.
+    pub(crate) fn render_with_helpers(template: &str, data: &Value) -> String {
+        let mut handlebars = Handlebars::new();
+        register_helpers(&mut handlebars);
+        handlebars.render_template(template, data).unwrap()
+    }
+
+    #[test]
+    fn splits_lines() {
+        assert_eq!(
+            render_with_helpers(
+                "{{#each (splitLines text)}}<{{this}}>{{/each}}",
+                &json!({"text": "a\nb"})
+            ),
+            "<a><b>"
+        );
+    }
+}
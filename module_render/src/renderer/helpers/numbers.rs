@@ -0,0 +1,58 @@
+use handlebars::handlebars_helper;
+
+handlebars_helper!(format_number: |number: f64, {decimals: u64 = 0, thousands: str = ",", point: str = "."}| {
+    let formatted = format!("{:.*}", decimals as usize, number);
+    let (sign, digits) = match formatted.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", formatted.as_str()),
+    };
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits, None),
+    };
+    let mut grouped = String::new();
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push_str(thousands);
+        }
+        grouped.push(digit);
+    }
+    match fraction {
+        Some(fraction) => format!("{}{}{}{}", sign, grouped, point, fraction),
+        None => format!("{}{}", sign, grouped),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+
+    use crate::renderer::helpers::tests::render_with_helpers;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn formats_numbers() {
+        let data = json!({"gold": 1234567.891, "debt": -1500});
+
+        assert_eq!(
+            render_with_helpers("{{formatNumber gold}}", &data),
+            "1,234,568"
+        );
+        assert_eq!(
+            render_with_helpers("{{formatNumber gold decimals=2}}", &data),
+            "1,234,567.89"
+        );
+        assert_eq!(
+            render_with_helpers(
+                "{{formatNumber gold decimals=1 thousands=\".\" point=\",\"}}",
+                &data
+            ),
+            "1.234.567,9"
+        );
+        assert_eq!(
+            render_with_helpers("{{formatNumber debt}}", &data),
+            "-1,500"
+        );
+        assert_eq!(render_with_helpers("{{formatNumber 999}}", &data), "999");
+    }
+}
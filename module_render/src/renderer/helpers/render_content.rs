@@ -0,0 +1,91 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use serde_json::{json, Value};
+
+use super::lookup_content::content_from_context;
+use crate::{
+    escaping::EscapingScope,
+    renderer::{content_template, RenderSettings},
+    TYPE_KEY,
+};
+
+/// How deep contents can be rendered inside each other, which stops
+/// contents that (indirectly) render themselves.
+const MAX_DEPTH: usize = 16;
+
+/// Renders another content of the module with the template of it's own type,
+/// like `{{renderContent "fireball"}}`.
+///
+/// The content is rendered with the format that is being rendered, unless
+/// another one is provided, like `{{renderContent "fireball" "txt"}}`.
+///
+/// The content is prepared and escaped as it's own type requires, with the
+/// settings of the renderer.
+#[derive(Default)]
+pub(crate) struct RenderContent {
+    depth: AtomicUsize,
+    settings: Arc<RenderSettings>,
+}
+
+impl RenderContent {
+    pub(crate) fn new(settings: Arc<RenderSettings>) -> Self {
+        RenderContent {
+            depth: AtomicUsize::new(0),
+            settings,
+        }
+    }
+}
+
+impl HelperDef for RenderContent {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let id = h.param(0).and_then(|p| p.value().as_str()).ok_or_else(|| {
+            RenderError::new("`renderContent` expects the identifier of a content")
+        })?;
+        let data = ctx.data();
+        let format = match h.param(1) {
+            Some(format) => format.value().as_str(),
+            None => data.get("format").and_then(Value::as_str),
+        }
+        .ok_or_else(|| RenderError::new("`renderContent` could not find the format to render"))?;
+        let content = content_from_context(ctx, id)?;
+        let type_key = content
+            .get(TYPE_KEY)
+            .and_then(Value::as_str)
+            .ok_or_else(|| RenderError::new(format!("the content `{}` has no type", id)))?;
+
+        if self.depth.fetch_add(1, Ordering::SeqCst) >= MAX_DEPTH {
+            self.depth.fetch_sub(1, Ordering::SeqCst);
+            return Err(RenderError::new(format!(
+                "contents are nested more than {} times when rendering `{}`",
+                MAX_DEPTH, id
+            )));
+        }
+        let template = content_template(type_key, format);
+        let mut self_data = content.clone();
+        self.settings
+            .convert_markdown(type_key, &mut self_data, format);
+        let rendered = {
+            let _escaping = EscapingScope::enter(self.settings.escaping(&template, format));
+            r.render(
+                &template,
+                &json!({"self": self_data, "module": data["module"], "format": format}),
+            )
+        };
+        self.depth.fetch_sub(1, Ordering::SeqCst);
+        out.write(&rendered?)?;
+        Ok(())
+    }
+}
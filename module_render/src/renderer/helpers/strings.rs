@@ -0,0 +1,130 @@
+use handlebars::handlebars_helper;
+use serde_json::Value;
+
+handlebars_helper!(upper: |text: str| text.to_uppercase());
+
+handlebars_helper!(lower: |text: str| text.to_lowercase());
+
+handlebars_helper!(title_case: |text: str| {
+    text.split(' ')
+        .map(|word| {
+            let mut characters = word.chars();
+            match characters.next() {
+                Some(first) => first.to_uppercase().chain(characters.flat_map(char::to_lowercase)).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+});
+
+handlebars_helper!(slugify: |text: str| {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+});
+
+handlebars_helper!(truncate: |text: str, length: u64, {ellipsis: str = "…"}| {
+    if text.chars().count() as u64 > length {
+        let truncated: String = text.chars().take(length as usize).collect();
+        format!("{}{}", truncated.trim_end(), ellipsis)
+    } else {
+        text.to_string()
+    }
+});
+
+handlebars_helper!(join: |items: array, separator: str| {
+    items
+        .iter()
+        .map(|item| match item {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(separator)
+});
+
+handlebars_helper!(pluralize: |count: f64, singular: str, {plural: str = ""}| {
+    if count == 1.0 {
+        singular.to_string()
+    } else if plural.is_empty() {
+        format!("{}s", singular)
+    } else {
+        plural.to_string()
+    }
+});
+
+#[cfg(test)]
+mod tests {
+
+    use crate::renderer::helpers::tests::render_with_helpers;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn changes_case() {
+        let data = json!({"name": "mAgIc missile"});
+
+        assert_eq!(
+            render_with_helpers("{{upper name}}", &data),
+            "MAGIC MISSILE"
+        );
+        assert_eq!(
+            render_with_helpers("{{lower name}}", &data),
+            "magic missile"
+        );
+        assert_eq!(
+            render_with_helpers("{{titleCase name}}", &data),
+            "Magic Missile"
+        );
+    }
+
+    #[test]
+    fn slugifies_text() {
+        assert_eq!(
+            render_with_helpers("{{slugify name}}", &json!({"name": " Fire & Ice: Bolt! "})),
+            "fire-ice-bolt"
+        );
+    }
+
+    #[test]
+    fn truncates_long_text() {
+        let data = json!({"text": "A ball of fire"});
+
+        assert_eq!(render_with_helpers("{{truncate text 7}}", &data), "A ball…");
+        assert_eq!(
+            render_with_helpers("{{truncate text 7 ellipsis=\"...\"}}", &data),
+            "A ball..."
+        );
+        assert_eq!(
+            render_with_helpers("{{truncate text 20}}", &data),
+            "A ball of fire"
+        );
+    }
+
+    #[test]
+    fn joins_items() {
+        assert_eq!(
+            render_with_helpers("{{join tags \", \"}}", &json!({"tags": ["fire", 3, true]})),
+            "fire, 3, true"
+        );
+    }
+
+    #[test]
+    fn pluralizes_by_count() {
+        assert_eq!(
+            render_with_helpers("{{pluralize 1 \"die\" plural=\"dice\"}}", &json!({})),
+            "die"
+        );
+        assert_eq!(
+            render_with_helpers("{{pluralize 2 \"die\" plural=\"dice\"}}", &json!({})),
+            "dice"
+        );
+        assert_eq!(
+            render_with_helpers("{{pluralize 0 \"spell\"}}", &json!({})),
+            "spells"
+        );
+    }
+}
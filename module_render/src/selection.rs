@@ -0,0 +1,461 @@
+use std::{cmp::Ordering, collections::BTreeMap, str::FromStr};
+
+use serde_json::Value;
+
+use crate::{RenderingError, TYPE_KEY};
+
+/// A generic JSON Map, like the contents of a [Module](module::module::Module).
+type JsonMap = BTreeMap<String, Value>;
+
+/// A comparison between a property of a content and a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOperator {
+    /// The property is present.
+    Exists,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+/// A predicate on a property of a content, identified by a JSON pointer.
+///
+/// It is written as the pointer, followed by an operator and a value,
+/// like `/school=fire` or `/cost>=2`, or just the pointer to check if the
+/// property exists. Values are read as JSON, and as strings when they are not valid JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentFilter {
+    pub pointer: String,
+    pub operator: FilterOperator,
+    pub value: Value,
+}
+
+/// The operators of filters, in the order they are searched for.
+const OPERATORS: [(&str, FilterOperator); 6] = [
+    ("!=", FilterOperator::NotEqual),
+    (">=", FilterOperator::GreaterOrEqual),
+    ("<=", FilterOperator::LessOrEqual),
+    ("=", FilterOperator::Equal),
+    (">", FilterOperator::Greater),
+    ("<", FilterOperator::Less),
+];
+
+impl FromStr for ContentFilter {
+    type Err = RenderingError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if !value.starts_with('/') {
+            return Err(RenderingError::InvalidSelection(value.to_string()));
+        }
+        let operator = OPERATORS
+            .iter()
+            .filter_map(|(symbol, operator)| value.find(symbol).map(|i| (i, *symbol, *operator)))
+            .min_by_key(|(i, _, _)| *i);
+        Ok(match operator {
+            Some((index, symbol, operator)) => {
+                let expected = &value[index + symbol.len()..];
+                ContentFilter {
+                    pointer: value[..index].to_string(),
+                    operator,
+                    value: serde_json::from_str(expected)
+                        .unwrap_or_else(|_| Value::String(expected.to_string())),
+                }
+            }
+            None => ContentFilter {
+                pointer: value.to_string(),
+                operator: FilterOperator::Exists,
+                value: Value::Null,
+            },
+        })
+    }
+}
+
+impl ContentFilter {
+    /// Checks if a content matches the filter.
+    pub fn matches(&self, content: &Value) -> bool {
+        let Some(actual) = content.pointer(&self.pointer) else {
+            return self.operator == FilterOperator::NotEqual;
+        };
+        let ordering = compare_values(Some(actual), Some(&self.value));
+        match self.operator {
+            FilterOperator::Exists => true,
+            FilterOperator::Equal => actual == &self.value,
+            FilterOperator::NotEqual => actual != &self.value,
+            FilterOperator::Greater => comparable(actual, &self.value) && ordering.is_gt(),
+            FilterOperator::GreaterOrEqual => comparable(actual, &self.value) && ordering.is_ge(),
+            FilterOperator::Less => comparable(actual, &self.value) && ordering.is_lt(),
+            FilterOperator::LessOrEqual => comparable(actual, &self.value) && ordering.is_le(),
+        }
+    }
+}
+
+/// A property contents are sorted by, written as a JSON pointer
+/// optionally followed by `:desc`, like `/cost:desc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub pointer: String,
+    pub descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = RenderingError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (pointer, descending) = match value.rsplit_once(':') {
+            Some((pointer, "desc")) => (pointer, true),
+            Some((pointer, "asc")) => (pointer, false),
+            _ => (value, false),
+        };
+        if !pointer.starts_with('/') {
+            return Err(RenderingError::InvalidSelection(value.to_string()));
+        }
+        Ok(SortKey {
+            pointer: pointer.to_string(),
+            descending,
+        })
+    }
+}
+
+/// How contents are grouped together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupBy {
+    /// By their type.
+    Type,
+    /// By the value of a property, identified by a JSON pointer.
+    Field(String),
+}
+
+impl FromStr for GroupBy {
+    type Err = RenderingError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "type" => Ok(GroupBy::Type),
+            pointer if pointer.starts_with('/') => Ok(GroupBy::Field(pointer.to_string())),
+            _ => Err(RenderingError::InvalidSelection(value.to_string())),
+        }
+    }
+}
+
+/// Which contents are rendered, and in which order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentSelection {
+    /// The types that are rendered, in the order their groups are rendered.
+    /// All types are rendered when empty.
+    pub types: Vec<String>,
+    /// The identifiers of the rendered contents, which may use `*` and `?`
+    /// as wildcards. All contents are rendered when empty.
+    pub ids: Vec<String>,
+    /// The filters every rendered content must match.
+    pub filters: Vec<ContentFilter>,
+    /// The properties contents are sorted by, in order of importance.
+    /// Contents are sorted by their identifier otherwise.
+    pub sort_by: Vec<SortKey>,
+    /// How contents are grouped.
+    pub group_by: Option<GroupBy>,
+}
+
+/// Contents that share the same value for the property they are grouped by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentGroup<'a> {
+    /// The value the contents were grouped by, which is `None` when
+    /// contents are not grouped, or do not have the property.
+    pub key: Option<Value>,
+    /// The identifiers and contents of the group.
+    pub contents: Vec<(&'a String, &'a JsonMap)>,
+}
+
+impl ContentSelection {
+    /// Filters, sorts and groups contents.
+    ///
+    /// When contents are not grouped, a single group with all of them is returned.
+    pub fn apply<'a>(&self, contents: &'a BTreeMap<String, JsonMap>) -> Vec<ContentGroup<'a>> {
+        let mut selected: Vec<(&String, &JsonMap, Value)> = contents
+            .iter()
+            .map(|(id, content)| {
+                let value = serde_json::to_value(content)
+                    .expect("Content should always be a valid JSON Value");
+                (id, content, value)
+            })
+            .filter(|(_, content, _)| {
+                self.types.is_empty()
+                    || content
+                        .get(TYPE_KEY)
+                        .and_then(Value::as_str)
+                        .is_some_and(|t| self.types.iter().any(|included| included == t))
+            })
+            .filter(|(id, _, _)| self.ids.is_empty() || self.ids.iter().any(|p| matches_id(p, id)))
+            .filter(|(_, _, value)| self.filters.iter().all(|f| f.matches(value)))
+            .collect();
+        selected.sort_by(|(_, _, a), (_, _, b)| {
+            self.sort_by
+                .iter()
+                .map(|key| {
+                    let ordering = compare_values(a.pointer(&key.pointer), b.pointer(&key.pointer));
+                    if key.descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                })
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let pointer = match &self.group_by {
+            None => {
+                return vec![ContentGroup {
+                    key: None,
+                    contents: selected.into_iter().map(|(id, c, _)| (id, c)).collect(),
+                }]
+            }
+            Some(GroupBy::Type) => format!("/{}", TYPE_KEY),
+            Some(GroupBy::Field(pointer)) => pointer.clone(),
+        };
+        let mut groups: Vec<ContentGroup> = Vec::new();
+        for (id, content, value) in selected {
+            let key = value.pointer(&pointer).cloned();
+            match groups.iter_mut().find(|g| g.key == key) {
+                Some(group) => group.contents.push((id, content)),
+                None => groups.push(ContentGroup {
+                    key,
+                    contents: vec![(id, content)],
+                }),
+            }
+        }
+        groups.sort_by(|a, b| match (&self.group_by, &a.key, &b.key) {
+            (Some(GroupBy::Type), Some(Value::String(a)), Some(Value::String(b)))
+                if !self.types.is_empty() =>
+            {
+                let position = |t: &String| self.types.iter().position(|i| i == t);
+                position(a).cmp(&position(b))
+            }
+            _ => compare_values(a.key.as_ref(), b.key.as_ref()),
+        });
+        groups
+    }
+
+    /// Checks that every identifier of the selection matches at least one content.
+    pub fn check_ids(&self, contents: &BTreeMap<String, JsonMap>) -> Result<(), RenderingError> {
+        match self
+            .ids
+            .iter()
+            .find(|pattern| !contents.keys().any(|id| matches_id(pattern, id)))
+        {
+            Some(pattern) => Err(RenderingError::UnknownContent(pattern.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks if an identifier matches a pattern, where `*` matches any number
+/// of characters and `?` matches a single character.
+pub fn matches_id(pattern: &str, id: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let id: Vec<char> = id.chars().collect();
+    let (mut p, mut i) = (0, 0);
+    // Where the last `*` was, and how much of the identifier it matches
+    let mut wildcard: Option<(usize, usize)> = None;
+    while i < id.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                wildcard = Some((p, i));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == id[i] => {
+                p += 1;
+                i += 1;
+            }
+            _ => match wildcard {
+                Some((star, matched)) => {
+                    wildcard = Some((star, matched + 1));
+                    p = star + 1;
+                    i = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Checks if two values can be compared by their order.
+pub(crate) fn comparable(a: &Value, b: &Value) -> bool {
+    matches!(
+        (a, b),
+        (Value::Number(_), Value::Number(_)) | (Value::String(_), Value::String(_))
+    )
+}
+
+/// Orders values, with numbers before strings, and missing values last.
+pub(crate) fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    fn rank(value: Option<&Value>) -> u8 {
+        match value {
+            Some(Value::Bool(_)) => 0,
+            Some(Value::Number(_)) => 1,
+            Some(Value::String(_)) => 2,
+            Some(Value::Array(_)) | Some(Value::Object(_)) => 3,
+            Some(Value::Null) => 4,
+            None => 5,
+        }
+    }
+    match (a, b) {
+        (Some(Value::Bool(a)), Some(Value::Bool(b))) => a.cmp(b),
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Some(Value::String(a)), Some(Value::String(b))) => a.cmp(b),
+        (Some(a @ Value::Array(_)), Some(b @ Value::Array(_)))
+        | (Some(a @ Value::Object(_)), Some(b @ Value::Object(_))) => {
+            a.to_string().cmp(&b.to_string())
+        }
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn contents() -> BTreeMap<String, JsonMap> {
+        serde_json::from_value(json!({
+            "fireball": {"type": "spell", "school": "fire", "cost": 3},
+            "frost": {"type": "spell", "school": "ice", "cost": 1},
+            "spark": {"type": "spell", "school": "fire", "cost": 2},
+            "sword": {"type": "item", "cost": 10},
+        }))
+        .unwrap()
+    }
+
+    fn ids(groups: &[ContentGroup]) -> Vec<Vec<String>> {
+        groups
+            .iter()
+            .map(|g| g.contents.iter().map(|(id, _)| id.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn filters_are_parsed() {
+        assert_eq!(
+            "/cost>=2".parse::<ContentFilter>().unwrap(),
+            ContentFilter {
+                pointer: "/cost".to_string(),
+                operator: FilterOperator::GreaterOrEqual,
+                value: json!(2)
+            }
+        );
+        assert_eq!(
+            "/school!=fire".parse::<ContentFilter>().unwrap(),
+            ContentFilter {
+                pointer: "/school".to_string(),
+                operator: FilterOperator::NotEqual,
+                value: json!("fire")
+            }
+        );
+        assert_eq!(
+            "/school".parse::<ContentFilter>().unwrap().operator,
+            FilterOperator::Exists
+        );
+        assert!("school=fire".parse::<ContentFilter>().is_err());
+    }
+
+    #[test]
+    fn without_options_all_contents_are_kept_in_order() {
+        let contents = contents();
+
+        assert_eq!(
+            ids(&ContentSelection::default().apply(&contents)),
+            vec![vec!["fireball", "frost", "spark", "sword"]]
+        );
+    }
+
+    #[test]
+    fn contents_are_filtered_and_sorted() {
+        let contents = contents();
+        let selection = ContentSelection {
+            types: vec!["spell".to_string()],
+            ids: Vec::new(),
+            filters: vec!["/cost<3".parse().unwrap()],
+            sort_by: vec!["/cost:desc".parse().unwrap()],
+            group_by: None,
+        };
+
+        assert_eq!(
+            ids(&selection.apply(&contents)),
+            vec![vec!["spark", "frost"]]
+        );
+    }
+
+    #[test]
+    fn contents_are_selected_by_identifier_patterns() {
+        let contents = contents();
+        let selection = ContentSelection {
+            ids: vec!["f*".to_string(), "spar?".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ids(&selection.apply(&contents)),
+            vec![vec!["fireball", "frost", "spark"]]
+        );
+        assert!(selection.check_ids(&contents).is_ok());
+        assert!(matches_id("*b*l", "fireball"));
+        assert!(!matches_id("fire", "fireball"));
+        assert!(matches!(
+            ContentSelection {
+                ids: vec!["sword".to_string(), "shield*".to_string()],
+                ..Default::default()
+            }
+            .check_ids(&contents),
+            Err(RenderingError::UnknownContent(id)) if id == "shield*"
+        ));
+    }
+
+    #[test]
+    fn groups_by_type_follow_the_order_of_the_types() {
+        let contents = contents();
+        let selection = ContentSelection {
+            types: vec!["spell".to_string(), "item".to_string()],
+            sort_by: vec!["/cost".parse().unwrap()],
+            group_by: Some(GroupBy::Type),
+            ..Default::default()
+        };
+
+        let groups = selection.apply(&contents);
+
+        assert_eq!(
+            groups.iter().map(|g| g.key.clone()).collect::<Vec<_>>(),
+            vec![Some(json!("spell")), Some(json!("item"))]
+        );
+        assert_eq!(
+            ids(&groups),
+            vec![vec!["frost", "spark", "fireball"], vec!["sword"]]
+        );
+    }
+
+    #[test]
+    fn groups_by_field_are_sorted_with_missing_values_last() {
+        let contents = contents();
+        let selection = ContentSelection {
+            group_by: Some("/school".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let groups = selection.apply(&contents);
+
+        assert_eq!(
+            groups.iter().map(|g| g.key.clone()).collect::<Vec<_>>(),
+            vec![Some(json!("fire")), Some(json!("ice")), None]
+        );
+        assert_eq!(
+            ids(&groups),
+            vec![vec!["fireball", "spark"], vec!["frost"], vec!["sword"]]
+        );
+    }
+}
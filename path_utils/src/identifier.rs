@@ -1,6 +1,5 @@
 use std::{
     fmt::Debug,
-    ops::Deref,
     path::{Component, Path},
 };
 
@@ -19,7 +18,7 @@ pub trait IdentifierPaths {
 impl<T: AsRef<Path> + Debug> IdentifierPaths for T {
     #[instrument]
     fn get_id_from_path(&self, base: &Path) -> Option<String> {
-        let mut path: &Path = self.deref().as_ref();
+        let mut path: &Path = self.as_ref();
 
         if path.is_named("_") {
             path = path.ancestors().nth(1).expect("Path ends with a file named `_` therefore the second ancestor should be a valid path to it's parent");
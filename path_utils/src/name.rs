@@ -1,4 +1,4 @@
-use std::{fmt::Debug, ops::Deref, path::Path};
+use std::{fmt::Debug, path::Path};
 
 use tracing::{debug, instrument};
 
@@ -8,17 +8,23 @@ pub trait NamePaths {
     fn is_named(&self, file_name: &str) -> bool;
     /// Gets the file or directory name, excluding any file extension.
     fn get_name_without_extension(&self) -> String;
+    /// Gets the name of a translated file (like `title.pt.md`) without the
+    /// extension and the locale, next to the locale it is translated into.
+    ///
+    /// Only the known locales are recognized, so names like `stats.max.md`
+    /// are not translations unless `max` is one of them.
+    fn get_localized_name(&self, known_locales: &[String]) -> Option<(String, String)>;
 }
 
 impl<T: AsRef<Path> + Debug> NamePaths for T {
     fn is_named(&self, name: &str) -> bool {
-        let path: &Path = self.deref().as_ref();
+        let path: &Path = self.as_ref();
         path.get_name_without_extension().eq(name)
     }
 
     #[instrument]
     fn get_name_without_extension(&self) -> String {
-        let path: &Path = self.deref().as_ref();
+        let path: &Path = self.as_ref();
         let name = path
             .file_name()
             .expect("Path should be a directory or a file and always have a name")
@@ -42,6 +48,13 @@ impl<T: AsRef<Path> + Debug> NamePaths for T {
             }
         }
     }
+
+    fn get_localized_name(&self, known_locales: &[String]) -> Option<(String, String)> {
+        let name = self.get_name_without_extension();
+        let (base, locale) = name.rsplit_once('.')?;
+        (!base.is_empty() && known_locales.iter().any(|known| known == locale))
+            .then(|| (base.to_string(), locale.to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -80,6 +93,36 @@ mod tests {
         logs_contain("Path did not have an extension.");
     }
 
+    #[test]
+    fn localized_file_names_are_split() {
+        let dir = testdir!();
+        let known_locales = vec!["pt-BR".to_string()];
+
+        let translated = create_test_file(&dir.join("title.pt-BR.md"), "");
+        let default = create_test_file(&dir.join("title.md"), "");
+
+        assert_eq!(
+            translated.get_localized_name(&known_locales),
+            Some(("title".to_string(), "pt-BR".to_string()))
+        );
+        assert_eq!(default.get_localized_name(&known_locales), None);
+    }
+
+    #[test]
+    fn only_known_locales_are_split_from_file_names() {
+        let dir = testdir!();
+        let known_locales = vec!["pt".to_string()];
+
+        let max = create_test_file(&dir.join("stats.max.md"), "");
+        let min = create_test_file(&dir.join("damage.min.json"), "");
+        let old = create_test_file(&dir.join("notes.old.md"), "");
+
+        assert_eq!(max.get_localized_name(&known_locales), None);
+        assert_eq!(min.get_localized_name(&known_locales), None);
+        assert_eq!(old.get_localized_name(&known_locales), None);
+        assert_eq!(max.get_name_without_extension(), "stats.max");
+    }
+
     #[test]
     fn file_names_are_correct() {
         let dir = testdir!();
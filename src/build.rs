@@ -1,9 +1,11 @@
 use clap::{Args, ValueEnum};
 use fs::file_system::FileSystem;
-use module::module::Module;
-use std::io::Write;
-use std::{convert::TryFrom, error::Error, ffi::OsString, fs::File, path::PathBuf};
-use tracing::{debug, info, instrument};
+use module::{format::ModuleFormat, integrity::Integrity, module::Module};
+use std::{convert::TryFrom, error::Error, ffi::OsString, fs, path::PathBuf};
+use tracing::{debug, info, instrument, warn};
+
+use crate::module_file::write_module;
+use crate::output::{Compression, STANDARD_OUTPUT};
 
 /// Builds a module from files in your computer.
 #[derive(Debug, Args)]
@@ -12,7 +14,7 @@ pub struct BuildArguments {
     /// The path to the directory that will be built.
     #[arg(required = true)]
     source: PathBuf,
-    /// The name of the output file, without extension.
+    /// The name of the output file, without extension, or `-` to write to the standard output.
     #[arg(short = 'o', long = "output", default_value = "module")]
     output_file_name: OsString,
     /// What type of output should be generated.
@@ -23,6 +25,36 @@ pub struct BuildArguments {
             value_enum
         )]
     output_type: OutputType,
+    /// Compresses the output, adding the extension of the compression to the output file.
+    #[arg(short = 'c', long = "compress", value_enum)]
+    compression: Option<Compression>,
+    /// Writes the hashes of the module, and each of it's types and contents,
+    /// into `{output}.integrity.json`.
+    #[arg(long = "integrity")]
+    integrity: bool,
+    #[command(flatten)]
+    options: BuildOptions,
+}
+
+/// The options that change the contents of a built module.
+#[derive(Debug, Args)]
+pub struct BuildOptions {
+    /// Fills the missing properties of contents with the defaults declared in
+    /// the schema of their types.
+    #[arg(long = "apply-defaults")]
+    apply_defaults: bool,
+    /// Replaces references to other contents with a copy of the referenced
+    /// content, so templates can use them directly.
+    ///
+    /// The resulting module is meant for rendering, and it's contents may no
+    /// longer be valid against their schemas.
+    #[arg(long = "embed-references")]
+    embed_references: bool,
+    /// The locale the module should be translated into.
+    ///
+    /// Fields without a translation use the default locale.
+    #[arg(short = 'l', long = "locale")]
+    locale: Option<String>,
 }
 
 /// The shape of the produced Module.
@@ -32,6 +64,29 @@ pub enum OutputType {
     Pretty,
     /// A condensed, and therefore better for transfers, format of JSON.
     Minimized,
+    /// A binary format, [CBOR](https://cbor.io/).
+    Cbor,
+    /// A binary format, [MessagePack](https://msgpack.org/).
+    #[value(name = "msgpack")]
+    MessagePack,
+    /// A format meant for humans, [YAML](https://yaml.org/).
+    Yaml,
+    /// A format meant for humans, [TOML](https://toml.io/),
+    /// which can not represent `null` values.
+    Toml,
+}
+
+impl OutputType {
+    /// The format the output is stored in.
+    pub fn format(&self) -> ModuleFormat {
+        match self {
+            OutputType::Pretty | OutputType::Minimized => ModuleFormat::Json,
+            OutputType::Cbor => ModuleFormat::Cbor,
+            OutputType::MessagePack => ModuleFormat::MessagePack,
+            OutputType::Yaml => ModuleFormat::Yaml,
+            OutputType::Toml => ModuleFormat::Toml,
+        }
+    }
 }
 
 /// Executes the [Build](crate::Commands::Build) command.
@@ -41,25 +96,52 @@ pub fn run(
         source,
         output_file_name,
         output_type,
+        compression,
+        integrity,
+        options,
     }: BuildArguments,
 ) -> Result<(), Box<dyn Error>> {
     info!("Starting to build the module");
+    let module = build_module(source, &options)?;
+    write_module(&module, &output_file_name, output_type, compression)?;
+    if integrity {
+        if output_file_name == STANDARD_OUTPUT {
+            warn!("The integrity file is not written when writing to the standard output");
+        } else {
+            let mut integrity_file = output_file_name.clone();
+            integrity_file.push(".integrity.json");
+            debug!("Writing integrity to {:?}", integrity_file);
+            fs::write(
+                integrity_file,
+                serde_json::to_string_pretty(&Integrity::of(&module))? + "\n",
+            )?;
+        }
+    }
+    info!("Done!");
+    Ok(())
+}
+
+/// Builds a module from a source directory.
+#[instrument]
+pub(crate) fn build_module(
+    source: PathBuf,
+    BuildOptions {
+        apply_defaults,
+        embed_references,
+        locale,
+    }: &BuildOptions,
+) -> Result<Module, Box<dyn Error>> {
     let file_system = FileSystem::try_from(source)?;
     debug!("Source directory was parsed correctly: {:#?}", file_system);
-    let module = Module::try_from(file_system)?;
+    let mut module = Module::try_from_localized(file_system, locale.as_deref())?;
     info!("Module was created from source directory: {:#?}", module);
-    let mut output_file = File::create(format!(
-        "{}.json",
-        output_file_name
-            .to_str()
-            .expect("The output file name should be a valid UTF-8 String")
-    ))?;
-    debug!("About to write module as {:?}", output_type);
-    let output_contents = match output_type {
-        OutputType::Pretty => serde_json::to_string_pretty(&module)?,
-        OutputType::Minimized => serde_json::to_string(&module)?,
-    };
-    write!(output_file, "{}", output_contents)?;
-    info!("Done!");
-    Ok(())
+    if *apply_defaults {
+        module.apply_schema_defaults();
+        debug!("Applied schema defaults to the contents");
+    }
+    if *embed_references {
+        module.embed_references();
+        debug!("Embedded referenced contents");
+    }
+    Ok(module)
 }
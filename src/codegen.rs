@@ -0,0 +1,60 @@
+use clap::{Args, ValueEnum};
+use module_codegen::{rust::generate_rust, typescript::generate_typescript};
+use std::io::Write;
+use std::{error::Error, fs::File, path::PathBuf};
+use tracing::{info, instrument};
+
+use crate::module_file::read_module;
+
+/// Generates source code for the contents of a module, based on the schemas of it's types.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct CodegenArguments {
+    /// The path to the module, either built or a source directory.
+    #[arg(required = true)]
+    source: PathBuf,
+    /// The language of the generated code.
+    #[arg(
+            short = 'l',
+            long = "lang",
+            default_value_t = Language::Rust,
+            value_enum
+        )]
+    language: Language,
+    /// The file the code is written to, instead of the standard output.
+    #[arg(short = 'o', long = "output")]
+    output_file: Option<PathBuf>,
+}
+
+/// The languages code can be generated for.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Language {
+    /// Structs and enums with serde derives.
+    Rust,
+    /// Declarations of interfaces for the module and it's contents.
+    #[value(name = "typescript")]
+    TypeScript,
+}
+
+/// Executes the [Codegen](crate::Commands::Codegen) command.
+#[instrument]
+pub fn run(
+    CodegenArguments {
+        source,
+        language,
+        output_file,
+    }: CodegenArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to generate code");
+    let module = read_module(&source)?;
+    let code = match language {
+        Language::Rust => generate_rust(&module)?,
+        Language::TypeScript => generate_typescript(&module)?,
+    };
+    match output_file {
+        Some(path) => write!(File::create(path)?, "{}", code)?,
+        None => print!("{}", code),
+    }
+    info!("Done!");
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use clap::{Args, ValueEnum};
+use module::diff::{diff, EntriesDiff, FieldChange, ModuleDiff};
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::{error::Error, fs::File, path::PathBuf};
+use tracing::{debug, info, instrument};
+
+use crate::module_file::read_module;
+
+/// Compares two versions of a module.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct DiffArguments {
+    /// The path to the old version of the module, either built or a source directory.
+    #[arg(required = true)]
+    old: PathBuf,
+    /// The path to the new version of the module, either built or a source directory.
+    #[arg(required = true)]
+    new: PathBuf,
+    /// How the differences should be reported.
+    #[arg(
+            short = 'f',
+            long = "format",
+            default_value_t = DiffFormat::Text,
+            value_enum
+        )]
+    format: DiffFormat,
+    /// The file the report is written to, instead of the standard output.
+    #[arg(short = 'o', long = "output")]
+    output_file: Option<PathBuf>,
+}
+
+/// The format of the differences report.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// A human-readable summary, for terminals.
+    Text,
+    /// A Markdown document, ready for changelogs and release notes.
+    Markdown,
+    /// A JSON document, for other tools.
+    Json,
+}
+
+/// Executes the [Diff](crate::Commands::Diff) command.
+#[instrument]
+pub fn run(
+    DiffArguments {
+        old,
+        new,
+        format,
+        output_file,
+    }: DiffArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to compare the modules");
+    let old_module = read_module(&old)?;
+    let new_module = read_module(&new)?;
+    let module_diff = diff(&old_module, &new_module);
+    debug!("Compared modules: {:#?}", module_diff);
+    let report = match format {
+        DiffFormat::Text => text_report(&module_diff),
+        DiffFormat::Markdown => markdown_report(&module_diff),
+        DiffFormat::Json => serde_json::to_string_pretty(&module_diff)? + "\n",
+    };
+    match output_file {
+        Some(path) => write!(File::create(path)?, "{}", report)?,
+        None => print!("{}", report),
+    }
+    info!("Done!");
+    Ok(())
+}
+
+/// Formats an optional value for reports.
+fn display_value(value: &Option<Value>) -> String {
+    match value {
+        Some(v) => v.to_string(),
+        None => "(none)".to_string(),
+    }
+}
+
+fn text_report(module_diff: &ModuleDiff) -> String {
+    let mut result = String::new();
+    if module_diff.is_empty() {
+        return "No changes.\n".to_string();
+    }
+    if module_diff.is_breaking() {
+        result.push_str("This version contains breaking changes.\n\n");
+    }
+    if !module_diff.metadata.is_empty() {
+        result.push_str("Module:\n");
+        text_fields(&mut result, &module_diff.metadata, "  ");
+    }
+    text_entries(&mut result, "Types", &module_diff.types);
+    text_entries(&mut result, "Contents", &module_diff.contents);
+    result
+}
+
+fn text_entries(result: &mut String, title: &str, entries: &EntriesDiff) {
+    if entries.is_empty() {
+        return;
+    }
+    let _ = writeln!(result, "{}:", title);
+    for id in &entries.added {
+        let _ = writeln!(result, "  + {}", id);
+    }
+    for id in &entries.removed {
+        let _ = writeln!(result, "  - {}", id);
+    }
+    for (id, changes) in &entries.modified {
+        let _ = writeln!(result, "  ~ {}", id);
+        text_fields(result, &changes.fields, "      ");
+        for change in &changes.schema {
+            let _ = writeln!(
+                result,
+                "      [{}] {} ({})",
+                if change.breaking {
+                    "breaking"
+                } else {
+                    "non-breaking"
+                },
+                change.reason,
+                change.pointer
+            );
+        }
+    }
+}
+
+fn text_fields(result: &mut String, fields: &[FieldChange], indentation: &str) {
+    for field in fields {
+        let _ = writeln!(
+            result,
+            "{}{}: {} -> {}",
+            indentation,
+            field.pointer,
+            display_value(&field.old),
+            display_value(&field.new)
+        );
+    }
+}
+
+fn markdown_report(module_diff: &ModuleDiff) -> String {
+    let mut result = String::new();
+    if module_diff.is_empty() {
+        return "No changes.\n".to_string();
+    }
+    if module_diff.is_breaking() {
+        result.push_str("> **Warning**\n> This version contains breaking changes.\n\n");
+    }
+    if !module_diff.metadata.is_empty() {
+        result.push_str("## Module\n\n");
+        markdown_fields(&mut result, &module_diff.metadata);
+        result.push('\n');
+    }
+    markdown_entries(&mut result, "Types", &module_diff.types);
+    markdown_entries(&mut result, "Contents", &module_diff.contents);
+    result
+}
+
+fn markdown_entries(result: &mut String, title: &str, entries: &EntriesDiff) {
+    if entries.is_empty() {
+        return;
+    }
+    let _ = writeln!(result, "## {}\n", title);
+    if !entries.added.is_empty() {
+        result.push_str("### Added\n\n");
+        for id in &entries.added {
+            let _ = writeln!(result, "- `{}`", id);
+        }
+        result.push('\n');
+    }
+    if !entries.removed.is_empty() {
+        result.push_str("### Removed\n\n");
+        for id in &entries.removed {
+            let _ = writeln!(result, "- `{}`", id);
+        }
+        result.push('\n');
+    }
+    if !entries.modified.is_empty() {
+        result.push_str("### Changed\n\n");
+        for (id, changes) in &entries.modified {
+            let _ = writeln!(result, "#### `{}`\n", id);
+            markdown_fields(result, &changes.fields);
+            for change in &changes.schema {
+                let _ = writeln!(
+                    result,
+                    "- {}{} (`{}`)",
+                    if change.breaking {
+                        "**Breaking:** "
+                    } else {
+                        ""
+                    },
+                    change.reason,
+                    change.pointer
+                );
+            }
+            result.push('\n');
+        }
+    }
+}
+
+fn markdown_fields(result: &mut String, fields: &[FieldChange]) {
+    for field in fields {
+        let _ = writeln!(
+            result,
+            "- `{}`: `{}` → `{}`",
+            field.pointer,
+            display_value(&field.old),
+            display_value(&field.new)
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use clap::Args;
+use module_render::module::RenderableModule;
+use std::{error::Error, path::PathBuf};
+use thiserror::Error;
+use tracing::{error, info, instrument};
+
+use crate::module_file::read_module;
+
+/// Checks the templates of a module without rendering it.
+///
+/// The templates of every type are checked against the schema of the type,
+/// finding the properties it does not define, and the helpers and partials
+/// that do not exist.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct LintTemplatesArguments {
+    /// The path to the module to be checked.
+    #[arg(required = true)]
+    source: PathBuf,
+}
+
+/// The errors that can happen when checking the templates of a Module.
+#[derive(Error, Debug)]
+pub enum LintError {
+    #[error("found {0} problems in the templates of the module")]
+    Problems(usize),
+}
+
+/// Executes the [LintTemplates](crate::Commands::LintTemplates) command.
+#[instrument]
+pub fn run(
+    LintTemplatesArguments { source }: LintTemplatesArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to check the templates of the module");
+    let module = read_module(&source)?;
+    let problems = module.get_renderer()?.lint_templates();
+    for problem in &problems {
+        error!("Template error: {}", problem);
+    }
+    if !problems.is_empty() {
+        return Err(Box::new(LintError::Problems(problems.len())));
+    }
+    println!("The templates of the module have no problems.");
+    info!("Done!");
+    Ok(())
+}
@@ -1,27 +1,45 @@
 extern crate clap;
+extern crate flate2;
 extern crate fs;
 extern crate jsonschema;
 extern crate module;
+extern crate module_codegen;
 extern crate module_render;
 extern crate serde_json;
 extern crate thiserror;
 extern crate tracing;
 extern crate tracing_subscriber;
+extern crate zstd;
+
+#[cfg(test)]
+extern crate pretty_assertions;
+#[cfg(test)]
+extern crate testdir;
 
 use std::error::Error;
 
 use build::BuildArguments;
 use clap::{Parser, Subcommand};
+use codegen::CodegenArguments;
+use diff::DiffArguments;
+use lint_templates::LintTemplatesArguments;
+use merge::MergeArguments;
 
 use render::RenderArguments;
+use schemas::SchemasArguments;
+use sign::SignArguments;
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
+use translations::TranslationsArguments;
 use validate::ValidateArguments;
+use verify::VerifyArguments;
+use verify_signature::VerifySignatureArguments;
 
 /// The entry point of the CLI execution.
 fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize tracing subscriber.
+    // Initialize tracing subscriber, keeping the standard output free for piping.
     let subscriber = FmtSubscriber::builder()
         .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
         .finish();
     tracing::subscriber::set_global_default(subscriber)
         .expect("Setting up a default tracing subscriber should always succeed.");
@@ -30,8 +48,17 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     match args.command {
         Commands::Build(args) => build::run(args),
+        Commands::Codegen(args) => codegen::run(args),
+        Commands::Diff(args) => diff::run(args),
+        Commands::LintTemplates(args) => lint_templates::run(args),
+        Commands::Merge(args) => merge::run(args),
         Commands::Render(args) => render::run(args),
+        Commands::Schemas(args) => schemas::run(args),
+        Commands::Sign(args) => sign::run(args),
+        Commands::Translations(args) => translations::run(args),
         Commands::Validate(args) => validate::run(args),
+        Commands::Verify(args) => verify::run(args),
+        Commands::VerifySignature(args) => verify_signature::run(args),
     }
 }
 
@@ -47,13 +74,44 @@ struct Cli {
 #[derive(Debug, Subcommand)]
 enum Commands {
     Build(BuildArguments),
+    Codegen(CodegenArguments),
+    Diff(DiffArguments),
+    LintTemplates(LintTemplatesArguments),
+    Merge(MergeArguments),
     Render(RenderArguments),
+    Schemas(SchemasArguments),
+    Sign(SignArguments),
+    Translations(TranslationsArguments),
     Validate(ValidateArguments),
+    Verify(VerifyArguments),
+    VerifySignature(VerifySignatureArguments),
 }
 
 /// Implements the [Build](crate::Commands::Build) command.
 pub mod build;
+/// Implements the [Codegen](crate::Commands::Codegen) command.
+pub mod codegen;
+/// Implements the [Diff](crate::Commands::Diff) command.
+pub mod diff;
+/// Implements the [LintTemplates](crate::Commands::LintTemplates) command.
+pub mod lint_templates;
+/// Implements the [Merge](crate::Commands::Merge) command.
+pub mod merge;
+/// Handles reading and writing Modules from and into files.
+mod module_file;
+/// Handles writing the outputs of commands into files or the standard output.
+mod output;
 /// Implements the [Render](crate::Commands::Render) command.
 pub mod render;
+/// Implements the [Schemas](crate::Commands::Schemas) command.
+pub mod schemas;
+/// Implements the [Sign](crate::Commands::Sign) command.
+pub mod sign;
+/// Implements the [Translations](crate::Commands::Translations) command.
+pub mod translations;
 /// Implements the [Validate](crate::Commands::Validate) command.
 pub mod validate;
+/// Implements the [Verify](crate::Commands::Verify) command.
+pub mod verify;
+/// Implements the [VerifySignature](crate::Commands::VerifySignature) command.
+pub mod verify_signature;
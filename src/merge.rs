@@ -0,0 +1,223 @@
+use clap::{Args, ValueEnum};
+use module::merge::{merge, ConflictPolicy, MergeOptions, MergeSource, MetadataPolicy};
+use std::collections::BTreeSet;
+use std::{error::Error, ffi::OsString, fs, path::PathBuf};
+use thiserror::Error;
+use tracing::{debug, info, instrument, warn};
+
+use crate::build::OutputType;
+use crate::module_file::{read_module, write_module};
+use crate::output::{Compression, STANDARD_OUTPUT};
+
+/// Merges several built modules into a single one.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct MergeArguments {
+    /// The paths to the modules that will be merged, in order.
+    ///
+    /// The name of each file (without extension) is used as it's namespace.
+    #[arg(required = true, num_args = 2..)]
+    sources: Vec<PathBuf>,
+    /// The name of the output file, without extension.
+    #[arg(short = 'o', long = "output", default_value = "module")]
+    output_file_name: OsString,
+    /// What type of output should be generated.
+    #[arg(
+            short = 't',
+            long = "type",
+            default_value_t = OutputType::Pretty,
+            value_enum
+        )]
+    output_type: OutputType,
+    /// Compresses the output, adding the extension of the compression to the output file.
+    #[arg(short = 'c', long = "compress", value_enum)]
+    compression: Option<Compression>,
+    /// How types defined differently by multiple modules are handled.
+    #[arg(long = "types-conflict", default_value_t = Conflict::Fail, value_enum)]
+    types_conflict: Conflict,
+    /// How contents defined differently by multiple modules are handled.
+    #[arg(long = "contents-conflict", default_value_t = Conflict::Fail, value_enum)]
+    contents_conflict: Conflict,
+    /// How layouts and partials defined differently by multiple modules are handled.
+    ///
+    /// Layouts can't be namespaced, since they are named after their format.
+    #[arg(long = "templates-conflict", default_value_t = Conflict::Fail, value_enum)]
+    templates_conflict: Conflict,
+    /// How the titles of the modules are combined.
+    #[arg(long = "title-policy", default_value_t = Metadata::First, value_enum)]
+    title_policy: Metadata,
+    /// How the descriptions of the modules are combined.
+    #[arg(long = "description-policy", default_value_t = Metadata::First, value_enum)]
+    description_policy: Metadata,
+    /// Replaces the title of the merged module, ignoring the title policy.
+    #[arg(long = "title")]
+    title: Option<String>,
+    /// Replaces the description of the merged module, ignoring the description policy.
+    #[arg(long = "description")]
+    description: Option<String>,
+}
+
+/// How identifiers defined differently by multiple modules are handled.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// Stop the merge with an error.
+    Fail,
+    /// Keep the entry of the first module that defines it.
+    FirstWins,
+    /// Keep the entry of the last module that defines it.
+    LastWins,
+    /// Keep all entries, prefixing their identifiers with the module namespace.
+    Namespaced,
+}
+
+impl From<Conflict> for ConflictPolicy {
+    fn from(value: Conflict) -> Self {
+        match value {
+            Conflict::Fail => ConflictPolicy::Fail,
+            Conflict::FirstWins => ConflictPolicy::FirstWins,
+            Conflict::LastWins => ConflictPolicy::LastWins,
+            Conflict::Namespaced => ConflictPolicy::Namespaced,
+        }
+    }
+}
+
+/// How a metadata field of the modules is combined.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Metadata {
+    /// Use the value of the first module.
+    First,
+    /// Use the value of the last module.
+    Last,
+    /// Join the values of all modules.
+    Join,
+}
+
+impl From<Metadata> for MetadataPolicy {
+    fn from(value: Metadata) -> Self {
+        match value {
+            Metadata::First => MetadataPolicy::First,
+            Metadata::Last => MetadataPolicy::Last,
+            Metadata::Join => MetadataPolicy::Join,
+        }
+    }
+}
+
+/// The errors that can happen when merging modules.
+#[derive(Error, Debug)]
+pub enum MergeCommandError {
+    #[error("more than one module uses the namespace `{0}`, rename one of the files")]
+    DuplicateNamespace(String),
+}
+
+/// Executes the [Merge](crate::Commands::Merge) command.
+#[instrument]
+pub fn run(
+    MergeArguments {
+        sources,
+        output_file_name,
+        output_type,
+        compression,
+        types_conflict,
+        contents_conflict,
+        templates_conflict,
+        title_policy,
+        description_policy,
+        title,
+        description,
+    }: MergeArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to merge the modules");
+    let mut namespaces = BTreeSet::new();
+    let mut merge_sources = Vec::new();
+    for source in sources {
+        let namespace = source
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .expect("The module file name should be a valid UTF-8 String")
+            .to_string();
+        if !namespaces.insert(namespace.clone()) {
+            return Err(Box::new(MergeCommandError::DuplicateNamespace(namespace)));
+        }
+        merge_sources.push(MergeSource {
+            namespace,
+            module: read_module(&source)?,
+        });
+    }
+    let options = MergeOptions {
+        types: types_conflict.into(),
+        contents: contents_conflict.into(),
+        templates: templates_conflict.into(),
+        title: title.map_or(title_policy.into(), MetadataPolicy::Custom),
+        description: description.map_or(description_policy.into(), MetadataPolicy::Custom),
+    };
+    let merged = merge(&merge_sources, &options)?;
+    info!("Modules were merged: {:#?}", merged.module);
+    write_module(&merged.module, &output_file_name, output_type, compression)?;
+    if output_file_name == STANDARD_OUTPUT {
+        warn!("The provenance file is not written when writing to the standard output");
+    } else {
+        let mut provenance_file = output_file_name.clone();
+        provenance_file.push(".sources.json");
+        debug!(
+            "Writing the provenance of the merged entries to {:?}",
+            provenance_file
+        );
+        fs::write(
+            provenance_file,
+            serde_json::to_string_pretty(&merged.provenance)?,
+        )?;
+    }
+    info!("Done!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use module::module::Module;
+    use pretty_assertions::assert_eq;
+    use testdir::testdir;
+
+    #[test]
+    fn merged_modules_are_compressed_next_to_their_provenance() {
+        let dir = testdir!();
+        let mut sources = Vec::new();
+        for (namespace, content) in [("fire", "fireball"), ("ice", "frost")] {
+            let path = dir.join(format!("{}.json", namespace));
+            fs::write(
+                &path,
+                serde_json::json!({
+                    "title": namespace,
+                    "description": "My description",
+                    "source": "https://powerd6.org",
+                    "contents": {content: {"type": "spell"}}
+                })
+                .to_string(),
+            )
+            .unwrap();
+            sources.push(path);
+        }
+
+        run(MergeArguments {
+            sources,
+            output_file_name: dir.join("merged").into_os_string(),
+            output_type: OutputType::Pretty,
+            compression: Some(Compression::Zstd),
+            types_conflict: Conflict::Fail,
+            contents_conflict: Conflict::Fail,
+            templates_conflict: Conflict::Fail,
+            title_policy: Metadata::First,
+            description_policy: Metadata::First,
+            title: None,
+            description: None,
+        })
+        .unwrap();
+
+        let merged: Module = read_module(&dir.join("merged.json.zst")).unwrap();
+        assert_eq!(merged.title(), "fire");
+        assert_eq!(merged.contents.unwrap().len(), 2);
+        assert!(dir.join("merged.sources.json").exists());
+        assert!(!dir.join("merged.json").exists());
+    }
+}
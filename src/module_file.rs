@@ -0,0 +1,120 @@
+use crate::build::OutputType;
+use crate::output::{Compression, Output};
+use flate2::read::GzDecoder;
+use fs::file_system::FileSystem;
+use module::{format::ModuleFormat, module::Module};
+use std::io::Read;
+use std::{convert::TryFrom, error::Error, ffi::OsStr, fs::File, path::Path};
+use tracing::{debug, instrument};
+
+/// Reads a module from a file, or builds it if the path is a source directory.
+///
+/// The format of files is detected from their extension, defaulting to JSON,
+/// and compressed files (like `module.json.gz`) are decompressed.
+/// The inheritance between types is always resolved.
+#[instrument]
+pub(crate) fn read_module(path: &Path) -> Result<Module, Box<dyn Error>> {
+    if path.is_dir() {
+        let file_system = FileSystem::try_from(path.to_path_buf())?;
+        let module = Module::try_from(file_system)?;
+        debug!(
+            "Built module from source directory correctly: {:#?}",
+            module
+        );
+        return Ok(module);
+    }
+    let file = File::open(path)?;
+    let compression = extension_of(path).and_then(Compression::from_extension);
+    let (mut reader, format_path): (Box<dyn Read>, &Path) = match compression {
+        Some(Compression::Gzip) => (
+            Box::new(GzDecoder::new(file)),
+            path.file_stem().unwrap().as_ref(),
+        ),
+        Some(Compression::Zstd) => (
+            Box::new(zstd::Decoder::new(file)?),
+            path.file_stem().unwrap().as_ref(),
+        ),
+        None => (Box::new(file), path),
+    };
+    let format = extension_of(format_path)
+        .and_then(ModuleFormat::from_extension)
+        .unwrap_or(ModuleFormat::Json);
+    debug!(
+        "Reading module as {:?}, compressed with {:?}",
+        format, compression
+    );
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let mut module = Module::from_bytes(&bytes, format)?;
+    module.resolve_type_inheritance()?;
+    debug!("Loaded module from file correctly: {:#?}", module);
+    Ok(module)
+}
+
+fn extension_of(path: &Path) -> Option<&str> {
+    path.extension().and_then(OsStr::to_str)
+}
+
+/// Writes a module into a file, named after `output_file_name` with the
+/// extension that corresponds to the `output_type` and `compression`.
+///
+/// The module is written to the standard output if the name is `-`.
+#[instrument(skip(module))]
+pub(crate) fn write_module(
+    module: &Module,
+    output_file_name: &OsStr,
+    output_type: OutputType,
+    compression: Option<Compression>,
+) -> Result<(), Box<dyn Error>> {
+    let format = output_type.format();
+    let mut output = Output::create(output_file_name, format.extension(), compression)?;
+    debug!("About to write module as {:?}", output_type);
+    match output_type {
+        OutputType::Pretty => serde_json::to_writer_pretty(&mut output, module)?,
+        _ => module.to_writer(&mut output, format)?,
+    };
+    output.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use testdir::testdir;
+
+    #[test]
+    fn compressed_modules_round_trip() {
+        let dir = testdir!();
+        let module: Module = serde_json::from_value(serde_json::json!({
+            "title": "My title",
+            "description": "My description",
+            "source": "https://powerd6.org",
+            "contents": {"fireball": {"type": "spell", "name": "Fireball"}}
+        }))
+        .unwrap();
+        let outputs = [
+            (
+                OutputType::Pretty,
+                Some(Compression::Gzip),
+                "pretty.json.gz",
+            ),
+            (OutputType::Cbor, Some(Compression::Zstd), "cbor.cbor.zst"),
+            (OutputType::Yaml, Some(Compression::Gzip), "yaml.yaml.gz"),
+            (OutputType::Minimized, None, "minimized.json"),
+        ];
+        for (output_type, compression, file_name) in outputs {
+            let name = file_name.split('.').next().unwrap();
+            write_module(
+                &module,
+                dir.join(name).as_os_str(),
+                output_type,
+                compression,
+            )
+            .unwrap();
+
+            assert_eq!(read_module(&dir.join(file_name)).unwrap(), module);
+        }
+    }
+}
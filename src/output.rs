@@ -0,0 +1,187 @@
+use clap::ValueEnum;
+use flate2::{write::GzEncoder, Compression as GzipLevel};
+use std::{
+    error::Error,
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// The name of the output that represents the standard output.
+pub(crate) const STANDARD_OUTPUT: &str = "-";
+
+/// The compression applied to an output.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// [gzip](https://www.gzip.org/), widely supported.
+    Gzip,
+    /// [Zstandard](https://facebook.github.io/zstd/), faster and smaller.
+    Zstd,
+}
+
+impl Compression {
+    /// The extension added to compressed files, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+        }
+    }
+
+    /// Finds the compression of a file from it's extension.
+    pub fn from_extension(extension: &str) -> Option<Compression> {
+        match extension {
+            "gz" => Some(Compression::Gzip),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// A destination for the output of a command, that compresses what is written to it.
+///
+/// It must be [finished](Output::finish) for everything to be written.
+pub(crate) enum Output {
+    Plain(BufWriter<Box<dyn Write>>),
+    Gzip(GzEncoder<BufWriter<Box<dyn Write>>>),
+    Zstd(zstd::Encoder<'static, BufWriter<Box<dyn Write>>>),
+}
+
+impl Output {
+    /// Creates the output `{file_name}.{extension}`, followed by the extension
+    /// of the compression, or the standard output if the name is `-`.
+    pub(crate) fn create(
+        file_name: &OsStr,
+        extension: &str,
+        compression: Option<Compression>,
+    ) -> Result<Output, Box<dyn Error>> {
+        if file_name == STANDARD_OUTPUT {
+            debug!("Writing to the standard output");
+            return Output::wrap(Box::new(io::stdout().lock()), compression);
+        }
+        let mut path = file_name.to_owned();
+        path.push(format!(".{}", extension));
+        Output::create_file(Path::new(&path), compression)
+    }
+
+    /// Creates the output at a path, followed by the extension of the compression,
+    /// creating the directories that contain it.
+    pub(crate) fn create_file(
+        path: &Path,
+        compression: Option<Compression>,
+    ) -> Result<Output, Box<dyn Error>> {
+        let mut path = path.as_os_str().to_owned();
+        if let Some(compression) = compression {
+            path.push(format!(".{}", compression.extension()));
+        }
+        let path = PathBuf::from(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        debug!("Writing to {:?}", path);
+        Output::wrap(Box::new(File::create(path)?), compression)
+    }
+
+    fn wrap(
+        destination: Box<dyn Write>,
+        compression: Option<Compression>,
+    ) -> Result<Output, Box<dyn Error>> {
+        let writer = BufWriter::new(destination);
+        Ok(match compression {
+            None => Output::Plain(writer),
+            Some(Compression::Gzip) => Output::Gzip(GzEncoder::new(writer, GzipLevel::default())),
+            Some(Compression::Zstd) => Output::Zstd(zstd::Encoder::new(writer, 0)?),
+        })
+    }
+
+    /// Completes the compression, and flushes everything that was written.
+    pub(crate) fn finish(self) -> io::Result<()> {
+        let mut writer = match self {
+            Output::Plain(writer) => writer,
+            Output::Gzip(encoder) => encoder.finish()?,
+            Output::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Plain(writer) => writer.write(buf),
+            Output::Gzip(encoder) => encoder.write(buf),
+            Output::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Plain(writer) => writer.flush(),
+            Output::Gzip(encoder) => encoder.flush(),
+            Output::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use flate2::read::GzDecoder;
+    use pretty_assertions::assert_eq;
+    use std::io::Read;
+    use testdir::testdir;
+
+    #[test]
+    fn compression_is_found_by_extension() {
+        assert_eq!(Compression::from_extension("gz"), Some(Compression::Gzip));
+        assert_eq!(Compression::from_extension("zst"), Some(Compression::Zstd));
+        assert_eq!(Compression::from_extension("json"), None);
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            assert_eq!(
+                Compression::from_extension(compression.extension()),
+                Some(compression)
+            );
+        }
+    }
+
+    #[test]
+    fn compressed_outputs_round_trip() {
+        let dir = testdir!();
+        for compression in [None, Some(Compression::Gzip), Some(Compression::Zstd)] {
+            let mut output = Output::create(
+                dir.join("nested").join("output").as_os_str(),
+                "txt",
+                compression,
+            )
+            .unwrap();
+            output.write_all(b"Fireball").unwrap();
+            output.finish().unwrap();
+
+            let mut path = dir.join("nested").join("output.txt").into_os_string();
+            if let Some(compression) = compression {
+                path.push(format!(".{}", compression.extension()));
+            }
+            let file = File::open(path).unwrap();
+            let mut reader: Box<dyn Read> = match compression {
+                None => Box::new(file),
+                Some(Compression::Gzip) => Box::new(GzDecoder::new(file)),
+                Some(Compression::Zstd) => Box::new(zstd::Decoder::new(file).unwrap()),
+            };
+            let mut contents = String::new();
+            reader.read_to_string(&mut contents).unwrap();
+            assert_eq!(contents, "Fireball");
+        }
+    }
+
+    #[test]
+    fn standard_output_is_not_a_file() {
+        let output = Output::create(OsStr::new(STANDARD_OUTPUT), "json", None).unwrap();
+
+        assert!(matches!(output, Output::Plain(_)));
+        output.finish().unwrap();
+        assert!(!Path::new("-.json").exists());
+    }
+}
@@ -1,14 +1,26 @@
-use clap::Args;
+use clap::{Args, ValueEnum};
+use module::TYPE_KEY;
+use module_render::escaping::Escaping;
+use module_render::markdown::MarkdownOptions;
 use module_render::module::RenderableModule;
-use module_render::renderer::ModuleRenderer;
+use module_render::renderer::{
+    index_key, render_standalone, ModuleRenderer, RenderedContent, RenderedGroup,
+};
+use module_render::selection::{ContentFilter, ContentGroup, ContentSelection, GroupBy, SortKey};
+use module_render::RenderingError;
+use serde_json::{json, Value};
 
-use module::module::Module;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
-use std::io::BufReader;
 use std::io::Write;
-use std::{ffi::OsString, fs::File, path::PathBuf};
+use std::path::{Component, Path};
+use std::str::FromStr;
+use std::{ffi::OsString, path::PathBuf};
 use thiserror::Error;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::module_file::read_module;
+use crate::output::{Compression, Output, STANDARD_OUTPUT};
 
 /// Renders a module with a specific format.
 #[derive(Debug, Args)]
@@ -17,12 +29,150 @@ pub struct RenderArguments {
     /// The path to the module to be rendered.
     #[arg(required = true)]
     source: PathBuf,
-    /// The name of the output file, without extension.
+    /// The name of the output file, without extension, or `-` to write to the standard output.
+    ///
+    /// When splitting the output, this is the directory the files are written into.
     #[arg(short = 'o', long = "output", default_value = "module")]
     output_file_name: OsString,
     /// The format that should be rendered.
     #[arg(required = true)]
     format: String,
+    /// Compresses the output, adding the extension of the compression to the output file.
+    #[arg(short = 'c', long = "compress", value_enum)]
+    compression: Option<Compression>,
+    /// Writes one file per content or per type into the output directory,
+    /// next to an `index.{format}` file that lists them.
+    ///
+    /// The index uses the `@index/{format}` template of the module, if it exists,
+    /// which source directories define in `rendering/@index/{format}.hjs`.
+    #[arg(long = "split", value_enum)]
+    split: Option<Split>,
+    /// The template of the names of the files when splitting the output,
+    /// relative to the output directory, like `{{type}}/{{id}}.md`.
+    ///
+    /// It receives the `type` and `format`, and also the `id` and `content` when
+    /// splitting per content.
+    #[arg(long = "file-name", requires = "split")]
+    file_name: Option<String>,
+    #[command(flatten)]
+    selection: SelectionArguments,
+    #[command(flatten)]
+    markdown: MarkdownArguments,
+    /// Escapes the values written by every template with `html`, `markdown`,
+    /// `latex` or `none`.
+    ///
+    /// By default, values are escaped as the format requires (like `html` for
+    /// `html` and `none` for `md`), or as the types configure with their
+    /// `escaping`. Values written with `{{{value}}}` are never escaped.
+    #[arg(long = "escape", value_parser = parse_option::<Escaping>)]
+    escaping: Option<Escaping>,
+    /// Fails when a template uses a property that does not exist, instead of
+    /// rendering it as an empty string.
+    #[arg(long = "strict")]
+    strict: bool,
+}
+
+/// How the Markdown properties of contents are converted.
+#[derive(Debug, Args)]
+pub struct MarkdownArguments {
+    /// Converts the Markdown properties of contents, which have
+    /// `"contentMediaType": "text/markdown"` in their schema, before rendering them.
+    ///
+    /// They become sanitized HTML for `html`, stay unchanged for `md`, and become plain text otherwise.
+    /// Templates write converted HTML with `{{{value}}}`, since `{{value}}` escapes it again.
+    #[arg(long = "markdown")]
+    convert: bool,
+    /// How many levels the headings of converted Markdown are moved down.
+    #[arg(long = "heading-offset", default_value_t = 0, requires = "convert")]
+    heading_offset: u8,
+    /// Does not convert the footnotes of Markdown.
+    #[arg(long = "no-footnotes", requires = "convert")]
+    no_footnotes: bool,
+    /// Does not convert the tables of Markdown.
+    #[arg(long = "no-tables", requires = "convert")]
+    no_tables: bool,
+}
+
+impl From<&MarkdownArguments> for MarkdownOptions {
+    fn from(arguments: &MarkdownArguments) -> Self {
+        MarkdownOptions {
+            heading_offset: arguments.heading_offset,
+            footnotes: !arguments.no_footnotes,
+            tables: !arguments.no_tables,
+        }
+    }
+}
+
+/// How the rendered output is split into files.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Split {
+    /// One file for every content.
+    PerContent,
+    /// One file for every type, with all of it's contents.
+    PerType,
+}
+
+impl Split {
+    /// The template of the names of the files, when none is provided.
+    fn default_file_name(&self) -> &'static str {
+        match self {
+            Split::PerContent => "{{type}}/{{id}}.{{format}}",
+            Split::PerType => "{{type}}.{{format}}",
+        }
+    }
+}
+
+/// Which contents are rendered, and in which order.
+#[derive(Debug, Args)]
+pub struct SelectionArguments {
+    /// Renders only the contents of a type. Can be repeated, and the order
+    /// of the types is the order of their groups when grouping by type.
+    #[arg(long = "type")]
+    types: Vec<String>,
+    /// Renders only the contents with an identifier, which may use `*` and `?`
+    /// as wildcards, like `fire*`. Can be repeated, and each identifier must
+    /// match at least one content.
+    #[arg(long = "id")]
+    ids: Vec<String>,
+    /// Renders only the contents that match a filter, like `/school=fire`,
+    /// `/cost>=2` or `/tags` (the property exists). Can be repeated.
+    #[arg(long = "where", value_parser = parse_option::<ContentFilter>)]
+    filters: Vec<ContentFilter>,
+    /// Sorts the contents by a property, like `/cost` or `/cost:desc`.
+    /// Can be repeated, in order of importance.
+    #[arg(long = "sort-by", value_parser = parse_option::<SortKey>)]
+    sort_by: Vec<SortKey>,
+    /// Groups the contents by `type` or by a property, like `/school`.
+    ///
+    /// Each group starts with the `@group/{format}` template of the module, if it exists,
+    /// which source directories define in `rendering/@group/{format}.hjs`.
+    #[arg(long = "group-by", value_parser = parse_option::<GroupBy>)]
+    group_by: Option<GroupBy>,
+}
+
+impl From<SelectionArguments> for ContentSelection {
+    fn from(
+        SelectionArguments {
+            types,
+            ids,
+            filters,
+            sort_by,
+            group_by,
+        }: SelectionArguments,
+    ) -> Self {
+        ContentSelection {
+            types,
+            ids,
+            filters,
+            sort_by,
+            group_by,
+        }
+    }
+}
+
+/// Parses the options that are read by the rendering library.
+fn parse_option<T: FromStr<Err = RenderingError>>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|e: RenderingError| e.to_string())
 }
 
 /// The errors that can happen when rendering a Module.
@@ -30,6 +180,12 @@ pub struct RenderArguments {
 pub enum RenderError {
     #[error("found no contents in the module")]
     MissingContents,
+    #[error("split outputs must be written into a directory, not the standard output")]
+    SplitToStandardOutput,
+    #[error("the file name `{0}` must be a relative path inside the output directory")]
+    InvalidFileName(String),
+    #[error("the file name `{0}` is used by more than one file")]
+    DuplicateFileName(String),
 }
 
 /// Executes the [Render](crate::Commands::Render) command.
@@ -38,43 +194,325 @@ pub fn run(
         source,
         output_file_name,
         format,
+        compression,
+        split,
+        file_name,
+        selection,
+        markdown,
+        escaping,
+        strict,
     }: RenderArguments,
 ) -> Result<(), Box<dyn Error>> {
     info!("Starting to render the module");
-    let file = File::open(source)?;
-    let reader = BufReader::new(file);
-    let module: Module = serde_json::from_reader(reader)?;
-    debug!("Loaded module from file correctly: {:#?}", module);
-    let module_renderer = module.get_renderer()?;
+    if split.is_some() && output_file_name == STANDARD_OUTPUT {
+        error!("Split outputs can not be written to the standard output");
+        return Err(RenderError::SplitToStandardOutput.into());
+    }
+    let module = read_module(&source)?;
+    let mut module_renderer = module.get_renderer()?;
+    if markdown.convert {
+        debug!("Converting the Markdown properties of contents");
+        module_renderer = module_renderer.with_markdown_conversion((&markdown).into());
+    }
+    if let Some(escaping) = escaping {
+        debug!("Escaping every template with {:?}", escaping);
+        module_renderer = module_renderer.with_escaping(escaping);
+    }
+    if strict {
+        debug!("Rendering in strict mode");
+        module_renderer = module_renderer.with_strict_mode();
+    }
     debug!("Compiled the rendering for module");
-    let rendered_module = render_contents(&module_renderer, &format)?;
-    debug!("Rendered contents to string");
-    let mut output_file = File::create(format!(
-        "{}.{}",
-        output_file_name
-            .to_str()
-            .expect("The output file name should be a valid UTF-8 String"),
-        format
-    ))?;
-    write!(output_file, "{}", rendered_module)?;
+    let mut selection = ContentSelection::from(selection);
+    if split == Some(Split::PerType) {
+        if matches!(selection.group_by, Some(GroupBy::Field(_))) {
+            warn!("Splitting per type ignores the grouping of contents");
+        }
+        selection.group_by = Some(GroupBy::Type);
+    }
+    let contents = module_renderer.module().contents.as_ref();
+    selection
+        .check_ids(contents.unwrap_or(&BTreeMap::new()))
+        .map_err(|e| {
+            error!("The selected contents do not exist: {}", e);
+            e
+        })?;
+    let contents = contents.ok_or_else(|| {
+        error!("No contents were present in the module");
+        RenderError::MissingContents
+    })?;
+    let groups = selection.apply(contents);
+    match split {
+        Some(split) => {
+            let file_name = file_name.unwrap_or_else(|| split.default_file_name().to_string());
+            render_split(
+                &module_renderer,
+                &groups,
+                split,
+                &file_name,
+                Path::new(&output_file_name),
+                &format,
+                compression,
+            )?;
+        }
+        None => {
+            let grouped = selection.group_by.is_some();
+            let mut output = Output::create(&output_file_name, &format, compression)?;
+            render_document(&module_renderer, &groups, grouped, &format, &mut output)?;
+            output.finish()?;
+        }
+    }
+    debug!("Rendered contents to output");
     info!("Done!");
     Ok(())
 }
 
-#[instrument(skip(renderer))]
-fn render_contents(renderer: &ModuleRenderer, format: &String) -> Result<String, Box<dyn Error>> {
-    match &renderer.module.contents {
-        Some(contents) => {
-            let mut result = String::new();
-            for value in contents.values() {
-                result += &renderer.render(value, format)?;
-                result += "\n";
+/// Renders groups of contents into a writer, inside the module layout if it exists.
+#[instrument(skip(renderer, groups, writer))]
+fn render_document<W: Write>(
+    renderer: &ModuleRenderer,
+    groups: &[ContentGroup],
+    grouped: bool,
+    format: &str,
+    writer: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    if renderer.has_layout(format) {
+        debug!("Rendering contents into the module layout");
+        let groups = render_groups_for_layout(renderer, groups, format)?;
+        renderer.render_layout_to_write(&groups, format, writer)?;
+    } else {
+        render_groups(renderer, groups, grouped, format, writer)?;
+    }
+    Ok(())
+}
+
+/// Renders every content or type into it's own file inside a directory,
+/// followed by the index of the files.
+#[instrument(skip(renderer, groups))]
+fn render_split(
+    renderer: &ModuleRenderer,
+    groups: &[ContentGroup],
+    split: Split,
+    file_name: &str,
+    directory: &Path,
+    format: &str,
+    compression: Option<Compression>,
+) -> Result<(), Box<dyn Error>> {
+    let index_name = format!("index.{}", format);
+    let mut used_names = BTreeSet::from([index_name.clone()]);
+    let mut files = Vec::new();
+    let mut write_file = |name: String,
+                          id: Option<&String>,
+                          type_key: &str,
+                          group: ContentGroup|
+     -> Result<(), Box<dyn Error>> {
+        let path = relative_file_path(&name)?;
+        if !used_names.insert(name.clone()) {
+            error!("More than one file would be written to `{}`", name);
+            return Err(RenderError::DuplicateFileName(name).into());
+        }
+        debug!(
+            "Rendering {} contents into `{}`",
+            group.contents.len(),
+            name
+        );
+        let mut output = Output::create_file(&directory.join(path), compression)?;
+        render_document(renderer, &[group], false, format, &mut output)?;
+        output.finish()?;
+        files.push(json!({
+            "path": with_compression_extension(name, compression),
+            "id": id,
+            "type": type_key,
+        }));
+        Ok(())
+    };
+    for group in groups {
+        match split {
+            Split::PerContent => {
+                for (id, content) in &group.contents {
+                    let type_key = type_of(content);
+                    let name = render_standalone(
+                        file_name,
+                        &json!({"id": id, "type": type_key, "format": format, "content": content}),
+                    )?;
+                    let single = ContentGroup {
+                        key: group.key.clone(),
+                        contents: vec![(*id, *content)],
+                    };
+                    write_file(name, Some(id), type_key, single)?;
+                }
+            }
+            Split::PerType => {
+                let type_key = group
+                    .key
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let name =
+                    render_standalone(file_name, &json!({"type": type_key, "format": format}))?;
+                write_file(name, None, type_key, group.clone())?;
             }
-            Ok(result)
         }
-        None => {
-            error!("No contents were present in the module");
-            Err(Box::new(RenderError::MissingContents))
+    }
+    let mut index = Output::create_file(&directory.join(&index_name), compression)?;
+    render_index(renderer, &files, format, &mut index)?;
+    index.finish()?;
+    info!("Wrote {} files into {}", files.len(), directory.display());
+    Ok(())
+}
+
+/// Renders the list of files of a split output into a writer.
+///
+/// Uses the `@index/{format}` template of the module, that receives the `files`
+/// with their `path`, `id` and `type`, or a plain list of links otherwise.
+#[instrument(skip(renderer, files, writer))]
+fn render_index<W: Write>(
+    renderer: &ModuleRenderer,
+    files: &[Value],
+    format: &str,
+    writer: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    let key = index_key(format);
+    if renderer.has_module_template(&key) {
+        let data = BTreeMap::from([("files".to_string(), Value::from(files.to_vec()))]);
+        renderer.render_module_template_to_write(&key, format, data, writer)?;
+        return Ok(());
+    }
+    debug!("The module has no index for the format `{}`", format);
+    let entries = files.iter().map(|file| {
+        let path = file["path"].as_str().unwrap_or_default();
+        let label = file["id"]
+            .as_str()
+            .or(file["type"].as_str())
+            .unwrap_or(path);
+        (path, label)
+    });
+    match format {
+        "md" => {
+            for (path, label) in entries {
+                writeln!(writer, "- [{}]({})", label, path)?;
+            }
+        }
+        "html" => {
+            writeln!(writer, "<ul>")?;
+            for (path, label) in entries {
+                writeln!(
+                    writer,
+                    "<li><a href=\"{}\">{}</a></li>",
+                    escape_html(path),
+                    escape_html(label)
+                )?;
+            }
+            writeln!(writer, "</ul>")?;
+        }
+        _ => {
+            for (path, _) in entries {
+                writeln!(writer, "{}", path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a file name stays inside the output directory.
+fn relative_file_path(name: &str) -> Result<&Path, RenderError> {
+    let path = Path::new(name);
+    let is_relative = path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if is_relative {
+        Ok(path)
+    } else {
+        error!(
+            "The file name `{}` is outside of the output directory",
+            name
+        );
+        Err(RenderError::InvalidFileName(name.to_string()))
+    }
+}
+
+/// Adds the extension of the compression to the name of a file.
+fn with_compression_extension(name: String, compression: Option<Compression>) -> String {
+    match compression {
+        Some(compression) => format!("{}.{}", name, compression.extension()),
+        None => name,
+    }
+}
+
+/// Escapes the characters that are special in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The type of a content, or an empty string if it has none.
+fn type_of(content: &BTreeMap<String, Value>) -> &str {
+    content
+        .get(TYPE_KEY)
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+}
+
+/// Renders every content on it's own, to be provided to the module layout.
+///
+/// The layout needs all of them at once, so they are kept in memory.
+#[instrument(skip(renderer, groups))]
+fn render_groups_for_layout(
+    renderer: &ModuleRenderer,
+    groups: &[ContentGroup],
+    format: &str,
+) -> Result<Vec<RenderedGroup>, Box<dyn Error>> {
+    let mut result = Vec::new();
+    for group in groups {
+        let mut contents = Vec::new();
+        for (id, content) in &group.contents {
+            contents.push(RenderedContent {
+                id: id.to_string(),
+                type_key: type_of(content).to_string(),
+                rendered: renderer.render(content, format)?,
+            });
         }
+        result.push(RenderedGroup {
+            key: group.key.clone(),
+            contents,
+        });
+    }
+    Ok(result)
+}
+
+/// Renders every group, one after the other, into a writer.
+///
+/// When contents are grouped, groups start with their header, if the module
+/// has one for the format.
+#[instrument(skip(renderer, groups, writer))]
+fn render_groups<W: Write>(
+    renderer: &ModuleRenderer,
+    groups: &[ContentGroup],
+    grouped: bool,
+    format: &str,
+    writer: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    let has_header = grouped && renderer.has_group_header(format);
+    if grouped && !has_header {
+        warn!("The module has no group header for the format `{}`", format);
     }
+    for group in groups {
+        if has_header {
+            renderer.render_group_header_to_write(
+                &group.key,
+                group.contents.len(),
+                format,
+                &mut *writer,
+            )?;
+            writeln!(writer)?;
+        }
+        for (_, content) in &group.contents {
+            renderer.render_to_write(content, format, &mut *writer)?;
+            writeln!(writer)?;
+        }
+    }
+    Ok(())
 }
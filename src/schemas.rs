@@ -0,0 +1,47 @@
+use clap::Args;
+use module::schema::{module_schema, type_schema};
+use serde_json::Value;
+use std::{error::Error, fs, path::Path, path::PathBuf};
+use tracing::{debug, info, instrument};
+
+use crate::module_file::read_module;
+
+/// Writes the JSON Schemas of modules, types and contents, to be used by editors.
+#[derive(Debug, Args)]
+pub struct SchemasArguments {
+    /// The directory the schemas are written into.
+    #[arg(short = 'o', long = "output", default_value = "schemas")]
+    output_directory: PathBuf,
+    /// The path to a module, either built or a source directory,
+    /// whose types are used to write the schema of it's contents.
+    #[arg(short = 'm', long = "module")]
+    module: Option<PathBuf>,
+}
+
+/// Executes the [Schemas](crate::Commands::Schemas) command.
+#[instrument]
+pub fn run(
+    SchemasArguments {
+        output_directory,
+        module,
+    }: SchemasArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to write the schemas");
+    fs::create_dir_all(&output_directory)?;
+    write_schema(&output_directory, "module", &module_schema())?;
+    write_schema(&output_directory, "type", &type_schema())?;
+    if let Some(source) = module {
+        let module = read_module(&source)?;
+        write_schema(&output_directory, "content", &module.content_schema())?;
+    }
+    info!("Done!");
+    Ok(())
+}
+
+/// Writes a schema as `{name}.schema.json` inside a directory.
+fn write_schema(directory: &Path, name: &str, schema: &Value) -> Result<(), Box<dyn Error>> {
+    let path = directory.join(format!("{}.schema.json", name));
+    debug!("Writing {:?}", path);
+    fs::write(path, serde_json::to_string_pretty(schema)? + "\n")?;
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use clap::Args;
+use module::signature::{read_signing_key, sign};
+use std::{error::Error, fs, path::PathBuf};
+use tracing::{debug, info, instrument};
+
+use crate::verify_signature::default_signature_file;
+
+/// Signs the file of a built module, writing a detached signature next to it.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct SignArguments {
+    /// The path to the built module.
+    #[arg(required = true)]
+    module: PathBuf,
+    /// The path to the Ed25519 private key, as a PKCS#8 PEM file.
+    #[arg(short = 'k', long = "key", required = true)]
+    key_file: PathBuf,
+    /// The path of the signature file. Defaults to the module path followed by `.sig`.
+    #[arg(short = 'o', long = "output")]
+    signature_file: Option<PathBuf>,
+}
+
+/// Executes the [Sign](crate::Commands::Sign) command.
+#[instrument]
+pub fn run(
+    SignArguments {
+        module,
+        key_file,
+        signature_file,
+    }: SignArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to sign the module");
+    let key = read_signing_key(&fs::read_to_string(key_file)?)?;
+    let signature = sign(&fs::read(&module)?, &key);
+    let signature_file = signature_file.unwrap_or_else(|| default_signature_file(&module));
+    debug!("Writing signature to {:?}", signature_file);
+    fs::write(
+        signature_file,
+        serde_json::to_string_pretty(&signature)? + "\n",
+    )?;
+    info!("Done!");
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use clap::Args;
+use fs::file_system::FileSystem;
+use module::locale::{find_locales, TranslationReport};
+use std::{convert::TryFrom, error::Error, path::PathBuf};
+use tracing::{debug, info, instrument};
+
+/// Reports the untranslated and stale keys of each locale of a source directory.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct TranslationsArguments {
+    /// The path to the directory that contains the module sources.
+    #[arg(required = true)]
+    source: PathBuf,
+    /// The locales to report on. If not provided, all locales declared in `locales` are used.
+    #[arg(short = 'l', long = "locale")]
+    locales: Vec<String>,
+}
+
+/// Executes the [Translations](crate::Commands::Translations) command.
+#[instrument]
+pub fn run(
+    TranslationsArguments { source, locales }: TranslationsArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to check the translations");
+    let file_system = FileSystem::try_from(source)?;
+    let locales = if locales.is_empty() {
+        find_locales(&file_system).into_iter().collect()
+    } else {
+        locales
+    };
+    debug!("Checking locales: {:?}", locales);
+    for locale in locales {
+        let report = TranslationReport::try_from_file_system(&file_system, &locale)?;
+        println!(
+            "{}: {} untranslated, {} stale",
+            report.locale,
+            report.untranslated.len(),
+            report.stale.len()
+        );
+        for key in &report.untranslated {
+            println!("  untranslated: {}", key);
+        }
+        for key in &report.stale {
+            println!("  stale: {}", key);
+        }
+    }
+    info!("Done!");
+    Ok(())
+}
@@ -3,10 +3,12 @@ use jsonschema::JSONSchema;
 use module::module::Module;
 use module::TYPE_KEY;
 use serde_json::{json, Value};
-use std::io::BufReader;
-use std::{error::Error, fs::File, path::PathBuf};
+use std::{error::Error, path::PathBuf};
 use tracing::{debug, error, info, instrument};
 
+use crate::module_file::read_module;
+use crate::verify_signature::{check_signature, TrustArguments};
+
 const MODULE_SCHEMA: &str = "https://specification.powerd6.org/schemas/module.json";
 const CONTENT_SCHEMA: &str = "https://specification.powerd6.org/schemas/content.json";
 
@@ -17,17 +19,30 @@ pub struct ValidateArguments {
     /// The path to the module to be rendered.
     #[arg(required = true)]
     source: PathBuf,
+    /// Fails unless the module was signed by one of the trusted keys.
+    #[arg(long = "require-signature", requires = "trusted_keys")]
+    require_signature: bool,
+    #[command(flatten)]
+    trust: TrustArguments,
 }
 
 /// Executes the [Validate](crate::Commands::Validate) command.
-pub fn run(ValidateArguments { source }: ValidateArguments) -> Result<(), Box<dyn Error>> {
+pub fn run(
+    ValidateArguments {
+        source,
+        require_signature,
+        trust,
+    }: ValidateArguments,
+) -> Result<(), Box<dyn Error>> {
     info!("Starting to validate the module");
-    let file = File::open(source)?;
-    let reader = BufReader::new(file);
-    let module: Module = serde_json::from_reader(reader)?;
+    if require_signature {
+        info!("Validating module signature");
+        check_signature(&source, &trust)?;
+    }
+    let module = read_module(&source)?;
     let module_value = serde_json::to_value(&module)?;
-    debug!("Loaded module from file correctly: {:#?}", module);
     validate_module_schema(&module_value)?;
+    validate_references(&module);
     validate_contents(module)?;
     info!("Module validated!");
     Ok(())
@@ -57,6 +72,18 @@ fn validate_contents(module: Module) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[instrument(skip(module))]
+fn validate_references(module: &Module) {
+    info!("Validating references between contents");
+    let problems = module.check_references();
+    for problem in &problems {
+        error!("Reference error: {}", problem);
+    }
+    if problems.is_empty() {
+        debug!("References passed validation");
+    }
+}
+
 #[instrument(skip(content, type_schema))]
 fn validate_content(
     content_id: String,
@@ -0,0 +1,68 @@
+use clap::Args;
+use module::integrity::Integrity;
+use std::{error::Error, fs, path::PathBuf};
+use thiserror::Error;
+use tracing::{debug, error, info, instrument};
+
+use crate::build::{build_module, BuildOptions};
+use crate::module_file::read_module;
+
+/// Checks that a built module matches it's sources, by rebuilding it.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct VerifyArguments {
+    /// The path to the directory the module was built from.
+    #[arg(required = true)]
+    source: PathBuf,
+    /// The path to the built module.
+    #[arg(required = true)]
+    module: PathBuf,
+    /// The path to an integrity file written by `build --integrity`,
+    /// which must also match the sources.
+    #[arg(short = 'i', long = "integrity")]
+    integrity_file: Option<PathBuf>,
+    /// The options the module was built with.
+    #[command(flatten)]
+    options: BuildOptions,
+}
+
+/// The errors that can happen when verifying a Module.
+#[derive(Error, Debug)]
+pub enum VerifyError {
+    #[error("found {0} differences between the sources and the built module")]
+    Mismatch(usize),
+}
+
+/// Executes the [Verify](crate::Commands::Verify) command.
+#[instrument]
+pub fn run(
+    VerifyArguments {
+        source,
+        module,
+        integrity_file,
+        options,
+    }: VerifyArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to verify the module");
+    let expected = Integrity::of(&build_module(source, &options)?);
+    debug!("Rebuilt module from sources: {:#?}", expected);
+    let mismatches = Integrity::of(&read_module(&module)?).compare(&expected);
+    for mismatch in &mismatches {
+        error!("Integrity error in module: {}", mismatch);
+    }
+    let mut count = mismatches.len();
+    if let Some(integrity_file) = integrity_file {
+        let integrity: Integrity = serde_json::from_slice(&fs::read(integrity_file)?)?;
+        let mismatches = integrity.compare(&expected);
+        for mismatch in &mismatches {
+            error!("Integrity error in integrity file: {}", mismatch);
+        }
+        count += mismatches.len();
+    }
+    if count > 0 {
+        return Err(Box::new(VerifyError::Mismatch(count)));
+    }
+    println!("The module matches it's sources.");
+    info!("Done!");
+    Ok(())
+}
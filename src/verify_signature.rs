@@ -0,0 +1,73 @@
+use clap::Args;
+use module::signature::{read_verifying_key, verify_signature, ModuleSignature};
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, info, instrument};
+
+/// Checks that a built module was signed by a trusted key.
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct VerifySignatureArguments {
+    /// The path to the built module.
+    #[arg(required = true)]
+    module: PathBuf,
+    #[command(flatten)]
+    trust: TrustArguments,
+}
+
+/// The signature of a module, and the keys that are trusted to sign it.
+#[derive(Debug, Args)]
+pub struct TrustArguments {
+    /// The path of the signature file. Defaults to the module path followed by `.sig`.
+    #[arg(short = 's', long = "signature")]
+    signature_file: Option<PathBuf>,
+    /// The path to an Ed25519 public key, as a PEM file, that is trusted to sign modules.
+    #[arg(long = "trusted-key")]
+    trusted_keys: Vec<PathBuf>,
+}
+
+/// Executes the [VerifySignature](crate::Commands::VerifySignature) command.
+#[instrument]
+pub fn run(
+    VerifySignatureArguments { module, trust }: VerifySignatureArguments,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting to verify the signature");
+    check_signature(&module, &trust)?;
+    println!("The module was signed by a trusted key.");
+    info!("Done!");
+    Ok(())
+}
+
+/// The signature file of a module, when none is provided.
+pub(crate) fn default_signature_file(module_path: &Path) -> PathBuf {
+    let mut result = module_path.as_os_str().to_owned();
+    result.push(".sig");
+    result.into()
+}
+
+/// Checks the signature of a module file against the trusted keys.
+///
+/// The file is checked as it is stored, before it is read into a module.
+#[instrument]
+pub(crate) fn check_signature(
+    module_path: &Path,
+    TrustArguments {
+        signature_file,
+        trusted_keys,
+    }: &TrustArguments,
+) -> Result<(), Box<dyn Error>> {
+    let signature_file = signature_file
+        .clone()
+        .unwrap_or_else(|| default_signature_file(module_path));
+    debug!("Reading signature from {:?}", signature_file);
+    let signature: ModuleSignature = serde_json::from_slice(&fs::read(signature_file)?)?;
+    let trusted_keys = trusted_keys
+        .iter()
+        .map(|path| Ok(read_verifying_key(&fs::read_to_string(path)?)?))
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    verify_signature(&fs::read(module_path)?, &signature, &trusted_keys)?;
+    Ok(())
+}